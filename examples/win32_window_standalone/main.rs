@@ -1,3 +1,10 @@
+// The bindings are declared right here, so only Windows can link them. Elsewhere the example
+// just says so.
+#![cfg_attr(not(windows), allow(dead_code, unused_imports))]
+// Everything keeps the names and shapes of the C headers, on purpose.
+#![allow(non_snake_case, unused_attributes, clippy::upper_case_acronyms, clippy::missing_safety_doc)]
+#![allow(clippy::field_reassign_with_default)]
+
 // We need void pointers often due C using them
 use core::ptr::{null, null_mut};
 
@@ -137,6 +144,7 @@ pub struct CREATESTRUCTW {
 //A bunch of C functions defined in various header files, any new type aliases are kept here with them.
 type DWORD = CUlong;
 type CUlong = u32;
+#[cfg(windows)]
 #[link(name = "Kernel32")]
 extern "system" {
     /// [`GetModuleHandleW`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulehandlew)
@@ -158,6 +166,7 @@ type WORD = CUshort;
 type CUshort = u16;
 type HMENU = HANDLE;
 type LPVOID = *mut core::ffi::c_void;
+#[cfg(windows)]
 #[link(name = "User32")]
 extern "system" {
     /// [`RegisterClassW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerclassw)
@@ -191,6 +200,7 @@ extern "system" {
 
 const SW_SHOW: CInt = 5;
 type BOOL = CInt;
+#[cfg(windows)]
 #[link(name = "User32")]
 extern "system" {
     /// [`ShowWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showwindow)
@@ -256,6 +266,7 @@ pub fn wide_null(s: &str) -> Vec<u16> {
 // This function defines all events which the generated window will respond to.
 // If the code given to us through Msg is not defined and included in the match
 // statement, the window will continue its default procedure instead.
+#[cfg(windows)]
 pub unsafe extern "system" fn window_procedure(
     h_wnd: HWND,
     msg: UINT,
@@ -301,6 +312,12 @@ unsafe_impl_default_zeroed!(RECT);
 unsafe_impl_default_zeroed!(POINT);
 unsafe_impl_default_zeroed!(CREATESTRUCTW);
 
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("The standalone Win32 window example only runs on Windows.");
+}
+
+#[cfg(windows)]
 fn main() {
     let h_instance = unsafe { GetModuleHandleW(null()) };
     let sample_window_class_wn = wide_null("Sample Window Class");
@@ -0,0 +1,410 @@
+//! Keyboard shortcuts written as text, like `"Ctrl+Shift+S"`.
+//!
+//! Windows turns key presses into `WM_COMMAND` through an accelerator table, a list of `ACCEL`
+//! entries of modifier flags, a virtual key and a command id. [`Shortcut`] parses and prints the
+//! usual notation, [`Accelerators`] collects the bindings of a window and refuses ones that clash,
+//! and on Windows an `AcceleratorTable` is made from them for the message loop.
+//!
+//! The command ids are the same ones menus use, see [`crate::menu`], so a shortcut and a menu
+//! item can share a command.
+
+use core::fmt;
+use core::str::FromStr;
+
+/// `ACCEL::fVirt` flags.
+pub const FVIRTKEY: u8 = 0x01;
+pub const FSHIFT: u8 = 0x04;
+pub const FCONTROL: u8 = 0x08;
+pub const FALT: u8 = 0x10;
+
+/// Virtual keys with a name, the first name of a key is the one we print.
+const NAMED_KEYS: [(&str, u16); 26] = [
+    ("Backspace", 0x08),
+    ("Tab", 0x09),
+    ("Enter", 0x0D),
+    ("Return", 0x0D),
+    ("Pause", 0x13),
+    ("Esc", 0x1B),
+    ("Escape", 0x1B),
+    ("Space", 0x20),
+    ("PageUp", 0x21),
+    ("PgUp", 0x21),
+    ("PageDown", 0x22),
+    ("PgDn", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("Insert", 0x2D),
+    ("Ins", 0x2D),
+    ("Delete", 0x2E),
+    ("Del", 0x2E),
+    ("Plus", 0xBB),
+    ("Comma", 0xBC),
+    ("Minus", 0xBD),
+    ("Period", 0xBE),
+];
+
+const VK_F1: u16 = 0x70;
+
+/// Why a shortcut did not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseShortcutError {
+    Empty,
+    /// Only modifiers, like `"Ctrl+Shift"`.
+    MissingKey,
+    UnknownKey(String),
+    DuplicateModifier(String),
+    /// Two keys that aren't modifiers, like `"Ctrl+A+B"`.
+    TooManyKeys(String),
+}
+
+impl fmt::Display for ParseShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseShortcutError::Empty => write!(f, "the shortcut is empty"),
+            ParseShortcutError::MissingKey => write!(f, "the shortcut has modifiers but no key"),
+            ParseShortcutError::UnknownKey(key) => write!(f, "unknown key {:?}", key),
+            ParseShortcutError::DuplicateModifier(modifier) => write!(f, "{} is given twice", modifier),
+            ParseShortcutError::TooManyKeys(key) => write!(f, "a shortcut has one key, {:?} is a second one", key),
+        }
+    }
+}
+
+impl std::error::Error for ParseShortcutError {}
+
+/// A key with modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    /// The virtual key code.
+    pub key: u16,
+}
+
+fn parse_key(name: &str) -> Option<u16> {
+    let upper = name.to_ascii_uppercase();
+    match upper.as_bytes() {
+        [c @ (b'A'..=b'Z' | b'0'..=b'9')] => return Some(u16::from(*c)),
+        [b'+'] => return Some(0xBB),
+        [b','] => return Some(0xBC),
+        [b'-'] => return Some(0xBD),
+        [b'.'] => return Some(0xBE),
+        [b'F', digits @ ..] if !digits.is_empty() => {
+            if let Ok(n @ 1..=24) = upper[1..].parse::<u16>() {
+                return Some(VK_F1 + n - 1);
+            }
+        }
+        _ => (),
+    }
+    NAMED_KEYS.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|&(_, vk)| vk)
+}
+
+impl Shortcut {
+    pub const fn new(key: u16) -> Self {
+        Self { ctrl: false, shift: false, alt: false, key }
+    }
+
+    pub const fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub const fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// The `ACCEL::fVirt` of this shortcut.
+    pub const fn flags(self) -> u8 {
+        let mut flags = FVIRTKEY;
+        if self.ctrl {
+            flags |= FCONTROL;
+        }
+        if self.shift {
+            flags |= FSHIFT;
+        }
+        if self.alt {
+            flags |= FALT;
+        }
+        flags
+    }
+
+    /// Whether this key, pressed without Ctrl or Alt, is something the user types.
+    pub fn is_typing(self) -> bool {
+        !self.ctrl && !self.alt && matches!(self.key, 0x20 | 0x30..=0x39 | 0x41..=0x5A | 0xBB..=0xBE)
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = ParseShortcutError;
+
+    /// Parses `Ctrl`, `Shift` and `Alt` in any order and case, followed by one key: a letter,
+    /// digit, `F1` to `F24`, a named key like `PageUp` or one of `+ , - .`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ParseShortcutError::Empty);
+        }
+        // `Ctrl++` ends in the plus key, every other `+` separates.
+        let (modifiers, last) = match text.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+        let mut shortcut = Shortcut::new(0);
+        let parts = modifiers.split('+').filter(|part| !modifiers.is_empty() || !part.is_empty());
+        for part in parts.chain([last]).map(str::trim) {
+            let flag = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut shortcut.ctrl,
+                "shift" => &mut shortcut.shift,
+                "alt" => &mut shortcut.alt,
+                _ if shortcut.key != 0 => return Err(ParseShortcutError::TooManyKeys(part.to_owned())),
+                _ => {
+                    shortcut.key = parse_key(part).ok_or_else(|| ParseShortcutError::UnknownKey(part.to_owned()))?;
+                    continue;
+                }
+            };
+            if *flag {
+                return Err(ParseShortcutError::DuplicateModifier(part.to_owned()));
+            }
+            *flag = true;
+        }
+        if shortcut.key == 0 {
+            return Err(ParseShortcutError::MissingKey);
+        }
+        Ok(shortcut)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (held, name) in [(self.ctrl, "Ctrl+"), (self.shift, "Shift+"), (self.alt, "Alt+")] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        match self.key {
+            0x30..=0x39 | 0x41..=0x5A => write!(f, "{}", self.key as u8 as char),
+            key @ VK_F1..=0x87 => write!(f, "F{}", key - VK_F1 + 1),
+            key => match NAMED_KEYS.iter().find(|&&(_, vk)| vk == key) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:#04X}", key),
+            },
+        }
+    }
+}
+
+/// One binding, the content of an `ACCEL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accel {
+    pub shortcut: Shortcut,
+    pub command: u16,
+}
+
+/// Why a binding was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AcceleratorError {
+    Parse { text: String, error: ParseShortcutError },
+    /// The shortcut already runs another command.
+    Conflict { shortcut: Shortcut, existing: u16, command: u16 },
+    /// The shortcut would swallow a key the user types, like `Shift+A`.
+    Typing(Shortcut),
+}
+
+impl fmt::Display for AcceleratorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AcceleratorError::Parse { text, error } => write!(f, "{:?}: {}", text, error),
+            AcceleratorError::Conflict { shortcut, existing, command } => {
+                write!(f, "{} already runs command {}, not {}", shortcut, existing, command)
+            }
+            AcceleratorError::Typing(shortcut) => write!(f, "{} is needed for typing", shortcut),
+        }
+    }
+}
+
+impl std::error::Error for AcceleratorError {}
+
+/// The shortcuts of a window, at most one command each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accelerators {
+    entries: Vec<Accel>,
+}
+
+impl Accelerators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `shortcut` and binds it, see [`Accelerators::bind`].
+    pub fn add(&mut self, shortcut: &str, command: u16) -> Result<(), AcceleratorError> {
+        let parsed = shortcut
+            .parse()
+            .map_err(|error| AcceleratorError::Parse { text: shortcut.to_owned(), error })?;
+        self.bind(parsed, command)
+    }
+
+    /// Binds `shortcut` to `command`. Binding the same pair twice is fine, giving a shortcut a
+    /// second command is a conflict.
+    pub fn bind(&mut self, shortcut: Shortcut, command: u16) -> Result<(), AcceleratorError> {
+        if shortcut.is_typing() {
+            return Err(AcceleratorError::Typing(shortcut));
+        }
+        match self.command_for(shortcut) {
+            Some(existing) if existing == command => Ok(()),
+            Some(existing) => Err(AcceleratorError::Conflict { shortcut, existing, command }),
+            None => {
+                self.entries.push(Accel { shortcut, command });
+                Ok(())
+            }
+        }
+    }
+
+    pub fn command_for(&self, shortcut: Shortcut) -> Option<u16> {
+        self.entries.iter().find(|accel| accel.shortcut == shortcut).map(|accel| accel.command)
+    }
+
+    /// The shortcuts of `command`, for showing them next to menu items.
+    pub fn shortcuts_for(&self, command: u16) -> impl Iterator<Item = Shortcut> + '_ {
+        self.entries.iter().filter(move |accel| accel.command == command).map(|accel| accel.shortcut)
+    }
+
+    pub fn entries(&self) -> &[Accel] {
+        &self.entries
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{Accel, Accelerators};
+    use crate::win32::*;
+
+    impl From<Accel> for ACCEL {
+        fn from(accel: Accel) -> Self {
+            ACCEL { f_virt: accel.shortcut.flags(), key: accel.shortcut.key, cmd: accel.command }
+        }
+    }
+
+    /// An accelerator table, destroyed on drop.
+    #[derive(Debug)]
+    pub struct AcceleratorTable(HACCEL);
+
+    impl AcceleratorTable {
+        /// See [`CreateAcceleratorTableW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createacceleratortablew)
+        pub fn new(accelerators: &Accelerators) -> Result<Self, Win32Error> {
+            let entries: Vec<ACCEL> = accelerators.entries().iter().map(|&accel| accel.into()).collect();
+            let table = unsafe { CreateAcceleratorTableW(entries.as_ptr(), entries.len() as CInt) };
+            if table.is_null() {
+                Err(get_last_error())
+            } else {
+                Ok(Self(table))
+            }
+        }
+
+        pub fn handle(&self) -> HACCEL {
+            self.0
+        }
+
+        /// Sends `WM_COMMAND` to `hwnd` if `msg` is one of our shortcuts, see
+        /// [`TranslateAcceleratorW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-translateacceleratorw)
+        ///
+        /// Returns whether it did, in which case the message must not be dispatched as well.
+        ///
+        /// # Safety
+        /// `hwnd` must be a window handle,
+        /// [`run_message_loop_with_accelerators`](crate::window::run_message_loop_with_accelerators) is
+        /// the safe way.
+        pub unsafe fn translate(&self, hwnd: HWND, msg: &MSG) -> bool {
+            TranslateAcceleratorW(hwnd, self.0, msg) != 0
+        }
+    }
+
+    impl Drop for AcceleratorTable {
+        fn drop(&mut self) {
+            unsafe { DestroyAcceleratorTable(self.0) };
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Shortcut, ParseShortcutError> {
+        text.parse()
+    }
+
+    #[test]
+    fn parses_modifiers_in_any_order() {
+        let save_as = Shortcut::new(u16::from(b'S')).ctrl().shift();
+        assert_eq!(parse("Ctrl+Shift+S"), Ok(save_as));
+        assert_eq!(parse("shift + control + s"), Ok(save_as));
+        assert_eq!(save_as.flags(), FVIRTKEY | FCONTROL | FSHIFT);
+        assert_eq!(parse("Alt+F4"), Ok(Shortcut::new(0x73).alt()));
+        assert_eq!(parse("F24"), Ok(Shortcut::new(0x87)));
+        assert_eq!(parse("Ctrl+PgDn"), Ok(Shortcut::new(0x22).ctrl()));
+        assert_eq!(parse("Ctrl++"), Ok(Shortcut::new(0xBB).ctrl()));
+        assert_eq!(parse("Ctrl+-"), Ok(Shortcut::new(0xBD).ctrl()));
+        assert_eq!(parse("Esc"), Ok(Shortcut::new(0x1B)));
+    }
+
+    #[test]
+    fn display_is_canonical() {
+        for (text, shown) in [
+            ("shift+ctrl+s", "Ctrl+Shift+S"),
+            ("alt+f12", "Alt+F12"),
+            ("Ctrl+Return", "Ctrl+Enter"),
+            ("Ctrl++", "Ctrl+Plus"),
+            ("Ctrl+Alt+Del", "Ctrl+Alt+Delete"),
+        ] {
+            let shortcut = parse(text).unwrap();
+            assert_eq!(shortcut.to_string(), shown);
+            assert_eq!(parse(shown), Ok(shortcut));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_shortcuts() {
+        assert_eq!(parse(""), Err(ParseShortcutError::Empty));
+        assert_eq!(parse("  "), Err(ParseShortcutError::Empty));
+        assert_eq!(parse("Ctrl+Shift"), Err(ParseShortcutError::MissingKey));
+        assert_eq!(parse("Ctrl+"), Err(ParseShortcutError::UnknownKey(String::new())));
+        assert_eq!(parse("Ctrl+Ctrl+S"), Err(ParseShortcutError::DuplicateModifier("Ctrl".into())));
+        assert_eq!(parse("Ctrl+A+B"), Err(ParseShortcutError::TooManyKeys("B".into())));
+        assert_eq!(parse("Ctrl+F25"), Err(ParseShortcutError::UnknownKey("F25".into())));
+        assert_eq!(parse("Meta+S"), Err(ParseShortcutError::UnknownKey("Meta".into())));
+        assert_eq!(parse("Hyper"), Err(ParseShortcutError::UnknownKey("Hyper".into())));
+    }
+
+    #[test]
+    fn conflicts_are_refused() {
+        let mut accelerators = Accelerators::new();
+        accelerators.add("Ctrl+S", 100).unwrap();
+        accelerators.add("Ctrl+Shift+S", 101).unwrap();
+        // Same binding again, written differently.
+        accelerators.add("control+s", 100).unwrap();
+        accelerators.add("F12", 101).unwrap();
+        let save = parse("Ctrl+S").unwrap();
+        assert_eq!(
+            accelerators.add("Ctrl+S", 102),
+            Err(AcceleratorError::Conflict { shortcut: save, existing: 100, command: 102 })
+        );
+        assert_eq!(accelerators.add("Shift+A", 103), Err(AcceleratorError::Typing(parse("Shift+A").unwrap())));
+        assert!(matches!(accelerators.add("Ctrl+Nope", 104), Err(AcceleratorError::Parse { .. })));
+        assert_eq!(accelerators.entries().len(), 3);
+        assert_eq!(accelerators.command_for(save), Some(100));
+        let shortcuts: Vec<String> = accelerators.shortcuts_for(101).map(|s| s.to_string()).collect();
+        assert_eq!(shortcuts, ["Ctrl+Shift+S", "F12"]);
+    }
+}
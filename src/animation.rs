@@ -0,0 +1,535 @@
+//! Tweens, easing and keyframes, and an [`Animator`] that keeps repainting only while something
+//! moves.
+//!
+//! Windows has no frame callback for plain GDI windows. Instead the animator asks for a repaint
+//! (`InvalidateRect`, see `Window::invalidate` on Windows) when an animation starts, and every
+//! `WM_PAINT` calls [`Animator::tick`] after `BeginPaint` and before drawing. The tick applies
+//! the current values and asks for the next repaint only if an animation is still running, so an
+//! idle window costs nothing.
+//!
+//! Ticking before `BeginPaint` doesn't work: `BeginPaint` validates the whole update region,
+//! including the repaint the tick just asked for, and the animation stops after one frame.
+//!
+//! Time comes from a [`Clock`], which tests replace with a [`MockClock`].
+
+use core::cell::Cell;
+use core::time::Duration;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::canvas::Vertex;
+use crate::color::Color;
+use crate::geometry::Point;
+use crate::image::Rgba;
+use crate::math3d::{Vec3, Vec4};
+
+/// Where the animator gets the time from.
+pub trait Clock {
+    /// Time since some fixed point, only differences matter.
+    fn now(&self) -> Duration;
+}
+
+/// The real time.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// A clock that only moves when told to, clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, now: Duration) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// How progress from 0 to 1 is reshaped, see <https://easings.net>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    /// Jumps to the end value once the time is up.
+    Step,
+}
+
+impl Easing {
+    /// Eases `t`, which is clamped to 0 to 1 first.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::SineInOut => -((core::f32::consts::PI * t).cos() - 1.0) / 2.0,
+            Easing::Step => (t >= 1.0) as u32 as f32,
+        }
+    }
+}
+
+/// Values that can be blended, `t` = 0 gives `self` and 1 gives `other`.
+pub trait Lerp {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for i32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*other as f32), t).round() as i32
+    }
+}
+
+impl Lerp for u8 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*other as f32), t).round().clamp(0.0, 255.0) as u8
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Point::new(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+    }
+}
+
+impl Lerp for Rgba {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Rgba::new(self.r.lerp(&other.r, t), self.g.lerp(&other.g, t), self.b.lerp(&other.b, t), self.a.lerp(&other.a, t))
+    }
+}
+
+impl Lerp for Color {
+    /// Blends the sRGB values, like `GradientFill` does.
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Color::new(self.r.lerp(&other.r, t), self.g.lerp(&other.g, t), self.b.lerp(&other.b, t), self.a.lerp(&other.a, t))
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Lerp for Vec4 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Vec4::lerp(*self, *other, t)
+    }
+}
+
+impl Lerp for Vertex {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Vertex { position: self.position.lerp(&other.position, t), color: self.color.lerp(&other.color, t) }
+    }
+}
+
+/// Something that has a value at every point in time.
+pub trait Track {
+    type Value;
+
+    /// The value `elapsed` after the start.
+    fn sample(&self, elapsed: Duration) -> Self::Value;
+
+    /// When the value stops changing, `None` if it never does.
+    fn end(&self) -> Option<Duration>;
+}
+
+/// How far `elapsed` is through `duration`, an empty duration is immediately done.
+fn progress(elapsed: Duration, duration: Duration) -> f32 {
+    if duration.is_zero() {
+        1.0
+    } else {
+        elapsed.as_secs_f32() / duration.as_secs_f32()
+    }
+}
+
+/// Moves from one value to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl<T> Tween<T> {
+    /// A linear tween.
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self { from, to, duration, easing: Easing::Linear }
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+impl<T: Lerp> Track for Tween<T> {
+    type Value = T;
+
+    fn sample(&self, elapsed: Duration) -> T {
+        self.from.lerp(&self.to, self.easing.apply(progress(elapsed, self.duration)))
+    }
+
+    fn end(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+}
+
+/// What keyframes do after the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Repeat {
+    /// Stay on the last value.
+    #[default]
+    Once,
+    /// Start over from the first keyframe.
+    Loop,
+    /// Play backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+/// A value at a point in time, reached from the previous keyframe with `easing`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: Duration,
+    pub value: T,
+    pub easing: Easing,
+}
+
+/// A value that passes through several keyframes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes<T> {
+    frames: Vec<Keyframe<T>>,
+    repeat: Repeat,
+}
+
+impl<T> Keyframes<T> {
+    /// Starts at `value` at time zero.
+    pub fn new(value: T) -> Self {
+        Self { frames: vec![Keyframe { time: Duration::ZERO, value, easing: Easing::Linear }], repeat: Repeat::Once }
+    }
+
+    /// Adds a keyframe at `time` after the start, keyframes may be added in any order.
+    pub fn key(mut self, time: Duration, value: T, easing: Easing) -> Self {
+        let index = self.frames.partition_point(|frame| frame.time <= time);
+        self.frames.insert(index, Keyframe { time, value, easing });
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn frames(&self) -> &[Keyframe<T>] {
+        &self.frames
+    }
+
+    /// The time of the last keyframe.
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map_or(Duration::ZERO, |frame| frame.time)
+    }
+}
+
+impl<T: Lerp + Clone> Track for Keyframes<T> {
+    type Value = T;
+
+    fn sample(&self, elapsed: Duration) -> T {
+        let duration = self.duration();
+        let time = match self.repeat {
+            _ if duration.is_zero() => elapsed,
+            Repeat::Once => elapsed,
+            Repeat::Loop => Duration::from_nanos((elapsed.as_nanos() % duration.as_nanos()) as u64),
+            Repeat::PingPong => {
+                let cycle = (elapsed.as_nanos() % (2 * duration.as_nanos())) as u64;
+                let forwards = Duration::from_nanos(cycle);
+                if forwards <= duration {
+                    forwards
+                } else {
+                    2 * duration - forwards
+                }
+            }
+        };
+        // The first keyframe after `time`, everything before it has been passed.
+        let next = self.frames.partition_point(|frame| frame.time <= time);
+        if next == 0 {
+            return self.frames[0].value.clone();
+        }
+        let Some(to) = self.frames.get(next) else {
+            return self.frames[next - 1].value.clone();
+        };
+        let from = &self.frames[next - 1];
+        let t = to.easing.apply(progress(time - from.time, to.time - from.time));
+        from.value.lerp(&to.value, t)
+    }
+
+    fn end(&self) -> Option<Duration> {
+        match self.repeat {
+            Repeat::Once => Some(self.duration()),
+            Repeat::Loop | Repeat::PingPong => None,
+        }
+    }
+}
+
+/// Identifies a running animation, for [`Animator::stop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnimationId(u64);
+
+/// Samples the track and hands the value on, returns whether the animation is over.
+type Step = Box<dyn FnMut(Duration) -> bool>;
+
+struct Running {
+    id: AnimationId,
+    start: Duration,
+    step: Step,
+}
+
+/// Runs animations and asks for repaints while any are running.
+pub struct Animator<C: Clock = SystemClock> {
+    clock: C,
+    running: Vec<Running>,
+    next_id: u64,
+    repaint: Option<Box<dyn Fn()>>,
+}
+
+impl Animator<SystemClock> {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::new())
+    }
+}
+
+impl Default for Animator<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> Animator<C> {
+    pub fn with_clock(clock: C) -> Self {
+        Self { clock, running: Vec::new(), next_id: 0, repaint: None }
+    }
+
+    /// Called whenever another frame is needed, usually invalidates the window.
+    pub fn on_repaint(mut self, repaint: impl Fn() + 'static) -> Self {
+        self.repaint = Some(Box::new(repaint));
+        self
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    fn request_repaint(&self) {
+        if let Some(repaint) = &self.repaint {
+            repaint();
+        }
+    }
+
+    /// Starts `track` now, `apply` receives its value on every [`Animator::tick`].
+    pub fn play<T>(&mut self, track: T, mut apply: impl FnMut(T::Value) + 'static) -> AnimationId
+    where
+        T: Track + 'static,
+    {
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        let step = Box::new(move |elapsed: Duration| {
+            apply(track.sample(elapsed));
+            track.end().is_some_and(|end| elapsed >= end)
+        });
+        self.running.push(Running { id, start: self.clock.now(), step });
+        self.request_repaint();
+        id
+    }
+
+    /// Stops an animation where it is, returns whether it was still running.
+    pub fn stop(&mut self, id: AnimationId) -> bool {
+        let before = self.running.len();
+        self.running.retain(|running| running.id != id);
+        self.running.len() != before
+    }
+
+    pub fn stop_all(&mut self) {
+        self.running.clear();
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Applies the current value of every animation, call this in `WM_PAINT` between `BeginPaint`
+    /// and drawing the frame.
+    ///
+    /// Finished animations get their final value and are dropped. Returns whether any are still
+    /// running, in which case the next repaint has already been requested.
+    pub fn tick(&mut self) -> bool {
+        let now = self.clock.now();
+        self.running.retain_mut(|running| !(running.step)(now.saturating_sub(running.start)));
+        let running = self.is_running();
+        if running {
+            self.request_repaint();
+        }
+        running
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn easings_start_and_end_in_place_and_never_go_back() {
+        use Easing::*;
+        for easing in [Linear, QuadIn, QuadOut, QuadInOut, CubicIn, CubicOut, CubicInOut, SineInOut] {
+            assert_eq!(easing.apply(0.0), 0.0, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+            let samples: Vec<f32> = (0..=100).map(|i| easing.apply(i as f32 / 100.0)).collect();
+            assert!(samples.windows(2).all(|pair| pair[0] <= pair[1] + 1e-6), "{:?}", easing);
+        }
+        assert!(QuadIn.apply(0.5) < 0.5 && QuadOut.apply(0.5) > 0.5);
+        assert!((CubicInOut.apply(0.5) - 0.5).abs() < 1e-6);
+        assert_eq!((Step.apply(0.99), Step.apply(1.0)), (0.0, 1.0));
+        assert_eq!(Linear.apply(2.0), 1.0);
+    }
+
+    #[test]
+    fn tweens_blend_between_their_ends() {
+        let tween = Tween::new(Point::new(0, 0), Point::new(100, -50), ms(1000));
+        assert_eq!(tween.sample(ms(0)), Point::new(0, 0));
+        assert_eq!(tween.sample(ms(250)), Point::new(25, -13));
+        assert_eq!(tween.sample(ms(5000)), Point::new(100, -50));
+        let eased = Tween::new(0.0_f32, 1.0, ms(1000)).easing(Easing::QuadIn);
+        assert_eq!(eased.sample(ms(500)), 0.25);
+        let color = Tween::new(Rgba::opaque(0, 0, 0), Rgba::opaque(255, 255, 255), ms(10));
+        assert_eq!(color.sample(ms(5)), Rgba::opaque(128, 128, 128));
+        assert_eq!(Tween::new(1.0_f32, 2.0, Duration::ZERO).sample(ms(0)), 2.0);
+    }
+
+    #[test]
+    fn keyframes_pass_through_every_key() {
+        let keys = Keyframes::new(0.0_f32)
+            .key(ms(200), 10.0, Easing::Linear)
+            .key(ms(100), 20.0, Easing::Linear)
+            .key(ms(300), 0.0, Easing::Step);
+        assert_eq!(keys.frames().iter().map(|f| f.value).collect::<Vec<_>>(), [0.0, 20.0, 10.0, 0.0]);
+        assert_eq!(keys.sample(ms(50)), 10.0);
+        assert_eq!(keys.sample(ms(100)), 20.0);
+        assert_eq!(keys.sample(ms(150)), 15.0);
+        // Step holds until the key is reached.
+        assert_eq!(keys.sample(ms(299)), 10.0);
+        assert_eq!(keys.sample(ms(300)), 0.0);
+        assert_eq!(keys.sample(ms(1000)), 0.0);
+        assert_eq!(keys.end(), Some(ms(300)));
+    }
+
+    #[test]
+    fn keyframes_repeat() {
+        let keys = Keyframes::new(0.0_f32).key(ms(100), 100.0, Easing::Linear);
+        let looping = keys.clone().repeat(Repeat::Loop);
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(close(looping.sample(ms(130)), 30.0));
+        assert_eq!(looping.end(), None);
+        let ping_pong = keys.repeat(Repeat::PingPong);
+        assert!(close(ping_pong.sample(ms(130)), 70.0));
+        assert!(close(ping_pong.sample(ms(230)), 30.0));
+    }
+
+    #[test]
+    fn repaints_are_requested_only_while_running() {
+        let clock = MockClock::new();
+        let repaints = Rc::new(Cell::new(0));
+        let counter = repaints.clone();
+        let mut animator = Animator::with_clock(clock.clone()).on_repaint(move || counter.set(counter.get() + 1));
+        assert!(!animator.tick());
+        assert_eq!(repaints.get(), 0);
+
+        let value = Rc::new(RefCell::new(Vec::new()));
+        let seen = value.clone();
+        clock.set(ms(1000));
+        animator.play(Tween::new(0, 100, ms(100)), move |v| seen.borrow_mut().push(v));
+        assert_eq!(repaints.get(), 1);
+        clock.advance(ms(40));
+        assert!(animator.tick());
+        assert_eq!(repaints.get(), 2);
+        // A late frame still ends on the final value.
+        clock.advance(ms(500));
+        assert!(!animator.tick());
+        assert_eq!(repaints.get(), 2);
+        assert!(!animator.tick());
+        assert_eq!(*value.borrow(), [40, 100]);
+    }
+
+    #[test]
+    fn stopping_leaves_the_value_alone() {
+        let clock = MockClock::new();
+        let mut animator = Animator::with_clock(clock.clone());
+        let value = Rc::new(Cell::new(0.0_f32));
+        let target = value.clone();
+        let forever = Keyframes::new(0.0).key(ms(10), 1.0, Easing::Linear).repeat(Repeat::Loop);
+        let id = animator.play(forever, move |v| target.set(v));
+        clock.advance(ms(5));
+        assert!(animator.tick());
+        assert_eq!(value.get(), 0.5);
+        assert!(animator.stop(id));
+        assert!(!animator.stop(id));
+        clock.advance(ms(2));
+        assert!(!animator.tick());
+        assert_eq!(value.get(), 0.5);
+    }
+}
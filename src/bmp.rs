@@ -0,0 +1,209 @@
+//! Reading and writing `.bmp` files.
+//!
+//! A BMP file is a `BITMAPFILEHEADER` followed by the same `BITMAPINFOHEADER` and pixel rows GDI
+//! uses for DIBs, so the row packing comes straight from [`crate::dib`]. Only uncompressed 24 and
+//! 32 bit files are supported, which is all we write.
+
+use crate::dib::{dib_to_image, image_to_bgr, image_to_bgra, Orientation};
+use crate::image::Image;
+
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+/// The pixel format of a written file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    /// `B, G, R`, alpha is dropped. Every viewer understands this.
+    Bgr24,
+    /// `B, G, R, A`. Many viewers ignore the alpha byte of `BI_RGB` files, but we read it back.
+    Bgra32,
+}
+
+impl BitDepth {
+    pub const fn bits_per_pixel(self) -> u16 {
+        match self {
+            BitDepth::Bgr24 => 24,
+            BitDepth::Bgra32 => 32,
+        }
+    }
+
+    /// 32 bit if any pixel is not fully opaque, 24 bit otherwise.
+    pub fn for_image(image: &Image) -> Self {
+        if image.pixels().iter().all(|p| p.a == 255) {
+            BitDepth::Bgr24
+        } else {
+            BitDepth::Bgra32
+        }
+    }
+}
+
+/// Encodes `image` as a bottom-up `BI_RGB` bitmap, the most widely supported layout.
+pub fn encode(image: &Image, depth: BitDepth) -> Vec<u8> {
+    let dib = encode_dib(image, depth);
+    let offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+    let mut bytes = Vec::with_capacity(FILE_HEADER_SIZE + dib.len());
+    // BITMAPFILEHEADER
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&((FILE_HEADER_SIZE + dib.len()) as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+    bytes.extend_from_slice(&dib);
+    bytes
+}
+
+/// Encodes `image` like [`encode`] but without the file header, which is the layout of a packed
+/// DIB such as the clipboard's `CF_DIB`.
+pub fn encode_dib(image: &Image, depth: BitDepth) -> Vec<u8> {
+    let pixels = match depth {
+        BitDepth::Bgr24 => image_to_bgr(image, Orientation::BottomUp),
+        BitDepth::Bgra32 => image_to_bgra(image, Orientation::BottomUp),
+    };
+    let mut bytes = Vec::with_capacity(INFO_HEADER_SIZE + pixels.len());
+    // BITMAPINFOHEADER
+    bytes.extend_from_slice(&(INFO_HEADER_SIZE as u32).to_le_bytes());
+    bytes.extend_from_slice(&(image.width() as i32).to_le_bytes());
+    bytes.extend_from_slice(&Orientation::BottomUp.header_height(image.height()).to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&depth.bits_per_pixel().to_le_bytes());
+    bytes.extend_from_slice(&BI_RGB.to_le_bytes());
+    bytes.extend_from_slice(&(pixels.len() as u32).to_le_bytes());
+    // 2835 pixels per meter is 72 DPI.
+    bytes.extend_from_slice(&2835_i32.to_le_bytes());
+    bytes.extend_from_slice(&2835_i32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+    bytes.extend_from_slice(&pixels);
+    bytes
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Decodes an uncompressed 24 or 32 bit bitmap in either row order.
+///
+/// Returns `None` for anything else or if the file is cut short. 32 bit pixels keep their alpha
+/// byte, as written by [`encode`].
+pub fn decode(bytes: &[u8]) -> Option<Image> {
+    if bytes.get(..2)? != b"BM" {
+        return None;
+    }
+    let offset = read_u32(bytes, 10)? as usize;
+    let pixels = offset.checked_sub(FILE_HEADER_SIZE)?;
+    read_dib(bytes.get(FILE_HEADER_SIZE..)?, pixels)
+}
+
+/// Decodes a packed DIB: a `BITMAPINFOHEADER` (or one of its longer versions) directly followed
+/// by the pixel rows.
+///
+/// Besides `BI_RGB` this accepts `BI_BITFIELDS` with the usual `B, G, R` masks, which is how
+/// Windows often hands out 32 bit clipboard images. Alpha is kept as stored.
+pub fn decode_dib(bytes: &[u8]) -> Option<Image> {
+    let header_size = read_u32(bytes, 0)? as usize;
+    let compression = read_u32(bytes, 16)?;
+    // Version 1 headers keep the masks after the header, later versions inside it.
+    let masks = if compression == BI_BITFIELDS && header_size == INFO_HEADER_SIZE { 12 } else { 0 };
+    let colors = (read_u32(bytes, 32)? as usize).checked_mul(4)?;
+    read_dib(bytes, header_size.checked_add(masks)?.checked_add(colors)?)
+}
+
+/// Reads the header at the start of `bytes` and the pixels at `offset`.
+fn read_dib(bytes: &[u8], offset: usize) -> Option<Image> {
+    let header_size = read_u32(bytes, 0)? as usize;
+    if header_size < INFO_HEADER_SIZE {
+        return None;
+    }
+    let width = read_u32(bytes, 4)? as i32;
+    let height = read_u32(bytes, 8)? as i32;
+    let bits_per_pixel = read_u16(bytes, 14)?;
+    if width < 0 {
+        return None;
+    }
+    match read_u32(bytes, 16)? {
+        BI_RGB => {}
+        BI_BITFIELDS if bits_per_pixel == 32 => {
+            // Right after a version 1 header, or the same place inside the longer ones.
+            let at = INFO_HEADER_SIZE;
+            let masks = [read_u32(bytes, at)?, read_u32(bytes, at + 4)?, read_u32(bytes, at + 8)?];
+            if masks != [0x00FF_0000, 0x0000_FF00, 0x0000_00FF] {
+                return None;
+            }
+        }
+        _ => return None,
+    }
+    dib_to_image(
+        bytes.get(offset..)?,
+        width as u32,
+        height.unsigned_abs(),
+        bits_per_pixel,
+        Orientation::from_header_height(height),
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Rgba;
+
+    fn sample() -> Image {
+        Image::from_fn(5, 3, |x, y| Rgba::new(40 * x as u8, 80 * y as u8, 7, 255 - x as u8))
+    }
+
+    #[test]
+    fn header_fields() {
+        let bytes = encode(&sample(), BitDepth::Bgr24);
+        assert_eq!(&bytes[..2], b"BM");
+        assert_eq!(read_u32(&bytes, 2), Some(bytes.len() as u32));
+        assert_eq!(read_u32(&bytes, 10), Some(54));
+        assert_eq!(read_u16(&bytes, 28), Some(24));
+        // Rows of 5 * 3 bytes are padded to 16.
+        assert_eq!(bytes.len(), 54 + 16 * 3);
+    }
+
+    #[test]
+    fn round_trip() {
+        let image = sample();
+        assert_eq!(decode(&encode(&image, BitDepth::Bgra32)), Some(image.clone()));
+        let opaque = decode(&encode(&image, BitDepth::Bgr24)).unwrap();
+        assert!(opaque.pixels().iter().zip(image.pixels()).all(|(a, b)| a.rgb() == b.rgb() && a.a == 255));
+    }
+
+    #[test]
+    fn depth_follows_alpha() {
+        assert_eq!(BitDepth::for_image(&sample()), BitDepth::Bgra32);
+        assert_eq!(BitDepth::for_image(&Image::filled(2, 2, Rgba::WHITE)), BitDepth::Bgr24);
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let bytes = encode(&sample(), BitDepth::Bgr24);
+        assert_eq!(decode(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(decode(b"PNG"), None);
+        assert_eq!(decode(&[]), None);
+    }
+
+    #[test]
+    fn packed_dib() {
+        let image = sample();
+        let dib = encode_dib(&image, BitDepth::Bgra32);
+        assert_eq!(dib[..], encode(&image, BitDepth::Bgra32)[FILE_HEADER_SIZE..]);
+        assert_eq!(decode_dib(&dib), Some(image.clone()));
+
+        // The same pixels as BI_BITFIELDS, with the masks after a version 1 header.
+        let mut fields = dib[..INFO_HEADER_SIZE].to_vec();
+        fields[16..20].copy_from_slice(&BI_BITFIELDS.to_le_bytes());
+        for mask in [0x00FF_0000_u32, 0x0000_FF00, 0x0000_00FF] {
+            fields.extend_from_slice(&mask.to_le_bytes());
+        }
+        fields.extend_from_slice(&dib[INFO_HEADER_SIZE..]);
+        assert_eq!(decode_dib(&fields), Some(image));
+        fields[INFO_HEADER_SIZE] = 0x0F;
+        assert_eq!(decode_dib(&fields), None);
+    }
+}
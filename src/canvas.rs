@@ -0,0 +1,486 @@
+//! A small 2D drawing API with one implementation per backend.
+//!
+//! Painting code written against [`Canvas`] draws on screen through `GdiCanvas` and into an
+//! [`Image`] through [`ImageCanvas`], so what a window shows can also be rendered and checked in
+//! tests off Windows. The in-memory rasterizer follows GDI's rules where it is cheap to do so
+//! (exclusive right and bottom edges, `LineTo` not drawing its end point, alternate polygon fill)
+//! but does not aim to be pixel identical, in particular text uses the built-in [`crate::font`].
+
+use crate::error::Win32Error;
+use crate::font;
+use crate::geometry::{Point, Rect, Size};
+use crate::image::{Image, Rgba};
+use crate::palette::{Palette, SysColor};
+
+/// An outline drawn with a solid pen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stroke {
+    pub color: Rgba,
+    pub width: u32,
+}
+
+/// How a shape is filled and outlined, either part may be left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fill: Option<Rgba>,
+    pub stroke: Option<Stroke>,
+}
+
+impl Style {
+    pub const fn filled(color: Rgba) -> Self {
+        Self { fill: Some(color), stroke: None }
+    }
+
+    pub const fn outlined(color: Rgba, width: u32) -> Self {
+        Self { fill: None, stroke: Some(Stroke { color, width }) }
+    }
+
+    pub const fn with_fill(self, color: Rgba) -> Self {
+        Self { fill: Some(color), ..self }
+    }
+
+    pub const fn with_stroke(self, color: Rgba, width: u32) -> Self {
+        Self { stroke: Some(Stroke { color, width }), ..self }
+    }
+}
+
+/// A corner of a gradient filled triangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vertex {
+    pub position: Point,
+    pub color: Rgba,
+}
+
+impl Vertex {
+    pub const fn new(x: i32, y: i32, color: Rgba) -> Self {
+        Self { position: Point::new(x, y), color }
+    }
+}
+
+/// Something that can be drawn on.
+///
+/// Colors are written as they are, like GDI there is no alpha blending.
+pub trait Canvas {
+    /// The size of the drawable area.
+    fn size(&self) -> Size;
+
+    /// Fills `rect` with a system color, the way `WM_PAINT` handlers clear their background.
+    fn fill_sys_color(&mut self, rect: Rect, color: SysColor) -> Result<(), Win32Error>;
+
+    /// A line from `from` up to, but not including, `to`.
+    fn line(&mut self, from: Point, to: Point, stroke: Stroke) -> Result<(), Win32Error>;
+
+    fn rect(&mut self, rect: Rect, style: Style) -> Result<(), Win32Error>;
+
+    /// The ellipse inscribed in `bounds`.
+    fn ellipse(&mut self, bounds: Rect, style: Style) -> Result<(), Win32Error>;
+
+    /// A closed polygon, filled with the alternate (even-odd) rule.
+    fn polygon(&mut self, points: &[Point], style: Style) -> Result<(), Win32Error>;
+
+    /// A triangle with the vertex colors blended across it, like `GradientFill`.
+    fn gradient_triangle(&mut self, vertices: [Vertex; 3]) -> Result<(), Win32Error>;
+
+    /// Draws `text` with its top left corner at `origin`.
+    fn text(&mut self, origin: Point, text: &str, color: Rgba) -> Result<(), Win32Error>;
+
+    /// Fills the whole canvas.
+    fn clear(&mut self, color: Rgba) -> Result<(), Win32Error> {
+        let rect = Rect::from_origin_size(Point::default(), self.size());
+        self.rect(rect, Style::filled(color))
+    }
+}
+
+/// Draws into an in-memory [`Image`], system colors come from a [`Palette`].
+pub struct ImageCanvas<'a> {
+    image: &'a mut Image,
+    palette: Palette,
+}
+
+impl<'a> ImageCanvas<'a> {
+    /// A canvas using the default (light) palette.
+    pub fn new(image: &'a mut Image) -> Self {
+        Self::with_palette(image, Palette::default())
+    }
+
+    pub fn with_palette(image: &'a mut Image, palette: Palette) -> Self {
+        Self { image, palette }
+    }
+
+    pub fn image(&self) -> &Image {
+        self.image
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    fn plot(&mut self, x: i32, y: i32, color: Rgba) {
+        if x >= 0 && y >= 0 {
+            self.image.set(x as u32, y as u32, color);
+        }
+    }
+
+    /// The part of `rect` that lies on the image.
+    fn clip(&self, rect: Rect) -> Option<Rect> {
+        rect.intersect(&Rect::from_origin_size(Point::default(), self.size()))
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Rgba) {
+        if let Some(rect) = self.clip(rect) {
+            for y in rect.top..rect.bottom {
+                self.image.row_mut(y as u32)[rect.left as usize..rect.right as usize].fill(color);
+            }
+        }
+    }
+
+    /// Fills every pixel whose center passes `inside`, looking only within `bounds`.
+    fn fill_where(&mut self, bounds: Rect, color: Rgba, inside: impl Fn(f64, f64) -> bool) {
+        if let Some(bounds) = self.clip(bounds) {
+            for y in bounds.top..bounds.bottom {
+                for x in bounds.left..bounds.right {
+                    if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                        self.plot(x, y, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A square pen tip of `stroke.width` pixels centered on `(x, y)`.
+    fn stamp(&mut self, x: i32, y: i32, stroke: Stroke) {
+        let width = stroke.width.clamp(1, i32::MAX as u32) as i32;
+        let (left, top) = (x.saturating_sub((width - 1) / 2), y.saturating_sub((width - 1) / 2));
+        let rect = Rect::new(left, top, left.saturating_add(width), top.saturating_add(width));
+        self.fill_rect(rect, stroke.color);
+    }
+}
+
+/// Runs `paint` on a transparent `size` image and returns what it drew, the offscreen twin of
+/// taking a screenshot after `WM_PAINT`.
+pub fn render(
+    size: Size,
+    palette: Palette,
+    paint: impl FnOnce(&mut ImageCanvas<'_>) -> Result<(), Win32Error>,
+) -> Result<Image, Win32Error> {
+    let mut image = Image::new(size.width.max(0) as u32, size.height.max(0) as u32);
+    paint(&mut ImageCanvas::with_palette(&mut image, palette))?;
+    Ok(image)
+}
+
+/// The pixels Bresenham's algorithm visits from `from` to `to`, not including `to`.
+pub fn line_points(from: Point, to: Point) -> Vec<Point> {
+    // In 64 bits, since the distance between two `i32`s may not fit 32.
+    let dx = (to.x as i64 - from.x as i64).abs();
+    let dy = -(to.y as i64 - from.y as i64).abs();
+    let sx = if from.x < to.x { 1 } else { -1 };
+    let sy = if from.y < to.y { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut point = from;
+    let mut points = Vec::new();
+    while point != to {
+        points.push(point);
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            point.x += sx;
+        }
+        if e2 <= dx {
+            error += dx;
+            point.y += sy;
+        }
+    }
+    points
+}
+
+/// Whether a point lies inside the ellipse inscribed in `bounds`.
+fn in_ellipse(bounds: Rect, inset: f64, x: f64, y: f64) -> bool {
+    let (left, top, right, bottom) = (bounds.left as f64, bounds.top as f64, bounds.right as f64, bounds.bottom as f64);
+    let rx = (right - left) / 2.0 - inset;
+    let ry = (bottom - top) / 2.0 - inset;
+    if rx <= 0.0 || ry <= 0.0 {
+        return false;
+    }
+    let cx = (left + right) / 2.0;
+    let cy = (top + bottom) / 2.0;
+    let (nx, ny) = ((x - cx) / rx, (y - cy) / ry);
+    nx * nx + ny * ny <= 1.0
+}
+
+/// Whether a point lies inside a polygon by the even-odd rule.
+fn in_polygon(points: &[Point], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let (ax, ay, bx, by) = (a.x as f64, a.y as f64, b.x as f64, b.y as f64);
+        if (ay <= y) != (by <= y) && x < ax + (y - ay) * (bx - ax) / (by - ay) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn bounding_box(points: impl IntoIterator<Item = Point>) -> Rect {
+    let mut points = points.into_iter();
+    let Some(first) = points.next() else {
+        return Rect::default();
+    };
+    // A point at `i32::MAX` loses its last pixel, which is far outside any image anyway.
+    let mut rect = Rect::new(first.x, first.y, first.x.saturating_add(1), first.y.saturating_add(1));
+    for p in points {
+        rect = Rect::new(
+            rect.left.min(p.x),
+            rect.top.min(p.y),
+            rect.right.max(p.x.saturating_add(1)),
+            rect.bottom.max(p.y.saturating_add(1)),
+        );
+    }
+    rect
+}
+
+impl Canvas for ImageCanvas<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.image.width() as i32, self.image.height() as i32)
+    }
+
+    fn fill_sys_color(&mut self, rect: Rect, color: SysColor) -> Result<(), Win32Error> {
+        let color = self.palette.get(color).into();
+        self.fill_rect(rect, color);
+        Ok(())
+    }
+
+    fn line(&mut self, from: Point, to: Point, stroke: Stroke) -> Result<(), Win32Error> {
+        for point in line_points(from, to) {
+            self.stamp(point.x, point.y, stroke);
+        }
+        Ok(())
+    }
+
+    fn rect(&mut self, rect: Rect, style: Style) -> Result<(), Win32Error> {
+        if let Some(fill) = style.fill {
+            self.fill_rect(rect, fill);
+        }
+        if let Some(stroke) = style.stroke {
+            let w = stroke.width.clamp(1, i32::MAX as u32) as i32;
+            let Rect { left, top, right, bottom } = rect;
+            self.fill_rect(Rect::new(left, top, right, top.saturating_add(w).min(bottom)), stroke.color);
+            self.fill_rect(Rect::new(left, bottom.saturating_sub(w).max(top), right, bottom), stroke.color);
+            self.fill_rect(Rect::new(left, top, left.saturating_add(w).min(right), bottom), stroke.color);
+            self.fill_rect(Rect::new(right.saturating_sub(w).max(left), top, right, bottom), stroke.color);
+        }
+        Ok(())
+    }
+
+    fn ellipse(&mut self, bounds: Rect, style: Style) -> Result<(), Win32Error> {
+        if let Some(fill) = style.fill {
+            self.fill_where(bounds, fill, |x, y| in_ellipse(bounds, 0.0, x, y));
+        }
+        if let Some(stroke) = style.stroke {
+            let inset = stroke.width.max(1) as f64;
+            self.fill_where(bounds, stroke.color, |x, y| {
+                in_ellipse(bounds, 0.0, x, y) && !in_ellipse(bounds, inset, x, y)
+            });
+        }
+        Ok(())
+    }
+
+    fn polygon(&mut self, points: &[Point], style: Style) -> Result<(), Win32Error> {
+        if points.len() < 2 {
+            return Ok(());
+        }
+        if let Some(fill) = style.fill {
+            let bounds = bounding_box(points.iter().copied());
+            self.fill_where(bounds, fill, |x, y| in_polygon(points, x, y));
+        }
+        if let Some(stroke) = style.stroke {
+            for (i, &from) in points.iter().enumerate() {
+                self.line(from, points[(i + 1) % points.len()], stroke)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn gradient_triangle(&mut self, vertices: [Vertex; 3]) -> Result<(), Win32Error> {
+        let [a, b, c] = vertices.map(|v| (v.position.x as f64, v.position.y as f64));
+        let edge = |(x0, y0): (f64, f64), (x1, y1): (f64, f64), x: f64, y: f64| {
+            (x1 - x0) * (y - y0) - (y1 - y0) * (x - x0)
+        };
+        let area = edge(a, b, c.0, c.1);
+        if area == 0.0 {
+            return Ok(());
+        }
+        let Some(bounds) = self.clip(bounding_box(vertices.map(|v| v.position))) else {
+            return Ok(());
+        };
+        for y in bounds.top..bounds.bottom {
+            for x in bounds.left..bounds.right {
+                let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                let weights = [edge(b, c, px, py) / area, edge(c, a, px, py) / area, edge(a, b, px, py) / area];
+                if weights.iter().any(|&w| w < 0.0) {
+                    continue;
+                }
+                let channel = |f: fn(&Rgba) -> u8| {
+                    let value: f64 = vertices.iter().zip(weights).map(|(v, w)| f(&v.color) as f64 * w).sum();
+                    value.round().clamp(0.0, 255.0) as u8
+                };
+                // Like `GradientFill` on a screen DC, the alpha of the vertices has no effect.
+                let color = Rgba::opaque(channel(|c| c.r), channel(|c| c.g), channel(|c| c.b));
+                self.plot(x, y, color);
+            }
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, origin: Point, text: &str, color: Rgba) -> Result<(), Win32Error> {
+        // In 64 bits, text may well run past `i32::MAX`. Those pixels aren't on the image anyway.
+        let size = self.size();
+        for (line_index, line) in text.split('\n').enumerate() {
+            let top = origin.y as i64 + line_index as i64 * font::LINE_HEIGHT as i64;
+            for (char_index, ch) in line.chars().enumerate() {
+                let left = origin.x as i64 + char_index as i64 * font::ADVANCE as i64;
+                if left >= size.width as i64 || top >= size.height as i64 {
+                    break;
+                }
+                for y in 0..font::GLYPH_HEIGHT {
+                    for x in 0..font::GLYPH_WIDTH {
+                        let (px, py) = (left + x as i64, top + y as i64);
+                        if font::is_set(ch, x, y) && px >= 0 && py >= 0 && px < size.width as i64 && py < size.height as i64 {
+                            self.plot(px as i32, py as i32, color);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba = Rgba::opaque(255, 0, 0);
+    const BLUE: Rgba = Rgba::opaque(0, 0, 255);
+
+    fn count(image: &Image, color: Rgba) -> usize {
+        image.pixels().iter().filter(|&&p| p == color).count()
+    }
+
+    #[test]
+    fn lines_skip_their_end_point() {
+        assert_eq!(
+            line_points(Point::new(0, 0), Point::new(3, 1)),
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 1)]
+        );
+        assert!(line_points(Point::new(2, 2), Point::new(2, 2)).is_empty());
+        let mut image = Image::new(8, 8);
+        ImageCanvas::new(&mut image)
+            .line(Point::new(1, 1), Point::new(1, 6), Stroke { color: RED, width: 1 })
+            .unwrap();
+        assert_eq!(count(&image, RED), 5);
+        assert_eq!(image.get(1, 6), Some(Rgba::TRANSPARENT));
+    }
+
+    #[test]
+    fn rect_fill_and_outline() {
+        let mut image = Image::new(10, 10);
+        let mut canvas = ImageCanvas::new(&mut image);
+        canvas.rect(Rect::new(2, 2, 8, 6), Style::filled(BLUE).with_stroke(RED, 1)).unwrap();
+        assert_eq!(count(&image, RED), 6 * 2 + 2 * 2);
+        assert_eq!(count(&image, BLUE), 4 * 2);
+        assert_eq!(image.get(8, 5), Some(Rgba::TRANSPARENT));
+    }
+
+    #[test]
+    fn shapes_are_clipped_to_the_image() {
+        let mut image = Image::new(4, 4);
+        let mut canvas = ImageCanvas::new(&mut image);
+        canvas.ellipse(Rect::new(-4, -4, 4, 4), Style::filled(BLUE)).unwrap();
+        canvas.rect(Rect::new(-10, -10, 2, 20), Style::filled(RED)).unwrap();
+        canvas.text(Point::new(-3, -3), "W", RED).unwrap();
+        assert_eq!(image.get(0, 3), Some(RED));
+        assert_eq!(image.get(3, 3), Some(Rgba::TRANSPARENT));
+    }
+
+    #[test]
+    fn ellipse_is_symmetric_and_inside_its_bounds() {
+        let mut image = Image::new(12, 8);
+        ImageCanvas::new(&mut image).ellipse(Rect::new(1, 1, 11, 7), Style::filled(RED)).unwrap();
+        for y in 0..8 {
+            for x in 0..12 {
+                assert_eq!(image.get(x, y), image.get(11 - x, 7 - y));
+            }
+        }
+        assert_eq!(image.get(0, 0), Some(Rgba::TRANSPARENT));
+        assert_eq!(image.get(6, 4), Some(RED));
+        assert_eq!(image.get(1, 1), Some(Rgba::TRANSPARENT));
+    }
+
+    #[test]
+    fn polygon_uses_even_odd_fill() {
+        // A square drawn twice around overlaps itself, which the alternate rule leaves empty.
+        let square = [Point::new(0, 0), Point::new(4, 0), Point::new(4, 4), Point::new(0, 4)];
+        let mut image = Image::new(4, 4);
+        ImageCanvas::new(&mut image).polygon(&square, Style::filled(RED)).unwrap();
+        assert_eq!(count(&image, RED), 16);
+        let twice: Vec<Point> = square.iter().chain(square.iter()).copied().collect();
+        let mut image = Image::new(4, 4);
+        ImageCanvas::new(&mut image).polygon(&twice, Style::filled(RED)).unwrap();
+        assert_eq!(count(&image, RED), 0);
+    }
+
+    #[test]
+    fn gradient_triangle_interpolates_colors() {
+        let mut image = Image::new(64, 64);
+        ImageCanvas::new(&mut image)
+            .gradient_triangle([
+                Vertex::new(0, 0, RED),
+                Vertex::new(64, 0, RED),
+                Vertex::new(0, 64, BLUE),
+            ])
+            .unwrap();
+        let top = image.get(1, 0).unwrap();
+        let bottom = image.get(0, 62).unwrap();
+        assert!(top.r > 240 && top.b < 15, "{:?}", top);
+        assert!(bottom.b > 240 && bottom.r < 15, "{:?}", bottom);
+        assert_eq!(image.get(63, 63), Some(Rgba::TRANSPARENT));
+        assert_eq!(top.a, 255);
+    }
+
+    #[test]
+    fn text_uses_the_builtin_font() {
+        let mut image = Image::new(20, 20);
+        ImageCanvas::new(&mut image).text(Point::new(1, 1), "l\nl", RED).unwrap();
+        let lit = count(&image, RED);
+        let glyph: u32 = (0..7).map(|y| (0..5).filter(|&x| font::is_set('l', x, y)).count() as u32).sum();
+        assert_eq!(lit as u32, glyph * 2);
+    }
+
+    #[test]
+    fn extreme_coordinates_do_not_overflow() {
+        let mut image = Image::new(8, 8);
+        let mut canvas = ImageCanvas::new(&mut image);
+        let (min, max) = (i32::MIN, i32::MAX);
+        canvas
+            .gradient_triangle([Vertex::new(0, 0, RED), Vertex::new(max, 0, RED), Vertex::new(0, max, RED)])
+            .unwrap();
+        canvas.rect(Rect::new(min, min, max, max), Style::filled(BLUE).with_stroke(RED, u32::MAX)).unwrap();
+        canvas.ellipse(Rect::new(min, min, max, max), Style::filled(BLUE).with_stroke(RED, 2)).unwrap();
+        canvas.polygon(&[Point::new(max, max), Point::new(max - 1, max), Point::new(max, max - 2)], Style::filled(RED)).unwrap();
+        canvas.text(Point::new(max - 7, 0), "far away", RED).unwrap();
+        canvas.text(Point::new(0, max - 1), "a\nb", RED).unwrap();
+        assert_eq!(line_points(Point::new(max - 1, min), Point::new(max, min)), [Point::new(max - 1, min)]);
+        assert_eq!(bounding_box([Point::new(max, max)]), Rect::new(max, max, max, max));
+        canvas.rect(Rect::new(max, max, min, min), Style::filled(RED)).unwrap();
+        assert_eq!(image.get(4, 4), Some(BLUE));
+    }
+
+    #[test]
+    fn sys_colors_come_from_the_palette() {
+        let mut image = Image::new(2, 2);
+        ImageCanvas::with_palette(&mut image, Palette::dark())
+            .fill_sys_color(Rect::new(0, 0, 2, 2), SysColor::Window)
+            .unwrap();
+        assert_eq!(image.get(1, 1), Some(Palette::dark().get(SysColor::Window).into()));
+    }
+}
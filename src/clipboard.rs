@@ -0,0 +1,243 @@
+//! Copying text and images through the system clipboard.
+//!
+//! The clipboard stores plain bytes per format. Text is `CF_UNICODETEXT`: NUL terminated UTF-16
+//! with `\r\n` line breaks. Images are `CF_DIB`: a `BITMAPINFOHEADER` followed by the pixels,
+//! which is a `.bmp` file without its file header. The conversions live here as plain functions,
+//! the Windows part only moves bytes in and out of `GlobalAlloc` memory.
+
+use crate::bmp::{self, BitDepth};
+use crate::image::Image;
+
+/// Encodes text for `CF_UNICODETEXT`, turning lone `\n` into `\r\n` and adding the terminator.
+pub fn encode_text(text: &str) -> Vec<u16> {
+    let mut units = Vec::with_capacity(text.len() + 1);
+    let mut previous = '\0';
+    for ch in text.chars() {
+        if ch == '\n' && previous != '\r' {
+            units.push(u16::from(b'\r'));
+        }
+        let mut buffer = [0; 2];
+        units.extend_from_slice(ch.encode_utf16(&mut buffer));
+        previous = ch;
+    }
+    units.push(0);
+    units
+}
+
+/// Decodes `CF_UNICODETEXT` data up to the first NUL, turning `\r\n` back into `\n`.
+///
+/// Unpaired surrogates, which other programs can leave behind, become `U+FFFD`.
+pub fn decode_text(units: &[u16]) -> String {
+    let end = units.iter().position(|&unit| unit == 0).unwrap_or(units.len());
+    String::from_utf16_lossy(&units[..end]).replace("\r\n", "\n")
+}
+
+/// Encodes an image for `CF_DIB`, with 32 bits per pixel only if it has transparency.
+pub fn encode_image(image: &Image) -> Vec<u8> {
+    bmp::encode_dib(image, BitDepth::for_image(image))
+}
+
+/// Decodes `CF_DIB` data, or `None` if the layout is one [`bmp::decode_dib`] does not read.
+///
+/// Most programs leave the fourth byte of 32 bit pixels at zero, so an image that would come out
+/// fully transparent is made opaque instead.
+pub fn decode_image(bytes: &[u8]) -> Option<Image> {
+    let mut image = bmp::decode_dib(bytes)?;
+    if image.pixels().iter().all(|pixel| pixel.a == 0) {
+        image.pixels_mut().iter_mut().for_each(|pixel| pixel.a = 255);
+    }
+    Some(image)
+}
+
+#[cfg(windows)]
+mod windows {
+    use core::marker::PhantomData;
+    use core::ptr::null_mut;
+
+    use super::{decode_image, decode_text, encode_image, encode_text};
+    use crate::image::Image;
+    use crate::win32::*;
+    use crate::window::Window;
+
+    /// Keeps a `GlobalAlloc` block locked while its bytes are used.
+    struct Locked {
+        memory: HGLOBAL,
+        data: *mut u8,
+    }
+
+    impl Locked {
+        fn new(memory: HGLOBAL) -> Result<Self, Win32Error> {
+            let data = unsafe { GlobalLock(memory) }.cast::<u8>();
+            if data.is_null() {
+                Err(get_last_error())
+            } else {
+                Ok(Self { memory, data })
+            }
+        }
+
+        fn bytes(&self) -> &[u8] {
+            unsafe { core::slice::from_raw_parts(self.data, GlobalSize(self.memory)) }
+        }
+    }
+
+    impl Drop for Locked {
+        fn drop(&mut self) {
+            unsafe { GlobalUnlock(self.memory) };
+        }
+    }
+
+    /// The open clipboard, closed again on drop.
+    ///
+    /// Only one window can have the clipboard open at a time, so keep it open just long enough to
+    /// read or write. The first `set_` call of a session empties the clipboard and takes ownership,
+    /// later ones add more formats, so text and an image can be offered together.
+    #[derive(Debug)]
+    pub struct Clipboard {
+        emptied: bool,
+        // The clipboard is opened per thread.
+        _not_send: PhantomData<*const ()>,
+    }
+
+    impl Clipboard {
+        /// See [`OpenClipboard`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-openclipboard)
+        ///
+        /// Without an `owner` nothing can be set.
+        pub fn open(owner: Option<&Window>) -> Result<Self, Win32Error> {
+            let owner = owner.map_or(null_mut(), Window::hwnd);
+            if unsafe { OpenClipboard(owner) } == 0 {
+                Err(get_last_error())
+            } else {
+                Ok(Self { emptied: false, _not_send: PhantomData })
+            }
+        }
+
+        /// See [`IsClipboardFormatAvailable`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-isclipboardformatavailable)
+        pub fn has_format(&self, format: UINT) -> bool {
+            unsafe { IsClipboardFormatAvailable(format) != 0 }
+        }
+
+        /// A copy of the data stored for `format`, `None` if there is none.
+        pub fn get(&self, format: UINT) -> Result<Option<Vec<u8>>, Win32Error> {
+            if !self.has_format(format) {
+                return Ok(None);
+            }
+            let memory = unsafe { GetClipboardData(format) };
+            if memory.is_null() {
+                return Err(get_last_error());
+            }
+            Ok(Some(Locked::new(memory)?.bytes().to_vec()))
+        }
+
+        /// The clipboard text, `None` if there is none.
+        pub fn text(&self) -> Result<Option<String>, Win32Error> {
+            Ok(self.get(CF_UNICODETEXT)?.map(|bytes| {
+                let units: Vec<u16> =
+                    bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+                decode_text(&units)
+            }))
+        }
+
+        /// The clipboard image, `None` if there is none or it is in a layout we can't read.
+        pub fn image(&self) -> Result<Option<Image>, Win32Error> {
+            Ok(self.get(CF_DIB)?.and_then(|bytes| decode_image(&bytes)))
+        }
+
+        /// See [`EmptyClipboard`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-emptyclipboard)
+        pub fn clear(&mut self) -> Result<(), Win32Error> {
+            if unsafe { EmptyClipboard() } == 0 {
+                return Err(get_last_error());
+            }
+            self.emptied = true;
+            Ok(())
+        }
+
+        /// Stores `bytes` for `format`, copied into memory the clipboard then owns.
+        pub fn set(&mut self, format: UINT, bytes: &[u8]) -> Result<(), Win32Error> {
+            if !self.emptied {
+                self.clear()?;
+            }
+            let memory = global_alloc_copy(bytes)?;
+            unsafe {
+                if SetClipboardData(format, memory).is_null() {
+                    let e = get_last_error();
+                    GlobalFree(memory);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        }
+
+        pub fn set_text(&mut self, text: &str) -> Result<(), Win32Error> {
+            let bytes: Vec<u8> = encode_text(text).iter().flat_map(|unit| unit.to_le_bytes()).collect();
+            self.set(CF_UNICODETEXT, &bytes)
+        }
+
+        pub fn set_image(&mut self, image: &Image) -> Result<(), Win32Error> {
+            self.set(CF_DIB, &encode_image(image))
+        }
+    }
+
+    impl Drop for Clipboard {
+        fn drop(&mut self) {
+            unsafe { CloseClipboard() };
+        }
+    }
+
+    /// Replaces the clipboard contents with `text`.
+    pub fn copy_text(owner: &Window, text: &str) -> Result<(), Win32Error> {
+        Clipboard::open(Some(owner))?.set_text(text)
+    }
+
+    /// The clipboard text, reading without an owner window.
+    pub fn paste_text() -> Result<Option<String>, Win32Error> {
+        Clipboard::open(None)?.text()
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Rgba;
+
+    #[test]
+    fn text_uses_crlf_and_a_terminator() {
+        let units = encode_text("a\nb\r\nç😀");
+        let expected: Vec<u16> = "a\r\nb\r\nç😀\0".encode_utf16().collect();
+        assert_eq!(units, expected);
+        assert_eq!(decode_text(&units), "a\nb\nç😀");
+        assert_eq!(encode_text(""), [0]);
+    }
+
+    #[test]
+    fn text_decoding_stops_at_the_terminator() {
+        let mut units: Vec<u16> = "left\0over".encode_utf16().collect();
+        assert_eq!(decode_text(&units), "left");
+        units.truncate(2);
+        assert_eq!(decode_text(&units), "le");
+        assert_eq!(decode_text(&[0xD800, u16::from(b'x')]), "\u{FFFD}x");
+    }
+
+    #[test]
+    fn images_round_trip() {
+        let image = Image::from_fn(3, 2, |x, y| Rgba::new(x as u8 * 90, y as u8 * 200, 9, 255));
+        let bytes = encode_image(&image);
+        // Opaque images go out as 24 bits per pixel.
+        assert_eq!(u16::from_le_bytes([bytes[14], bytes[15]]), 24);
+        assert_eq!(decode_image(&bytes), Some(image.clone()));
+
+        let mut translucent = image;
+        translucent.set(1, 1, Rgba::new(1, 2, 3, 128));
+        assert_eq!(decode_image(&encode_image(&translucent)), Some(translucent));
+    }
+
+    #[test]
+    fn zero_alpha_means_opaque() {
+        let clear = Image::filled(2, 2, Rgba::new(10, 20, 30, 0));
+        let image = decode_image(&bmp::encode_dib(&clear, BitDepth::Bgra32)).unwrap();
+        assert!(image.pixels().iter().all(|&pixel| pixel == Rgba::new(10, 20, 30, 255)));
+        assert_eq!(decode_image(&[1, 2, 3]), None);
+    }
+}
@@ -0,0 +1,322 @@
+//! A color type that converts into whatever the Win32 API wants.
+//!
+//! `TRIVERTIX` takes 16 bit channels, `COLORREF` packs bytes as `0x00BBGGRR` and `AlphaBlend`
+//! wants premultiplied BGRA. Writing those by hand invites mistakes like `0x900` where `0x9000`
+//! was meant, so build a [`Color`] once and convert.
+//!
+//! Channels are floats from 0 to 1 in sRGB, the space hex codes and color pickers use. Blending
+//! and lighting math should happen on [`Color::to_linear`] values.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::image::Rgba;
+use crate::palette::Rgb;
+
+/// An RGBA color in sRGB with straight alpha, channels from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// Why a hex color did not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(pub String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected a color like #ff8000 or #ff800080, found {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Decodes one sRGB channel into linear light.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes one linear light channel as sRGB.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+
+    /// From float channels, which are not clamped until converted to integers.
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    pub fn rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+    }
+
+    pub fn rgb8(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba8(r, g, b, 255)
+    }
+
+    /// `#rrggbb` or `#rrggbbaa`, the `#` is optional.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseColorError> {
+        let error = || ParseColorError(hex.to_owned());
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !(digits.len() == 6 || digits.len() == 8) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| error());
+        let alpha = if digits.len() == 8 { channel(6)? } else { 255 };
+        Ok(Self::rgba8(channel(0)?, channel(2)?, channel(4)?, alpha))
+    }
+
+    /// From hue in degrees (any value, wrapped into 0 to 360), saturation and value from 0 to 1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        Self::rgb(r + m, g + m, b + m)
+    }
+
+    /// Hue in degrees from 0 to 360 (0 for grays), saturation and value from 0 to 1.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / chroma).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / chroma + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        (hue, saturation, max)
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// The same color with the channels decoded to linear light, alpha is left alone.
+    pub fn to_linear(self) -> Self {
+        Self::new(srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a)
+    }
+
+    /// The inverse of [`Color::to_linear`].
+    pub fn from_linear(linear: Color) -> Self {
+        Self::new(linear_to_srgb(linear.r), linear_to_srgb(linear.g), linear_to_srgb(linear.b), linear.a)
+    }
+
+    /// Clamped and rounded to bytes.
+    pub fn to_rgba8(self) -> Rgba {
+        Rgba::new(to_u8(self.r), to_u8(self.g), to_u8(self.b), to_u8(self.a))
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when not opaque.
+    pub fn to_hex(self) -> String {
+        let Rgba { r, g, b, a } = self.to_rgba8();
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    /// The red, green, blue and alpha `COLOR16` channels of a `TRIVERTIX`.
+    ///
+    /// Full intensity is `0xffff`, each byte is repeated so `0x90` becomes `0x9090`.
+    pub fn to_color16(self) -> [u16; 4] {
+        let Rgba { r, g, b, a } = self.to_rgba8();
+        [r, g, b, a].map(|c| c as u16 * 0x101)
+    }
+
+    /// A [`COLORREF`](https://docs.microsoft.com/en-us/windows/win32/gdi/colorref), which has no
+    /// alpha.
+    pub fn to_colorref(self) -> u32 {
+        self.to_rgba8().rgb().to_colorref()
+    }
+
+    /// Bytes in blue, green, red, alpha order with the colors multiplied by alpha, as 32 bit DIBs
+    /// for `AlphaBlend` and `UpdateLayeredWindow` want them.
+    pub fn to_premultiplied_bgra(self) -> [u8; 4] {
+        let a = self.a.clamp(0.0, 1.0);
+        [to_u8(self.b * a), to_u8(self.g * a), to_u8(self.r * a), to_u8(a)]
+    }
+
+    /// The inverse of [`Color::to_premultiplied_bgra`], colors with no alpha come out black.
+    pub fn from_premultiplied_bgra([b, g, r, a]: [u8; 4]) -> Self {
+        if a == 0 {
+            return Color::TRANSPARENT;
+        }
+        let alpha = a as f32 / 255.0;
+        let channel = |c: u8| (c as f32 / 255.0 / alpha).min(1.0);
+        Self::new(channel(r), channel(g), channel(b), alpha)
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl From<Rgba> for Color {
+    fn from(rgba: Rgba) -> Self {
+        Self::rgba8(rgba.r, rgba.g, rgba.b, rgba.a)
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(rgb: Rgb) -> Self {
+        Self::rgb8(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+impl From<Color> for Rgba {
+    fn from(color: Color) -> Self {
+        color.to_rgba8()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift generator, the cases only need to be varied and repeatable.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn byte(&mut self) -> u8 {
+            self.next() as u8
+        }
+
+        fn unit(&mut self) -> f32 {
+            (self.next() >> 40) as f32 / (1u64 << 24) as f32
+        }
+    }
+
+    const CASES: usize = 2000;
+
+    #[test]
+    fn hex_parsing() {
+        assert_eq!(Color::from_hex("#ff8000").unwrap().to_rgba8(), Rgba::opaque(255, 128, 0));
+        assert_eq!("80FF0040".parse::<Color>().unwrap().to_rgba8(), Rgba::new(128, 255, 0, 64));
+        for bad in ["#fff", "#ff80000", "#gg8000", "", "#ff8000 "] {
+            assert_eq!(Color::from_hex(bad), Err(ParseColorError(bad.to_owned())));
+        }
+        assert_eq!(Color::rgba8(1, 2, 3, 4).to_string(), "#01020304");
+    }
+
+    #[test]
+    fn win32_formats() {
+        let color = Color::rgba8(0x90, 0x00, 0x90, 0xff);
+        assert_eq!(color.to_color16(), [0x9090, 0, 0x9090, 0xffff]);
+        assert_eq!(Color::rgb8(0x12, 0x34, 0x56).to_colorref(), 0x0056_3412);
+        assert_eq!(Color::rgba8(255, 128, 0, 128).to_premultiplied_bgra(), [0, 64, 128, 128]);
+        assert_eq!(Color::new(2.0, -1.0, 0.5, 1.0).to_rgba8(), Rgba::opaque(255, 0, 128));
+    }
+
+    #[test]
+    fn hsv_known_values() {
+        let cases = [(0.0, Rgba::opaque(255, 0, 0)), (120.0, Rgba::opaque(0, 255, 0)), (240.0, Rgba::opaque(0, 0, 255))];
+        for (hue, expected) in cases {
+            assert_eq!(Color::from_hsv(hue, 1.0, 1.0).to_rgba8(), expected);
+            assert_eq!(Color::from_hsv(hue + 360.0, 1.0, 1.0).to_rgba8(), expected);
+        }
+        assert_eq!(Color::from_hsv(30.0, 1.0, 1.0).to_rgba8(), Rgba::opaque(255, 128, 0));
+        assert_eq!(Color::rgb8(128, 128, 128).to_hsv().1, 0.0);
+    }
+
+    #[test]
+    fn byte_and_hex_round_trips() {
+        let mut random = Random(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..CASES {
+            let rgba = Rgba::new(random.byte(), random.byte(), random.byte(), random.byte());
+            let color = Color::from(rgba);
+            assert_eq!(color.to_rgba8(), rgba);
+            assert_eq!(Color::from_hex(&color.to_hex()).unwrap().to_rgba8(), rgba);
+            assert_eq!(Rgb::from_colorref(color.to_colorref()), rgba.rgb());
+            let [r, g, b, a] = color.to_color16();
+            assert_eq!([r >> 8, g >> 8, b >> 8, a >> 8], [rgba.r, rgba.g, rgba.b, rgba.a].map(u16::from));
+        }
+    }
+
+    #[test]
+    fn hsv_and_linear_round_trips() {
+        let mut random = Random(0xD1B5_4A32_D192_ED03);
+        for _ in 0..CASES {
+            let color = Color::rgb(random.unit(), random.unit(), random.unit());
+            let (h, s, v) = color.to_hsv();
+            assert!((0.0..360.0).contains(&h) && (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&v));
+            assert_eq!(Color::from_hsv(h, s, v).to_rgba8(), color.to_rgba8(), "{:?} via hsv {:?}", color, (h, s, v));
+            let back = Color::from_linear(color.to_linear());
+            for (a, b) in [(color.r, back.r), (color.g, back.g), (color.b, back.b)] {
+                assert!((a - b).abs() < 1e-5, "{:?} came back as {:?}", color, back);
+            }
+        }
+        // Linear light is darker than its sRGB encoding in the midtones.
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    }
+
+    #[test]
+    fn premultiplied_round_trips_when_alpha_keeps_enough_precision() {
+        let mut random = Random(0x2545_F491_4F6C_DD1D);
+        for _ in 0..CASES {
+            let rgba = Rgba::new(random.byte(), random.byte(), random.byte(), random.byte() | 0x80);
+            let back = Color::from_premultiplied_bgra(Color::from(rgba).to_premultiplied_bgra()).to_rgba8();
+            // Premultiplying loses up to 255 / alpha steps of each color channel.
+            for (a, b) in [(rgba.r, back.r), (rgba.g, back.g), (rgba.b, back.b)] {
+                assert!(a.abs_diff(b) <= 2, "{:?} came back as {:?}", rgba, back);
+            }
+            assert_eq!(back.a, rgba.a);
+        }
+        assert_eq!(Color::from_premultiplied_bgra([10, 20, 30, 0]), Color::TRANSPARENT);
+    }
+}
@@ -0,0 +1,57 @@
+//! What the `triangle_from_scratch` binary draws, kept in the library so it can be rendered
+//! offscreen and snapshot tested.
+
+use crate::canvas::{Canvas, Vertex};
+use crate::error::Win32Error;
+use crate::geometry::Rect;
+use crate::image::{Image, Rgba};
+use crate::math3d::{Mat4, Vec3};
+use crate::palette::SysColor;
+use crate::pipeline::{Renderer, Vertex3};
+
+/// The window background and a shaded triangle, `dirty` is the area that needs repainting.
+pub fn paint_triangle(canvas: &mut impl Canvas, dirty: Rect) -> Result<(), Win32Error> {
+    canvas.fill_sys_color(dirty, SysColor::Window)?;
+    canvas.gradient_triangle([
+        Vertex::new(150, 0, Rgba::opaque(0xff, 0x80, 0x00)),
+        Vertex::new(0, 150, Rgba::opaque(0x90, 0x00, 0x90)),
+        // The original `TRIVERTIX` had a red of `0x900`, likely meant as `0x9000`. The picture
+        // keeps it as it always looked.
+        Vertex::new(300, 150, Rgba::opaque(0x09, 0x80, 0x90)),
+    ])
+}
+
+/// The corners of a cube from -1 to 1, colored by position like an RGB color cube.
+pub fn cube_vertices() -> [Vertex3; 8] {
+    core::array::from_fn(|i| {
+        let bit = |n: usize| (i >> n) & 1;
+        let coordinate = |n: usize| if bit(n) == 1 { 1.0 } else { -1.0 };
+        let channel = |n: usize| bit(n) as u8 * 255;
+        Vertex3::new(
+            Vec3::new(coordinate(0), coordinate(1), coordinate(2)),
+            Rgba::opaque(channel(0), channel(1), channel(2)),
+        )
+    })
+}
+
+/// The twelve outward facing, counterclockwise triangles of [`cube_vertices`].
+#[rustfmt::skip]
+pub const CUBE_TRIANGLES: [[usize; 3]; 12] = [
+    [0, 2, 3], [0, 3, 1], // -Z
+    [4, 5, 7], [4, 7, 6], // +Z
+    [0, 4, 6], [0, 6, 2], // -X
+    [1, 3, 7], [1, 7, 5], // +X
+    [0, 1, 5], [0, 5, 4], // -Y
+    [2, 6, 7], [2, 7, 3], // +Y
+];
+
+/// The color cube turned by `angle` radians about the vertical axis, seen from slightly above.
+pub fn render_cube(width: u32, height: u32, angle: f32) -> Image {
+    let mut renderer = Renderer::new(width, height);
+    renderer.clear(Rgba::opaque(0x20, 0x20, 0x28));
+    let projection = Mat4::perspective(core::f32::consts::FRAC_PI_4, width as f32 / height as f32, 0.5, 20.0);
+    let view = Mat4::look_at(Vec3::new(0.0, 2.5, 6.0), Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0));
+    renderer.state.transform = projection * view * Mat4::rotation_y(angle);
+    renderer.draw(&cube_vertices(), &CUBE_TRIANGLES);
+    renderer.into_image()
+}
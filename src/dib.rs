@@ -0,0 +1,288 @@
+//! Pixel layout and scaling math for device independent bitmaps (DIBs).
+//!
+//! GDI wants DIB pixels as `B, G, R, X` bytes, with every row padded to a multiple of 4 bytes and,
+//! unless the height in the header is negative, the bottom row first. Everything here is plain
+//! arithmetic so it can be tested without a window.
+
+use crate::geometry::{Rect, Size};
+use crate::image::{Image, Rgba};
+
+/// The row order of the pixel data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// The first row in memory is the top of the image.
+    TopDown,
+    /// The first row in memory is the bottom of the image, the GDI default.
+    BottomUp,
+}
+
+impl Orientation {
+    /// The `biHeight` to put in a `BITMAPINFOHEADER`, negative for top-down bitmaps.
+    pub const fn header_height(self, height: u32) -> i32 {
+        match self {
+            Orientation::TopDown => -(height as i32),
+            Orientation::BottomUp => height as i32,
+        }
+    }
+
+    /// Reads the orientation back out of a `biHeight`.
+    pub const fn from_header_height(height: i32) -> Self {
+        if height < 0 {
+            Orientation::TopDown
+        } else {
+            Orientation::BottomUp
+        }
+    }
+
+    /// The image row stored at `memory_row`.
+    const fn image_row(self, memory_row: u32, height: u32) -> u32 {
+        match self {
+            Orientation::TopDown => memory_row,
+            Orientation::BottomUp => height - 1 - memory_row,
+        }
+    }
+}
+
+/// The number of bytes per row, rounded up to a whole `DWORD` as GDI requires.
+pub const fn stride(width: u32, bits_per_pixel: u16) -> usize {
+    (width as usize * bits_per_pixel as usize).div_ceil(32) * 4
+}
+
+/// [`stride`] for sizes read from a file or the clipboard, `None` if it doesn't fit a `usize`
+/// (which happens on 32 bit targets).
+pub const fn checked_stride(width: u32, bits_per_pixel: u16) -> Option<usize> {
+    match (width as usize).checked_mul(bits_per_pixel as usize) {
+        Some(bits) => Some(bits.div_ceil(32) * 4),
+        None => None,
+    }
+}
+
+/// Packs an image as 32 bit BGRA rows, alpha is carried over into the fourth byte.
+pub fn image_to_bgra(image: &Image, orientation: Orientation) -> Vec<u8> {
+    let stride = stride(image.width(), 32);
+    let mut bytes = vec![0; stride * image.height() as usize];
+    if stride == 0 {
+        return bytes;
+    }
+    for (memory_row, chunk) in bytes.chunks_exact_mut(stride).enumerate() {
+        let row = image.row(orientation.image_row(memory_row as u32, image.height()));
+        for (pixel, out) in row.iter().zip(chunk.chunks_exact_mut(4)) {
+            out.copy_from_slice(&[pixel.b, pixel.g, pixel.r, pixel.a]);
+        }
+    }
+    bytes
+}
+
+/// Packs an image as 24 bit BGR rows, padding every row to the DIB stride and dropping alpha.
+pub fn image_to_bgr(image: &Image, orientation: Orientation) -> Vec<u8> {
+    let stride = stride(image.width(), 24);
+    let mut bytes = vec![0; stride * image.height() as usize];
+    if stride == 0 {
+        return bytes;
+    }
+    for (memory_row, chunk) in bytes.chunks_exact_mut(stride).enumerate() {
+        let row = image.row(orientation.image_row(memory_row as u32, image.height()));
+        for (pixel, out) in row.iter().zip(chunk.chunks_exact_mut(3)) {
+            out.copy_from_slice(&[pixel.b, pixel.g, pixel.r]);
+        }
+    }
+    bytes
+}
+
+/// Unpacks 24 or 32 bit DIB rows into an image.
+///
+/// 24 bit pixels come out opaque. For 32 bit pixels `keep_alpha` decides between the fourth byte
+/// and full opacity, since plain `BI_RGB` bitmaps leave that byte undefined.
+///
+/// Returns `None` if `bytes` is too short for the given size and stride, which may come from an
+/// untrusted header.
+pub fn dib_to_image(
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+    bits_per_pixel: u16,
+    orientation: Orientation,
+    keep_alpha: bool,
+) -> Option<Image> {
+    let bytes_per_pixel = match bits_per_pixel {
+        24 => 3,
+        32 => 4,
+        _ => return None,
+    };
+    let stride = checked_stride(width, bits_per_pixel)?;
+    if bytes.len() < stride.checked_mul(height as usize)? {
+        return None;
+    }
+    if width == 0 {
+        return Some(Image::new(width, height));
+    }
+    let mut image = Image::new(width, height);
+    for memory_row in 0..height {
+        let chunk = &bytes[memory_row as usize * stride..][..width as usize * bytes_per_pixel];
+        let row = image.row_mut(orientation.image_row(memory_row, height));
+        for (pixel, data) in row.iter_mut().zip(chunk.chunks_exact(bytes_per_pixel)) {
+            let a = if bytes_per_pixel == 4 && keep_alpha { data[3] } else { 255 };
+            *pixel = Rgba::new(data[2], data[1], data[0], a);
+        }
+    }
+    Some(image)
+}
+
+/// How a framebuffer is mapped onto a client area of a different size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scaling {
+    /// Stretch to cover the whole client area, ignoring the aspect ratio.
+    Stretch,
+    /// The largest size that fits while keeping the aspect ratio, centered with bars.
+    #[default]
+    Fit,
+    /// The largest whole multiple of the image size that fits, centered. Keeps pixels square and
+    /// sharp, falls back to 1x if even that does not fit.
+    Integer,
+    /// No scaling, the image is centered (and may be cropped).
+    Center,
+}
+
+/// Where the image lands in the client area.
+pub fn destination_rect(image: Size, client: Size, scaling: Scaling) -> Rect {
+    let client_rect = Rect::from_origin_size(Default::default(), client);
+    if image.width <= 0 || image.height <= 0 {
+        return Rect::default();
+    }
+    let size = match scaling {
+        Scaling::Stretch => return client_rect,
+        Scaling::Fit => {
+            // Compare the aspect ratios by cross multiplying, so we stay in integers.
+            let (iw, ih) = (image.width as i64, image.height as i64);
+            let (cw, ch) = (client.width as i64, client.height as i64);
+            if cw * ih <= ch * iw {
+                Size::new(client.width, (cw * ih / iw) as i32)
+            } else {
+                Size::new((ch * iw / ih) as i32, client.height)
+            }
+        }
+        Scaling::Integer => {
+            let factor = (client.width / image.width).min(client.height / image.height).max(1);
+            Size::new(image.width * factor, image.height * factor)
+        }
+        Scaling::Center => image,
+    };
+    Rect::from_origin_size(Default::default(), size).offset(
+        (client.width - size.width) / 2,
+        (client.height - size.height) / 2,
+    )
+}
+
+/// The parts of the client area not covered by `destination`, to be cleared by the caller.
+///
+/// Returns the top, bottom, left and right bars, skipping any that are empty.
+pub fn border_rects(client: Size, destination: Rect) -> Vec<Rect> {
+    let top = destination.top.clamp(0, client.height);
+    let bottom = destination.bottom.clamp(0, client.height);
+    let left = destination.left.clamp(0, client.width);
+    let right = destination.right.clamp(0, client.width);
+    [
+        Rect::new(0, 0, client.width, top),
+        Rect::new(0, bottom, client.width, client.height),
+        Rect::new(0, top, left, bottom),
+        Rect::new(right, top, client.width, bottom),
+    ]
+    .into_iter()
+    .filter(|rect| !rect.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Image {
+        Image::from_fn(3, 2, |x, y| Rgba::new(x as u8, y as u8, 10 * x as u8 + y as u8, 200))
+    }
+
+    #[test]
+    fn stride_rounds_up_to_dwords() {
+        assert_eq!(stride(1, 32), 4);
+        assert_eq!(stride(3, 24), 12);
+        assert_eq!(stride(5, 24), 16);
+        assert_eq!(stride(4, 24), 12);
+        assert_eq!(stride(0, 24), 0);
+        assert_eq!(checked_stride(5, 24), Some(16));
+    }
+
+    #[test]
+    fn hostile_sizes_are_rejected() {
+        let bytes = [0; 64];
+        assert_eq!(dib_to_image(&bytes, u32::MAX, u32::MAX, 32, Orientation::TopDown, true), None);
+        assert_eq!(dib_to_image(&bytes, 0x4000_0000, 1, 32, Orientation::TopDown, true), None);
+        // No pixels needed, however many rows there are.
+        assert_eq!(dib_to_image(&[], 0, u32::MAX, 24, Orientation::BottomUp, true).map(|image| image.width()), Some(0));
+    }
+
+    #[test]
+    fn bgra_swaps_channels_and_keeps_rows_in_order() {
+        let bytes = image_to_bgra(&sample(), Orientation::TopDown);
+        assert_eq!(bytes.len(), 3 * 4 * 2);
+        assert_eq!(&bytes[4..8], &[10, 0, 1, 200]);
+        assert_eq!(&bytes[12..16], &[1, 1, 0, 200]);
+    }
+
+    #[test]
+    fn bottom_up_reverses_rows() {
+        let bytes = image_to_bgra(&sample(), Orientation::BottomUp);
+        assert_eq!(&bytes[0..4], &[1, 1, 0, 200]);
+        assert_eq!(Orientation::BottomUp.header_height(2), 2);
+        assert_eq!(Orientation::TopDown.header_height(2), -2);
+        assert_eq!(Orientation::from_header_height(-2), Orientation::TopDown);
+    }
+
+    #[test]
+    fn bgr_rows_are_padded() {
+        let bytes = image_to_bgr(&sample(), Orientation::TopDown);
+        assert_eq!(bytes.len(), 12 * 2);
+        assert_eq!(&bytes[9..12], &[0, 0, 0]);
+        assert_eq!(&bytes[12..15], &[1, 1, 0]);
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        let image = sample();
+        for orientation in [Orientation::TopDown, Orientation::BottomUp] {
+            let bgra = image_to_bgra(&image, orientation);
+            assert_eq!(dib_to_image(&bgra, 3, 2, 32, orientation, true), Some(image.clone()));
+            let bgr = image_to_bgr(&image, orientation);
+            let opaque = dib_to_image(&bgr, 3, 2, 24, orientation, false).unwrap();
+            assert!(opaque.pixels().iter().zip(image.pixels()).all(|(a, b)| a.rgb() == b.rgb() && a.a == 255));
+        }
+        assert_eq!(dib_to_image(&[0; 4], 3, 2, 32, Orientation::TopDown, true), None);
+    }
+
+    #[test]
+    fn fit_letterboxes_the_long_side() {
+        let image = Size::new(320, 240);
+        assert_eq!(destination_rect(image, Size::new(640, 480), Scaling::Fit), Rect::new(0, 0, 640, 480));
+        assert_eq!(destination_rect(image, Size::new(800, 480), Scaling::Fit), Rect::new(80, 0, 720, 480));
+        assert_eq!(destination_rect(image, Size::new(640, 600), Scaling::Fit), Rect::new(0, 60, 640, 540));
+    }
+
+    #[test]
+    fn integer_and_center_scaling() {
+        let image = Size::new(100, 50);
+        assert_eq!(destination_rect(image, Size::new(350, 120), Scaling::Integer), Rect::new(75, 10, 275, 110));
+        assert_eq!(destination_rect(image, Size::new(60, 60), Scaling::Integer), Rect::new(-20, 5, 80, 55));
+        assert_eq!(destination_rect(image, Size::new(200, 100), Scaling::Center), Rect::new(50, 25, 150, 75));
+        assert_eq!(destination_rect(image, Size::new(7, 9), Scaling::Stretch), Rect::new(0, 0, 7, 9));
+    }
+
+    #[test]
+    fn borders_cover_the_rest_of_the_client_area() {
+        let client = Size::new(800, 480);
+        let destination = destination_rect(Size::new(320, 240), client, Scaling::Fit);
+        let borders = border_rects(client, destination);
+        assert_eq!(borders, vec![Rect::new(0, 0, 80, 480), Rect::new(720, 0, 800, 480)]);
+        let covered: i32 = borders.iter().map(|r| r.width() * r.height()).sum::<i32>()
+            + destination.width() * destination.height();
+        assert_eq!(covered, 800 * 480);
+        assert!(border_rects(client, Rect::new(0, 0, 800, 480)).is_empty());
+    }
+}
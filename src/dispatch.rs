@@ -0,0 +1,331 @@
+//! Running code on the UI thread from worker threads.
+//!
+//! Windows may only be touched from the thread that created them, the one calling
+//! `GetMessageW`. Workers instead push closures or messages into a [`UiQueue`] through a
+//! [`UiHandle`], and the queue wakes the UI thread, which on Windows means posting a registered
+//! message to the window. The UI thread then runs everything queued in the order it was sent.
+//!
+//! Only the first task after the queue was drained wakes the UI thread, so a busy worker can't
+//! overflow the message queue (which holds 10000 messages). Once the queue is closed, usually
+//! because the window was destroyed, sending fails with [`Disconnected`] instead of queueing work
+//! nobody will run.
+
+use core::fmt;
+use core::marker::PhantomData;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Wakes the UI thread up to drain the queue.
+pub trait Notify: Send + Sync {
+    /// Returns `false` if the UI thread can't be reached anymore.
+    fn notify(&self) -> bool;
+}
+
+impl<F> Notify for F
+where
+    F: Fn() -> bool + Send + Sync,
+{
+    fn notify(&self) -> bool {
+        self()
+    }
+}
+
+/// The UI thread is gone, or stopped taking work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Disconnected;
+
+impl fmt::Display for Disconnected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the UI thread no longer takes work")
+    }
+}
+
+impl std::error::Error for Disconnected {}
+
+enum Task<M> {
+    Run(Box<dyn FnOnce() + Send>),
+    Message(M),
+}
+
+struct State<M> {
+    tasks: VecDeque<Task<M>>,
+    closed: bool,
+}
+
+struct Shared<M> {
+    state: Mutex<State<M>>,
+    notify: Box<dyn Notify>,
+}
+
+impl<M> Shared<M> {
+    fn lock(&self) -> MutexGuard<'_, State<M>> {
+        // Tasks never run under the lock, so poisoning leaves a consistent queue.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, task: Task<M>) -> Result<(), Disconnected> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(Disconnected);
+        }
+        state.tasks.push_back(task);
+        if state.tasks.len() > 1 || self.notify.notify() {
+            return Ok(());
+        }
+        state.closed = true;
+        let tasks = core::mem::take(&mut state.tasks);
+        // Dropping a closure may send again, which must not find the queue locked.
+        drop(state);
+        drop(tasks);
+        Err(Disconnected)
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        let tasks = core::mem::take(&mut state.tasks);
+        drop(state);
+        drop(tasks);
+    }
+}
+
+/// The receiving end, owned by the UI thread. Dropping it closes the queue.
+pub struct UiQueue<M = ()> {
+    shared: Arc<Shared<M>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+/// Sends work to a [`UiQueue`] from any thread.
+pub struct UiHandle<M = ()> {
+    shared: Arc<Shared<M>>,
+}
+
+impl<M> Clone for UiHandle<M> {
+    fn clone(&self) -> Self {
+        Self { shared: Arc::clone(&self.shared) }
+    }
+}
+
+impl<M> fmt::Debug for UiHandle<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UiHandle").field("closed", &self.shared.lock().closed).finish()
+    }
+}
+
+impl<M: Send + 'static> UiHandle<M> {
+    /// Queues `f` to run on the UI thread.
+    pub fn run(&self, f: impl FnOnce() + Send + 'static) -> Result<(), Disconnected> {
+        self.shared.push(Task::Run(Box::new(f)))
+    }
+
+    /// Queues `message` for the UI thread's message handler.
+    pub fn send(&self, message: M) -> Result<(), Disconnected> {
+        self.shared.push(Task::Message(message))
+    }
+
+    /// Whether sending fails, though it may start failing right after this returned `false`.
+    pub fn is_closed(&self) -> bool {
+        self.shared.lock().closed
+    }
+}
+
+impl<M: Send + 'static> UiQueue<M> {
+    /// A queue that calls `notify` when work arrives on an empty queue.
+    pub fn new(notify: impl Notify + 'static) -> Self {
+        let state = Mutex::new(State { tasks: VecDeque::new(), closed: false });
+        Self { shared: Arc::new(Shared { state, notify: Box::new(notify) }), _not_send: PhantomData }
+    }
+
+    pub fn handle(&self) -> UiHandle<M> {
+        UiHandle { shared: Arc::clone(&self.shared) }
+    }
+
+    /// Runs the queued closures and passes the messages to `on_message`, in the order they were
+    /// sent, returning how many there were.
+    ///
+    /// Work sent meanwhile waits for the next call, which its notification asks for.
+    pub fn drain(&self, mut on_message: impl FnMut(M)) -> usize {
+        let tasks = core::mem::take(&mut self.shared.lock().tasks);
+        let count = tasks.len();
+        for task in tasks {
+            match task {
+                Task::Run(f) => f(),
+                Task::Message(message) => on_message(message),
+            }
+        }
+        count
+    }
+
+    /// Makes every handle fail from now on and drops the work that didn't run yet.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.shared.lock().closed
+    }
+}
+
+impl<M> Drop for UiQueue<M> {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{UiHandle, UiQueue};
+    use crate::win32::*;
+
+    /// The name of the message that wakes the UI thread, registered once per session.
+    const DISPATCH_MESSAGE: &str = "triangle_from_scratch UiDispatcher";
+
+    /// A message id unique to the session, see [`RegisterWindowMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerwindowmessagew)
+    pub fn register_window_message(name: &str) -> Result<UINT, Win32Error> {
+        let name = wide_null(name);
+        match unsafe { RegisterWindowMessageW(name.as_ptr()) } {
+            0 => Err(get_last_error()),
+            message => Ok(message),
+        }
+    }
+
+    /// Posts `message` without waiting for it, see [`PostMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postmessagew)
+    ///
+    /// # Safety
+    /// `hwnd` must be a window handle, and the parameters what its window procedure expects with
+    /// `message`.
+    pub unsafe fn post_message(hwnd: HWND, message: UINT, w_param: WPARAM, l_param: LPARAM) -> Result<(), Win32Error> {
+        if PostMessageW(hwnd, message, w_param, l_param) != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+
+    /// A [`UiQueue`] woken by posting a registered message to a window.
+    pub struct UiDispatcher<M = ()> {
+        queue: UiQueue<M>,
+        message: UINT,
+    }
+
+    impl<M: Send + 'static> UiDispatcher<M> {
+        /// Work sent through its handles arrives at `hwnd` as [`message`](Self::message), which
+        /// the window procedure passes to [`handle_message`](Self::handle_message).
+        pub fn new(hwnd: HWND) -> Result<Self, Win32Error> {
+            let message = register_window_message(DISPATCH_MESSAGE)?;
+            // Window handles may be used from any thread, but the pointer type isn't `Send`.
+            let hwnd = hwnd as usize;
+            // Our own message has no parameters, and once the window is gone posting just fails.
+            let queue = UiQueue::new(move || unsafe { post_message(hwnd as HWND, message, 0, 0) }.is_ok());
+            Ok(Self { queue, message })
+        }
+
+        pub fn handle(&self) -> UiHandle<M> {
+            self.queue.handle()
+        }
+
+        pub fn message(&self) -> UINT {
+            self.message
+        }
+
+        /// Drains the queue if `msg` is the wake up message, returning whether it was.
+        pub fn handle_message(&self, msg: UINT, on_message: impl FnMut(M)) -> bool {
+            if msg != self.message {
+                return false;
+            }
+            self.queue.drain(on_message);
+            true
+        }
+
+        /// See [`UiQueue::close`], dropping the dispatcher does this as well.
+        pub fn close(&self) {
+            self.queue.close();
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn runs_in_order_with_one_notification() {
+        let notified = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&notified);
+        let queue = UiQueue::new(move || counter.fetch_add(1, Ordering::SeqCst) < usize::MAX);
+        let handle = queue.handle();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        for i in 0..3 {
+            let log = Arc::clone(&log);
+            handle.run(move || log.lock().unwrap().push(format!("run {}", i))).unwrap();
+            handle.send(i).unwrap();
+        }
+        assert_eq!(notified.load(Ordering::SeqCst), 1);
+        let count = queue.drain(|i| log.lock().unwrap().push(format!("message {}", i)));
+        assert_eq!(count, 6);
+        assert_eq!(log.lock().unwrap()[..3], ["run 0", "message 0", "run 1"]);
+        assert_eq!(queue.drain(|_| ()), 0);
+        // The queue is empty again, so the next send wakes the UI thread again.
+        handle.send(3).unwrap();
+        assert_eq!(notified.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn fails_once_closed() {
+        let queue: UiQueue<u32> = UiQueue::new(|| true);
+        let handle = queue.handle();
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        handle.run(move || flag.store(true, Ordering::SeqCst)).unwrap();
+        drop(queue);
+        assert!(handle.is_closed());
+        assert_eq!(handle.send(1), Err(Disconnected));
+        // What was queued is dropped, not run.
+        assert!(!ran.load(Ordering::SeqCst));
+        assert_eq!(Arc::strong_count(&ran), 1);
+        assert_eq!(handle.clone().run(|| ()), Err(Disconnected));
+    }
+
+    #[test]
+    fn closes_when_the_notification_fails() {
+        let queue: UiQueue<u32> = UiQueue::new(|| false);
+        assert_eq!(queue.handle().send(1), Err(Disconnected));
+        assert!(queue.is_closed());
+        assert_eq!(queue.drain(|_| panic!("nothing to drain")), 0);
+    }
+
+    #[test]
+    fn headless_loop() {
+        // Stands in for the message queue: one wake up per notification.
+        let (wake, woken) = mpsc::channel::<()>();
+        let wake = Mutex::new(wake);
+        let queue: UiQueue<(usize, usize)> = UiQueue::new(move || wake.lock().unwrap().send(()).is_ok());
+        let ui_thread = thread::current().id();
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let handle = queue.handle();
+                thread::spawn(move || {
+                    for i in 0..25 {
+                        handle.send((worker, i)).unwrap();
+                        handle.run(move || assert_eq!(thread::current().id(), ui_thread)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let mut received = vec![Vec::new(); 4];
+        let mut tasks = 0;
+        while tasks < 200 {
+            woken.recv().unwrap();
+            tasks += queue.drain(|(worker, i)| received[worker].push(i));
+        }
+        workers.into_iter().for_each(|worker| worker.join().unwrap());
+        // Each worker's messages arrive in the order it sent them.
+        assert!(received.iter().all(|messages| *messages == (0..25).collect::<Vec<_>>()));
+    }
+}
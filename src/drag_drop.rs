@@ -0,0 +1,193 @@
+//! Files dropped onto a window from Explorer.
+//!
+//! A window that called `DragAcceptFiles` gets `WM_DROPFILES` with an `HDROP` in `WPARAM`. That
+//! handle is a `DROPFILES` header followed by the paths as a NUL separated, double NUL terminated
+//! UTF-16 list. The shell functions read it for us, but the layout is simple enough to build and
+//! parse here, which is how tests (and programs pretending to be Explorer) make a drop without a
+//! mouse.
+
+use std::path::{Path, PathBuf};
+
+use crate::geometry::Point;
+
+/// `DROPFILES`: the offset of the list, the point, `fNC` and `fWide`.
+const HEADER_SIZE: usize = 20;
+
+/// What a `WM_DROPFILES` carries.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FilesDropped {
+    /// In the order Explorer lists them, which is not necessarily the order they were selected.
+    pub paths: Vec<PathBuf>,
+    /// Where the mouse was released, in client coordinates.
+    pub point: Point,
+}
+
+/// A path from UTF-16 without its terminator, lossless on Windows.
+pub fn path_from_wide(units: &[u16]) -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        std::ffi::OsString::from_wide(units).into()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        String::from_utf16_lossy(units).into()
+    }
+}
+
+/// The UTF-16 of a path without a terminator.
+pub fn path_to_wide(path: &Path) -> Vec<u16> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        path.as_os_str().encode_wide().collect()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        path.to_string_lossy().encode_utf16().collect()
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+impl FilesDropped {
+    pub fn new(paths: impl IntoIterator<Item = impl Into<PathBuf>>, point: Point) -> Self {
+        Self { paths: paths.into_iter().map(Into::into).collect(), point }
+    }
+
+    /// The `DROPFILES` block an `HDROP` points at, with wide paths and a client area point.
+    pub fn to_dropfiles(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + 2);
+        bytes.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.point.x.to_le_bytes());
+        bytes.extend_from_slice(&self.point.y.to_le_bytes());
+        // Not in the non-client area, and wide.
+        bytes.extend_from_slice(&0_i32.to_le_bytes());
+        bytes.extend_from_slice(&1_i32.to_le_bytes());
+        for path in &self.paths {
+            for unit in path_to_wide(path).into_iter().chain([0]) {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    /// Reads a `DROPFILES` block, `None` if it is cut short or uses ANSI paths.
+    pub fn from_dropfiles(bytes: &[u8]) -> Option<Self> {
+        let offset = read_u32(bytes, 0)? as usize;
+        let point = Point::new(read_u32(bytes, 4)? as i32, read_u32(bytes, 8)? as i32);
+        if read_u32(bytes, 16)? == 0 {
+            return None;
+        }
+        let units: Vec<u16> =
+            bytes.get(offset..)?.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+        let mut paths = Vec::new();
+        let mut rest = &units[..];
+        loop {
+            let end = rest.iter().position(|&unit| unit == 0)?;
+            if end == 0 {
+                return Some(Self { paths, point });
+            }
+            paths.push(path_from_wide(&rest[..end]));
+            rest = &rest[end + 1..];
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use core::ptr::null_mut;
+
+    use super::{path_from_wide, FilesDropped};
+    use crate::win32::*;
+
+    /// Turns `WM_DROPFILES` on or off for `hwnd`, see [`DragAcceptFiles`](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragacceptfiles)
+    ///
+    /// # Safety
+    /// `hwnd` must be a window handle, [`Window::accept_files`](crate::window::Window::accept_files)
+    /// is the safe way.
+    pub unsafe fn accept_files(hwnd: HWND, accept: bool) {
+        DragAcceptFiles(hwnd, accept as BOOL);
+    }
+
+    impl FilesDropped {
+        /// Reads the files and point out of `hdrop`, then releases it with `DragFinish`.
+        ///
+        /// # Safety
+        /// `hdrop` must be the `WPARAM` of a `WM_DROPFILES` that was not finished yet.
+        pub unsafe fn from_hdrop(hdrop: HDROP) -> Self {
+            let count = DragQueryFileW(hdrop, u32::MAX, null_mut(), 0);
+            let mut paths = Vec::with_capacity(count as usize);
+            for index in 0..count {
+                let length = DragQueryFileW(hdrop, index, null_mut(), 0);
+                let mut buffer = vec![0_u16; length as usize + 1];
+                let copied = DragQueryFileW(hdrop, index, buffer.as_mut_ptr(), buffer.len() as UINT);
+                paths.push(path_from_wide(&buffer[..copied as usize]));
+            }
+            let mut point = POINT::default();
+            DragQueryPoint(hdrop, &mut point);
+            DragFinish(hdrop);
+            Self { paths, point: point.into() }
+        }
+
+        /// The drop of a `WM_DROPFILES`, `None` for other messages.
+        ///
+        /// # Safety
+        /// Call this at most once per message, the `HDROP` is released.
+        pub unsafe fn from_message(msg: UINT, w_param: WPARAM) -> Option<Self> {
+            (msg == WM_DROPFILES).then(|| Self::from_hdrop(w_param as HDROP))
+        }
+
+        /// Sends this to `hwnd` as a `WM_DROPFILES`, as if the files were dropped from Explorer.
+        ///
+        /// The receiver releases the memory with `DragFinish`, a window that ignores the message
+        /// leaks it just like with a real drop.
+        ///
+        /// # Safety
+        /// `hwnd` must be a window handle.
+        pub unsafe fn send_to(&self, hwnd: HWND) -> Result<LRESULT, Win32Error> {
+            let hdrop = global_alloc_copy(&self.to_dropfiles())?;
+            Ok(SendMessageW(hwnd, WM_DROPFILES, hdrop as WPARAM, 0))
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropfiles_round_trip() {
+        let drop = FilesDropped::new(["C:\\scenes\\cube.scene", "D:\\Bilder\\Käse 🧀.bmp"], Point::new(12, -3));
+        let bytes = drop.to_dropfiles();
+        assert_eq!(read_u32(&bytes, 0), Some(HEADER_SIZE as u32));
+        assert_eq!(&bytes[bytes.len() - 4..], [0, 0, 0, 0]);
+        assert_eq!(FilesDropped::from_dropfiles(&bytes), Some(drop));
+
+        let empty = FilesDropped::default();
+        assert_eq!(FilesDropped::from_dropfiles(&empty.to_dropfiles()), Some(empty));
+    }
+
+    #[test]
+    fn rejects_short_and_ansi_blocks() {
+        let mut bytes = FilesDropped::new(["a.scene"], Point::new(1, 2)).to_dropfiles();
+        assert_eq!(FilesDropped::from_dropfiles(&bytes[..bytes.len() - 2]), None);
+        bytes[16] = 0;
+        assert_eq!(FilesDropped::from_dropfiles(&bytes), None);
+        assert_eq!(FilesDropped::from_dropfiles(&[]), None);
+    }
+
+    #[test]
+    fn wide_paths() {
+        let path = Path::new("scenes/ünïcode.scene");
+        let wide = path_to_wide(path);
+        assert_eq!(wide.len(), path.to_str().unwrap().encode_utf16().count());
+        assert_eq!(path_from_wide(&wide), path);
+    }
+}
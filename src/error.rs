@@ -0,0 +1,34 @@
+/// An error code as reported by [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror).
+///
+/// The type itself is platform independent so that code which reports Win32 failures can also be
+/// compiled and tested off Windows. On Windows the `Display` impl asks the system for the message
+/// text, elsewhere it just prints the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Win32Error(pub u32);
+impl std::error::Error for Win32Error {}
+
+impl Win32Error {
+    /// Bit 29 is reserved for application defined error codes.
+    pub const APPLICATION_ERROR_BIT: u32 = 1 << 29;
+
+    /// Builds an application defined error code, these never collide with system codes.
+    pub const fn application(code: u32) -> Self {
+        Self(code | Self::APPLICATION_ERROR_BIT)
+    }
+
+    pub const fn is_application_error(self) -> bool {
+        self.0 & Self::APPLICATION_ERROR_BIT > 0
+    }
+}
+
+#[cfg(not(windows))]
+impl core::fmt::Display for Win32Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.is_application_error() {
+            write!(f, "Win32ApplicationError({})", self.0)
+        } else {
+            write!(f, "Win32Error({})", self.0)
+        }
+    }
+}
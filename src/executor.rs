@@ -0,0 +1,590 @@
+//! A single threaded executor for writing UI flows as `async` code.
+//!
+//! Tasks live on the UI thread and may hold on to anything, `Rc`s and window handles included.
+//! Waking a task puts it into the ready queue, and the first wake after the queue was emptied
+//! notifies the UI thread, which on Windows means posting a thread message. The message loop
+//! ([`run_message_loop_with_executor`](crate::window::run_message_loop_with_executor)) then
+//! polls the ready tasks between messages and sleeps in `MsgWaitForMultipleObjectsEx` until the
+//! next message or timer.
+//!
+//! [`spawn_local`] and [`sleep`] find the executor of the task they are called from, so nested
+//! code doesn't need to pass it around. [`Events`] lets tasks wait for something the window
+//! procedure sees, like the next click.
+//!
+//! The time comes from a [`Clock`], so tests drive timers with a `MockClock`.
+
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::Wake;
+
+use crate::animation::{Clock, SystemClock};
+use crate::dispatch::Notify;
+
+type TaskId = u64;
+type TimerKey = (Duration, u64);
+
+struct ReadyQueue {
+    ids: Mutex<VecDeque<TaskId>>,
+    notify: Box<dyn Notify>,
+}
+
+impl ReadyQueue {
+    fn push(&self, id: TaskId) {
+        let mut ids = self.ids.lock().unwrap_or_else(PoisonError::into_inner);
+        ids.push_back(id);
+        // Nobody is left to run the task if this fails, so there is nothing to do about it.
+        if ids.len() == 1 {
+            self.notify.notify();
+        }
+    }
+
+    fn take(&self) -> VecDeque<TaskId> {
+        core::mem::take(&mut *self.ids.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ids.lock().unwrap_or_else(PoisonError::into_inner).is_empty()
+    }
+}
+
+/// Wakers may be used from any thread, so they only carry the task's id.
+struct TaskWaker {
+    id: TaskId,
+    /// Keeps a task from being queued twice when it is woken repeatedly before it runs.
+    scheduled: AtomicBool,
+    queue: Arc<ReadyQueue>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.scheduled.swap(true, Ordering::SeqCst) {
+            self.queue.push(self.id);
+        }
+    }
+}
+
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Arc<TaskWaker>,
+}
+
+struct Core {
+    tasks: RefCell<HashMap<TaskId, Task>>,
+    next_id: Cell<u64>,
+    ready: Arc<ReadyQueue>,
+    clock: Box<dyn Clock>,
+    timers: RefCell<BTreeMap<TimerKey, Waker>>,
+}
+
+impl Core {
+    fn next_id(&self) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        let id = self.next_id();
+        let waker = Arc::new(TaskWaker { id, scheduled: AtomicBool::new(true), queue: Arc::clone(&self.ready) });
+        self.tasks.borrow_mut().insert(id, Task { future: Box::pin(future), waker });
+        self.ready.push(id);
+    }
+
+    fn fire_timers(&self) {
+        let now = self.clock.now();
+        let expired = {
+            let mut timers = self.timers.borrow_mut();
+            let pending = timers.split_off(&(now, u64::MAX));
+            core::mem::replace(&mut *timers, pending)
+        };
+        expired.into_values().for_each(Waker::wake);
+    }
+}
+
+thread_local! {
+    /// The executor whose task is being polled.
+    static CURRENT: RefCell<Option<Rc<Core>>> = const { RefCell::new(None) };
+}
+
+/// Makes `core` the current executor until dropped, then restores the previous one.
+struct Enter {
+    previous: Option<Rc<Core>>,
+}
+
+impl Enter {
+    fn new(core: &Rc<Core>) -> Self {
+        Self { previous: CURRENT.with(|current| current.replace(Some(Rc::clone(core)))) }
+    }
+}
+
+impl Drop for Enter {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+fn current(what: &str) -> Rc<Core> {
+    CURRENT
+        .with(|current| current.borrow().clone())
+        .unwrap_or_else(|| panic!("{} must be called from a task of an `Executor`", what))
+}
+
+struct JoinState<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// The output of a spawned task. Dropping it lets the task run on unobserved.
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Whether the task finished and its output was not taken yet.
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().output.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+        let mut state = self.state.borrow_mut();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn spawn_on<F>(core: &Core, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+{
+    let state = Rc::new(RefCell::new(JoinState { output: None, waker: None }));
+    let shared = Rc::clone(&state);
+    core.spawn(async move {
+        let output = future.await;
+        let waker = {
+            let mut state = shared.borrow_mut();
+            state.output = Some(output);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+    JoinHandle { state }
+}
+
+/// Runs `future` on the executor of the calling task.
+///
+/// # Panics
+/// Outside of a task, use [`Executor::spawn_local`] there.
+pub fn spawn_local<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+{
+    spawn_on(&current("spawn_local"), future)
+}
+
+/// Completes once `duration` has passed since it was first polled.
+///
+/// # Panics
+/// When polled outside of a task.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep { duration, timer: None }
+}
+
+/// The future of [`sleep`].
+pub struct Sleep {
+    duration: Duration,
+    timer: Option<(Weak<Core>, TimerKey)>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let core = current("sleep");
+        let now = core.clock.now();
+        let key = match &self.timer {
+            Some((_, key)) => *key,
+            None => (now + self.duration, core.next_id()),
+        };
+        if now >= key.0 {
+            core.timers.borrow_mut().remove(&key);
+            self.timer = None;
+            return Poll::Ready(());
+        }
+        core.timers.borrow_mut().insert(key, cx.waker().clone());
+        self.timer = Some((Rc::downgrade(&core), key));
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some((core, key)) = self.timer.take() {
+            if let Some(core) = core.upgrade() {
+                core.timers.borrow_mut().remove(&key);
+            }
+        }
+    }
+}
+
+/// Runs tasks on the thread that created it.
+pub struct Executor {
+    core: Rc<Core>,
+}
+
+impl Executor {
+    /// An executor calling `notify` when a task becomes ready while none were.
+    pub fn new(notify: impl Notify + 'static) -> Self {
+        Self::with_clock(SystemClock::new(), notify)
+    }
+
+    /// Like [`new`](Self::new), with timers going by `clock`.
+    pub fn with_clock(clock: impl Clock + 'static, notify: impl Notify + 'static) -> Self {
+        let ready = Arc::new(ReadyQueue { ids: Mutex::new(VecDeque::new()), notify: Box::new(notify) });
+        let core = Core {
+            tasks: RefCell::new(HashMap::new()),
+            next_id: Cell::new(0),
+            ready,
+            clock: Box::new(clock),
+            timers: RefCell::new(BTreeMap::new()),
+        };
+        Self { core: Rc::new(core) }
+    }
+
+    /// Runs `future` as a new task, it is first polled by the next [`run_ready`](Self::run_ready).
+    pub fn spawn_local<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        spawn_on(&self.core, future)
+    }
+
+    /// Wakes the expired timers, then polls every task that is ready, returning how many.
+    ///
+    /// Tasks woken meanwhile wait for the next call, so a task that keeps waking itself can't
+    /// starve the message loop.
+    pub fn run_ready(&self) -> usize {
+        let _enter = Enter::new(&self.core);
+        self.core.fire_timers();
+        let mut polled = 0;
+        for id in self.core.ready.take() {
+            // Finished tasks may still be woken by wakers they left behind.
+            let Some(mut task) = self.core.tasks.borrow_mut().remove(&id) else {
+                continue;
+            };
+            task.waker.scheduled.store(false, Ordering::SeqCst);
+            let waker = Waker::from(Arc::clone(&task.waker));
+            polled += 1;
+            if task.future.as_mut().poll(&mut Context::from_waker(&waker)).is_pending() {
+                self.core.tasks.borrow_mut().insert(id, task);
+            }
+        }
+        polled
+    }
+
+    /// Whether a task is waiting to be polled.
+    pub fn has_ready(&self) -> bool {
+        !self.core.ready.is_empty()
+    }
+
+    /// How many tasks haven't finished yet.
+    pub fn task_count(&self) -> usize {
+        self.core.tasks.borrow().len()
+    }
+
+    /// The time until the earliest timer expires, zero if one already has.
+    pub fn next_timer(&self) -> Option<Duration> {
+        let timers = self.core.timers.borrow();
+        let (deadline, _) = timers.keys().next()?;
+        Some(deadline.saturating_sub(self.core.clock.now()))
+    }
+
+    /// How long the message loop may sleep, in whole milliseconds rounded up so it doesn't wake
+    /// just before a timer and spin. `None` means until the next message.
+    pub fn wait_millis(&self) -> Option<u32> {
+        if self.has_ready() {
+            return Some(0);
+        }
+        let next = self.next_timer()?;
+        let millis = next.as_nanos().div_ceil(1_000_000);
+        // `INFINITE` is `u32::MAX`, so stop just short of it.
+        Some(millis.min(u32::MAX as u128 - 1) as u32)
+    }
+}
+
+struct Waiter<T> {
+    filter: Box<dyn Fn(&T) -> bool>,
+    event: RefCell<Option<T>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+/// Events that tasks can wait for, emitted by whoever sees them (e.g. a window procedure).
+///
+/// Only events emitted after [`next`](Self::next) or [`wait_for`](Self::wait_for) was called
+/// count. Each waiting future takes the first event matching its filter.
+pub struct Events<T> {
+    waiting: Rc<RefCell<Vec<Weak<Waiter<T>>>>>,
+}
+
+impl<T> Clone for Events<T> {
+    fn clone(&self) -> Self {
+        Self { waiting: Rc::clone(&self.waiting) }
+    }
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self { waiting: Rc::default() }
+    }
+}
+
+impl<T: Clone + 'static> Events<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The next event.
+    pub fn next(&self) -> NextEvent<T> {
+        self.wait_for(|_| true)
+    }
+
+    /// The next event that `filter` accepts.
+    pub fn wait_for(&self, filter: impl Fn(&T) -> bool + 'static) -> NextEvent<T> {
+        let waiter = Rc::new(Waiter { filter: Box::new(filter), event: RefCell::new(None), waker: RefCell::new(None) });
+        self.waiting.borrow_mut().push(Rc::downgrade(&waiter));
+        NextEvent { waiter }
+    }
+
+    /// Hands `event` to everyone waiting for it, returning how many that were.
+    pub fn emit(&self, event: &T) -> usize {
+        let mut wakers = Vec::new();
+        self.waiting.borrow_mut().retain(|waiter| {
+            let Some(waiter) = waiter.upgrade() else {
+                return false;
+            };
+            if !(waiter.filter)(event) {
+                return true;
+            }
+            *waiter.event.borrow_mut() = Some(event.clone());
+            wakers.extend(waiter.waker.borrow_mut().take());
+            false
+        });
+        // Woken after the list is released, a waker is free to wait for the next event.
+        let count = wakers.len();
+        wakers.into_iter().for_each(Waker::wake);
+        count
+    }
+
+    /// How many futures are still waiting.
+    pub fn waiting(&self) -> usize {
+        self.waiting.borrow().iter().filter(|waiter| waiter.strong_count() > 0).count()
+    }
+}
+
+/// The future of [`Events::next`] and [`Events::wait_for`].
+pub struct NextEvent<T> {
+    waiter: Rc<Waiter<T>>,
+}
+
+impl<T> Future for NextEvent<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+        match self.waiter.event.borrow_mut().take() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                *self.waiter.waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::Executor;
+    use crate::dispatch::register_window_message;
+    use crate::win32::*;
+
+    /// The thread message that wakes the message loop, registered once per session.
+    const WAKE_MESSAGE: &str = "triangle_from_scratch Executor";
+
+    impl Executor {
+        /// An executor whose wakers post a thread message to the calling thread, see
+        /// [`PostThreadMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postthreadmessagew)
+        ///
+        /// Modal loops (message boxes, menus, resizing) drop thread messages, which is fine since
+        /// the library loop polls whatever is ready each time around.
+        pub fn for_current_thread() -> Result<Self, Win32Error> {
+            let message = register_window_message(WAKE_MESSAGE)?;
+            let thread = unsafe { GetCurrentThreadId() };
+            Ok(Self::new(move || unsafe { PostThreadMessageW(thread, message, 0, 0) } != 0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::MockClock;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    /// Counts the notifications of an executor.
+    fn counting() -> (Arc<AtomicUsize>, impl Notify) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        (count, move || counter.fetch_add(1, Ordering::SeqCst) < usize::MAX)
+    }
+
+    /// A fake waker counting its wakes.
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn spawns_and_joins() {
+        let (notified, notify) = counting();
+        let executor = Executor::new(notify);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let inner_log = Rc::clone(&log);
+        let outer = executor.spawn_local(async move {
+            let inner = spawn_local(async { 6 * 7 });
+            inner_log.borrow_mut().push("spawned");
+            let answer = inner.await;
+            inner_log.borrow_mut().push("joined");
+            answer
+        });
+        assert_eq!(notified.load(Ordering::SeqCst), 1);
+        assert_eq!(executor.run_ready(), 1);
+        assert_eq!(*log.borrow(), ["spawned"]);
+        // The inner task ran, which woke the outer one.
+        assert_eq!(executor.run_ready(), 1);
+        assert_eq!(executor.run_ready(), 1);
+        assert!(outer.is_finished());
+        assert_eq!(*log.borrow(), ["spawned", "joined"]);
+        assert_eq!(executor.task_count(), 0);
+        assert_eq!(executor.run_ready(), 0);
+    }
+
+    #[test]
+    fn woken_from_another_thread() {
+        let (notified, notify) = counting();
+        let executor = Executor::new(notify);
+        let flag = Arc::new(Mutex::new((false, None::<Waker>)));
+        let shared = Arc::clone(&flag);
+        let done = executor.spawn_local(core::future::poll_fn(move |cx| {
+            let mut flag = shared.lock().unwrap();
+            if flag.0 {
+                return Poll::Ready(());
+            }
+            flag.1 = Some(cx.waker().clone());
+            Poll::Pending
+        }));
+        executor.run_ready();
+        assert!(!executor.has_ready());
+        thread::spawn(move || {
+            let mut flag = flag.lock().unwrap();
+            flag.0 = true;
+            flag.1.take().unwrap().wake();
+        })
+        .join()
+        .unwrap();
+        assert!(executor.has_ready());
+        assert_eq!(notified.load(Ordering::SeqCst), 2);
+        executor.run_ready();
+        assert!(done.is_finished());
+    }
+
+    #[test]
+    fn timers_follow_the_clock() {
+        let clock = MockClock::new();
+        let executor = Executor::with_clock(clock.clone(), || true);
+        let done = executor.spawn_local(async {
+            sleep(Duration::from_millis(100)).await;
+            sleep(Duration::from_millis(10)).await;
+        });
+        assert_eq!(executor.wait_millis(), Some(0));
+        executor.run_ready();
+        assert_eq!(executor.next_timer(), Some(Duration::from_millis(100)));
+        clock.advance(Duration::from_micros(99_500));
+        assert_eq!(executor.run_ready(), 0);
+        // Rounded up, so the loop doesn't wake half a millisecond early.
+        assert_eq!(executor.wait_millis(), Some(1));
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(executor.run_ready(), 1);
+        assert_eq!(executor.next_timer(), Some(Duration::from_millis(10)));
+        clock.advance(Duration::from_millis(10));
+        executor.run_ready();
+        assert!(done.is_finished());
+        assert_eq!(executor.wait_millis(), None);
+    }
+
+    #[test]
+    fn dropped_sleeps_cancel_their_timer() {
+        let executor = Executor::with_clock(MockClock::new(), || true);
+        let events = Events::<u32>::new();
+        let next = events.next();
+        executor.spawn_local(async move {
+            // Whichever comes first, the event wins here.
+            let mut sleep = Box::pin(sleep(Duration::from_secs(5)));
+            let mut next = Box::pin(next);
+            core::future::poll_fn(|cx| match next.as_mut().poll(cx) {
+                Poll::Ready(event) => Poll::Ready(event),
+                Poll::Pending => sleep.as_mut().poll(cx).map(|_| 0),
+            })
+            .await
+        });
+        executor.run_ready();
+        assert_eq!(executor.next_timer(), Some(Duration::from_secs(5)));
+        events.emit(&7);
+        executor.run_ready();
+        assert_eq!(executor.task_count(), 0);
+        assert_eq!(executor.next_timer(), None);
+    }
+
+    #[test]
+    fn events_with_a_fake_waker() {
+        let events = Events::new();
+        let mut clicks = Box::pin(events.wait_for(|&button: &u32| button > 1));
+        let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&wakes));
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(clicks.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(events.emit(&1), 0);
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 0);
+        assert_eq!(events.emit(&2), 1);
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+        assert_eq!(clicks.as_mut().poll(&mut cx), Poll::Ready(2));
+        assert_eq!(events.waiting(), 0);
+        // Dropped futures stop waiting.
+        drop(events.next());
+        assert_eq!(events.emit(&3), 0);
+    }
+}
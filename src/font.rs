@@ -0,0 +1,138 @@
+//! A tiny bitmap font for drawing text without GDI.
+//!
+//! The glyphs are the printable ASCII range of the public domain X11 `5x7` "fixed" font. Every
+//! glyph is 7 rows of 5 pixels, each row stored in the low 5 bits of a byte with the leftmost pixel
+//! in bit 4.
+
+/// The width of a glyph in pixels.
+pub const GLYPH_WIDTH: u32 = 5;
+/// The height of a glyph in pixels.
+pub const GLYPH_HEIGHT: u32 = 7;
+/// The horizontal distance from one character to the next.
+pub const ADVANCE: u32 = GLYPH_WIDTH + 1;
+/// The vertical distance from one line to the next.
+pub const LINE_HEIGHT: u32 = GLYPH_HEIGHT + 1;
+
+const FIRST: char = ' ';
+const LAST: char = '~';
+
+/// The glyph for `ch`, characters outside printable ASCII are drawn as `?`.
+pub fn glyph(ch: char) -> &'static [u8; 7] {
+    let ch = if (FIRST..=LAST).contains(&ch) { ch } else { '?' };
+    &GLYPHS[ch as usize - FIRST as usize]
+}
+
+/// Whether the pixel at column `x`, row `y` of `ch` is set.
+pub fn is_set(ch: char, x: u32, y: u32) -> bool {
+    x < GLYPH_WIDTH && y < GLYPH_HEIGHT && glyph(ch)[y as usize] >> (GLYPH_WIDTH - 1 - x) & 1 == 1
+}
+
+/// The size in pixels of `text` when drawn, lines are split on `\n`.
+pub fn text_size(text: &str) -> (u32, u32) {
+    let lines = text.split('\n');
+    let (mut width, mut height) = (0, 0);
+    for line in lines {
+        let chars = line.chars().count() as u32;
+        width = width.max((chars * ADVANCE).saturating_sub(1));
+        height += LINE_HEIGHT;
+    }
+    (width, height - 1)
+}
+
+const GLYPHS: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00], // '!'
+    [0x0a, 0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x00, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x00], // '#'
+    [0x00, 0x0e, 0x14, 0x0e, 0x05, 0x0e, 0x00], // '$'
+    [0x10, 0x12, 0x04, 0x08, 0x12, 0x02, 0x00], // '%'
+    [0x00, 0x08, 0x14, 0x08, 0x14, 0x0a, 0x00], // '&'
+    [0x04, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x04, 0x08, 0x08, 0x08, 0x08, 0x04, 0x00], // '('
+    [0x08, 0x04, 0x04, 0x04, 0x04, 0x08, 0x00], // ')'
+    [0x00, 0x0a, 0x04, 0x0e, 0x04, 0x0a, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x06, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1e, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c, 0x00], // '.'
+    [0x00, 0x02, 0x04, 0x08, 0x10, 0x00, 0x00], // '/'
+    [0x04, 0x0a, 0x0a, 0x0a, 0x0a, 0x04, 0x00], // '0'
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x0e, 0x00], // '1'
+    [0x0c, 0x12, 0x02, 0x04, 0x08, 0x1e, 0x00], // '2'
+    [0x1e, 0x02, 0x0c, 0x02, 0x12, 0x0c, 0x00], // '3'
+    [0x04, 0x0c, 0x14, 0x1e, 0x04, 0x04, 0x00], // '4'
+    [0x1e, 0x10, 0x1c, 0x02, 0x12, 0x0c, 0x00], // '5'
+    [0x0c, 0x10, 0x1c, 0x12, 0x12, 0x0c, 0x00], // '6'
+    [0x1e, 0x02, 0x04, 0x04, 0x08, 0x08, 0x00], // '7'
+    [0x0c, 0x12, 0x0c, 0x12, 0x12, 0x0c, 0x00], // '8'
+    [0x0c, 0x12, 0x12, 0x0e, 0x02, 0x0c, 0x00], // '9'
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00], // ':'
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x08, 0x10], // ';'
+    [0x00, 0x02, 0x04, 0x08, 0x04, 0x02, 0x00], // '<'
+    [0x00, 0x00, 0x1e, 0x00, 0x1e, 0x00, 0x00], // '='
+    [0x00, 0x08, 0x04, 0x02, 0x04, 0x08, 0x00], // '>'
+    [0x04, 0x0a, 0x02, 0x04, 0x00, 0x04, 0x00], // '?'
+    [0x0c, 0x12, 0x16, 0x16, 0x10, 0x0c, 0x00], // '@'
+    [0x0c, 0x12, 0x12, 0x1e, 0x12, 0x12, 0x00], // 'A'
+    [0x1c, 0x12, 0x1c, 0x12, 0x12, 0x1c, 0x00], // 'B'
+    [0x0c, 0x12, 0x10, 0x10, 0x12, 0x0c, 0x00], // 'C'
+    [0x1c, 0x12, 0x12, 0x12, 0x12, 0x1c, 0x00], // 'D'
+    [0x1e, 0x10, 0x1c, 0x10, 0x10, 0x1e, 0x00], // 'E'
+    [0x1e, 0x10, 0x1c, 0x10, 0x10, 0x10, 0x00], // 'F'
+    [0x0c, 0x12, 0x10, 0x16, 0x12, 0x0e, 0x00], // 'G'
+    [0x12, 0x12, 0x1e, 0x12, 0x12, 0x12, 0x00], // 'H'
+    [0x0e, 0x04, 0x04, 0x04, 0x04, 0x0e, 0x00], // 'I'
+    [0x02, 0x02, 0x02, 0x02, 0x12, 0x0c, 0x00], // 'J'
+    [0x12, 0x14, 0x18, 0x18, 0x14, 0x12, 0x00], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x1e, 0x00], // 'L'
+    [0x12, 0x1e, 0x1e, 0x12, 0x12, 0x12, 0x00], // 'M'
+    [0x12, 0x1a, 0x1a, 0x16, 0x16, 0x12, 0x00], // 'N'
+    [0x0c, 0x12, 0x12, 0x12, 0x12, 0x0c, 0x00], // 'O'
+    [0x1c, 0x12, 0x12, 0x1c, 0x10, 0x10, 0x00], // 'P'
+    [0x0c, 0x12, 0x12, 0x12, 0x1a, 0x0c, 0x02], // 'Q'
+    [0x1c, 0x12, 0x12, 0x1c, 0x14, 0x12, 0x00], // 'R'
+    [0x0c, 0x12, 0x08, 0x04, 0x12, 0x0c, 0x00], // 'S'
+    [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 'T'
+    [0x12, 0x12, 0x12, 0x12, 0x12, 0x0c, 0x00], // 'U'
+    [0x12, 0x12, 0x12, 0x12, 0x0c, 0x0c, 0x00], // 'V'
+    [0x12, 0x12, 0x12, 0x1e, 0x1e, 0x12, 0x00], // 'W'
+    [0x12, 0x12, 0x0c, 0x0c, 0x12, 0x12, 0x00], // 'X'
+    [0x0a, 0x0a, 0x0a, 0x04, 0x04, 0x04, 0x00], // 'Y'
+    [0x1e, 0x02, 0x04, 0x08, 0x10, 0x1e, 0x00], // 'Z'
+    [0x0e, 0x08, 0x08, 0x08, 0x08, 0x0e, 0x00], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x00, 0x00], // '\\'
+    [0x0e, 0x02, 0x02, 0x02, 0x02, 0x0e, 0x00], // ']'
+    [0x04, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1e, 0x00], // '_'
+    [0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0e, 0x12, 0x16, 0x0a, 0x00], // 'a'
+    [0x10, 0x10, 0x1c, 0x12, 0x12, 0x1c, 0x00], // 'b'
+    [0x00, 0x00, 0x0c, 0x10, 0x10, 0x0c, 0x00], // 'c'
+    [0x02, 0x02, 0x0e, 0x12, 0x12, 0x0e, 0x00], // 'd'
+    [0x00, 0x00, 0x0c, 0x16, 0x18, 0x0c, 0x00], // 'e'
+    [0x04, 0x0a, 0x08, 0x1c, 0x08, 0x08, 0x00], // 'f'
+    [0x00, 0x00, 0x0e, 0x12, 0x0c, 0x10, 0x0e], // 'g'
+    [0x10, 0x10, 0x1c, 0x12, 0x12, 0x12, 0x00], // 'h'
+    [0x04, 0x00, 0x0c, 0x04, 0x04, 0x0e, 0x00], // 'i'
+    [0x02, 0x00, 0x02, 0x02, 0x02, 0x0a, 0x04], // 'j'
+    [0x10, 0x10, 0x14, 0x18, 0x14, 0x12, 0x00], // 'k'
+    [0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e, 0x00], // 'l'
+    [0x00, 0x00, 0x14, 0x1e, 0x12, 0x12, 0x00], // 'm'
+    [0x00, 0x00, 0x1c, 0x12, 0x12, 0x12, 0x00], // 'n'
+    [0x00, 0x00, 0x0c, 0x12, 0x12, 0x0c, 0x00], // 'o'
+    [0x00, 0x00, 0x1c, 0x12, 0x12, 0x1c, 0x10], // 'p'
+    [0x00, 0x00, 0x0e, 0x12, 0x12, 0x0e, 0x02], // 'q'
+    [0x00, 0x00, 0x1c, 0x12, 0x10, 0x10, 0x00], // 'r'
+    [0x00, 0x00, 0x0e, 0x18, 0x06, 0x1c, 0x00], // 's'
+    [0x08, 0x08, 0x1c, 0x08, 0x08, 0x06, 0x00], // 't'
+    [0x00, 0x00, 0x12, 0x12, 0x12, 0x0e, 0x00], // 'u'
+    [0x00, 0x00, 0x0a, 0x0a, 0x0a, 0x04, 0x00], // 'v'
+    [0x00, 0x00, 0x12, 0x12, 0x1e, 0x1e, 0x00], // 'w'
+    [0x00, 0x00, 0x12, 0x0c, 0x0c, 0x12, 0x00], // 'x'
+    [0x00, 0x00, 0x12, 0x12, 0x0a, 0x04, 0x08], // 'y'
+    [0x00, 0x00, 0x1e, 0x04, 0x08, 0x1e, 0x00], // 'z'
+    [0x02, 0x04, 0x0c, 0x04, 0x04, 0x02, 0x00], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // '|'
+    [0x08, 0x04, 0x06, 0x04, 0x04, 0x08, 0x00], // '}'
+    [0x0a, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
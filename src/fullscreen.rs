@@ -0,0 +1,220 @@
+//! Borderless fullscreen, the way games and video players do it.
+//!
+//! Instead of changing the display mode, the window drops its frame, becomes a popup and is moved
+//! to cover the monitor it is on. The taskbar notices a window covering a whole monitor and gets
+//! out of the way. Leaving fullscreen puts back the saved styles and `WINDOWPLACEMENT`, which
+//! also remembers whether the window was maximized and where it goes when restored.
+//!
+//! The style changes and rectangles are plain functions, the Windows part only reads and writes
+//! the window.
+
+use crate::geometry::{Point, Rect};
+use crate::monitor::{self, monitor_for, Monitor};
+use crate::style::{WindowExStyle, WindowStyle};
+
+const FRAME_EX_STYLES: WindowExStyle = WindowExStyle::DLGMODALFRAME
+    .union(WindowExStyle::OVERLAPPEDWINDOW)
+    .union(WindowExStyle::STATICEDGE);
+
+/// The style of the fullscreen window: no caption, sizing border or system menu, but a popup.
+/// Everything else (like `WS_VISIBLE`) is kept.
+pub const fn fullscreen_style(style: WindowStyle) -> WindowStyle {
+    style.difference(WindowStyle::OVERLAPPEDWINDOW).union(WindowStyle::POPUP)
+}
+
+/// The extended style of the fullscreen window, without any of the edges.
+pub const fn fullscreen_ex_style(ex_style: WindowExStyle) -> WindowExStyle {
+    ex_style.difference(FRAME_EX_STYLES)
+}
+
+/// What the fullscreen window covers: the whole monitor showing most of `window`, taskbar
+/// included. `None` only without monitors.
+pub fn fullscreen_rect(window: Rect, monitors: &[Monitor]) -> Option<Rect> {
+    let bounds: Vec<Rect> = monitors.iter().map(|monitor| monitor.bounds).collect();
+    monitor_for(window, &bounds).map(|index| bounds[index])
+}
+
+/// The normal rectangle to restore when leaving fullscreen, moved onto a monitor if the one it
+/// was on is gone by now (see [`monitor::restore`]).
+///
+/// Like `WINDOWPLACEMENT`, `normal` is in workspace coordinates, which start at the primary
+/// monitor's work area, except for tool windows, which use screen coordinates.
+pub fn restored_rect(normal: Rect, ex_style: WindowExStyle, monitors: &[Monitor]) -> Option<Rect> {
+    let origin = if ex_style.contains(WindowExStyle::TOOLWINDOW) {
+        Point::new(0, 0)
+    } else {
+        monitor::primary(monitors)?.work_area.origin()
+    };
+    let restored = monitor::restore(normal.offset(origin.x, origin.y), monitors)?;
+    Some(restored.offset(-origin.x, -origin.y))
+}
+
+#[cfg(windows)]
+mod windows {
+    use core::ptr::null_mut;
+
+    use super::{fullscreen_ex_style, fullscreen_rect, fullscreen_style, restored_rect};
+    use crate::geometry::Rect;
+    use crate::monitor::{monitors, Monitor};
+    use crate::style::{WindowExStyle, WindowStyle};
+    use crate::win32::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct Saved {
+        style: LongPtr,
+        ex_style: LongPtr,
+        placement: WINDOWPLACEMENT,
+    }
+
+    /// Whether a window is fullscreen, and what to restore once it isn't.
+    #[derive(Debug, Clone, Default)]
+    pub struct Fullscreen {
+        saved: Option<Saved>,
+    }
+
+    fn set_frame(hwnd: HWND, style: LongPtr, ex_style: LongPtr, rect: Option<Rect>) -> Result<(), Win32Error> {
+        unsafe {
+            SetWindowLongPtrW(hwnd, GWL_STYLE, style);
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style);
+            // The frame is cached until told otherwise with `SWP_FRAMECHANGED`.
+            let flags = SWP_NOOWNERZORDER | SWP_FRAMECHANGED;
+            let placed = match rect {
+                Some(rect) => SetWindowPos(
+                    hwnd,
+                    HWND_TOP,
+                    rect.left,
+                    rect.top,
+                    rect.width(),
+                    rect.height(),
+                    flags,
+                ),
+                None => SetWindowPos(hwnd, null_mut(), 0, 0, 0, 0, flags | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER),
+            };
+            if placed == 0 {
+                return Err(get_last_error());
+            }
+        }
+        Ok(())
+    }
+
+    impl Fullscreen {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn is_active(&self) -> bool {
+            self.saved.is_some()
+        }
+
+        /// Enters or leaves fullscreen, doing nothing if `hwnd` already is in the wanted state.
+        ///
+        /// # Safety
+        /// `hwnd` must be a window handle, [`Window::set_fullscreen`](crate::window::Window::set_fullscreen)
+        /// is the safe way.
+        pub unsafe fn set(&mut self, hwnd: HWND, fullscreen: bool) -> Result<(), Win32Error> {
+            match (fullscreen, self.saved) {
+                (true, None) => {
+                    let mut placement = WINDOWPLACEMENT::default();
+                    if GetWindowPlacement(hwnd, &mut placement) == 0 {
+                        return Err(get_last_error());
+                    }
+                    let mut window = RECT::default();
+                    if GetWindowRect(hwnd, &mut window) == 0 {
+                        return Err(get_last_error());
+                    }
+                    let cover = match fullscreen_rect(window.into(), &monitors()?) {
+                        Some(rect) => rect,
+                        None => Monitor::from_window(hwnd)?.bounds,
+                    };
+                    let saved = Saved {
+                        style: GetWindowLongPtrW(hwnd, GWL_STYLE),
+                        ex_style: GetWindowLongPtrW(hwnd, GWL_EXSTYLE),
+                        placement,
+                    };
+                    let style = fullscreen_style(WindowStyle::from_bits(saved.style as u32)).bits() as LongPtr;
+                    let ex_style = fullscreen_ex_style(WindowExStyle::from_bits(saved.ex_style as u32)).bits() as LongPtr;
+                    if let Err(e) = set_frame(hwnd, style, ex_style, Some(cover)) {
+                        // The styles are set before moving, don't leave a frameless window behind.
+                        let _ = set_frame(hwnd, saved.style, saved.ex_style, None);
+                        return Err(e);
+                    }
+                    self.saved = Some(saved);
+                    Ok(())
+                }
+                (false, Some(mut saved)) => {
+                    // Monitors may have been unplugged or rearranged meanwhile.
+                    let ex_style = WindowExStyle::from_bits(saved.ex_style as u32);
+                    let normal = saved.placement.rc_normal_position.into();
+                    if let Some(rect) = restored_rect(normal, ex_style, &monitors()?) {
+                        saved.placement.rc_normal_position = rect.into();
+                    }
+                    set_frame(hwnd, saved.style, saved.ex_style, None)?;
+                    self.saved = None;
+                    if SetWindowPlacement(hwnd, &saved.placement) == 0 {
+                        return Err(get_last_error());
+                    }
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// Switches between fullscreen and windowed, returning whether it is fullscreen now.
+        ///
+        /// # Safety
+        /// `hwnd` must be a window handle.
+        pub unsafe fn toggle(&mut self, hwnd: HWND) -> Result<bool, Win32Error> {
+            let fullscreen = !self.is_active();
+            self.set(hwnd, fullscreen)?;
+            Ok(fullscreen)
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_lose_the_frame() {
+        let style = WindowStyle::OVERLAPPEDWINDOW | WindowStyle::VISIBLE | WindowStyle::CLIPCHILDREN;
+        assert_eq!(fullscreen_style(style), WindowStyle::POPUP | WindowStyle::VISIBLE | WindowStyle::CLIPCHILDREN);
+        assert_eq!(fullscreen_style(WindowStyle::POPUP), WindowStyle::POPUP);
+        assert!(fullscreen_style(style).validate().is_ok());
+        let ex_style = WindowExStyle::WINDOWEDGE | WindowExStyle::APPWINDOW;
+        assert_eq!(fullscreen_ex_style(ex_style), WindowExStyle::APPWINDOW);
+    }
+
+    // The taskbar is on the left of the primary monitor, so its work area doesn't start at 0, 0.
+    const MAIN: Monitor = Monitor::new(Rect::new(0, 0, 1920, 1080), Rect::new(48, 0, 1920, 1080), 96, true);
+    const RIGHT: Monitor = Monitor::new(Rect::new(1920, 0, 4480, 1440), Rect::new(1920, 0, 4480, 1400), 144, false);
+
+    #[test]
+    fn covers_the_monitor_with_most_of_the_window() {
+        let monitors = [MAIN, RIGHT];
+        assert_eq!(fullscreen_rect(Rect::new(100, 100, 900, 700), &monitors), Some(MAIN.bounds));
+        assert_eq!(fullscreen_rect(Rect::new(1800, 100, 2600, 700), &monitors), Some(RIGHT.bounds));
+        // Minimized windows sit far off screen, they go to the nearest monitor.
+        assert_eq!(fullscreen_rect(Rect::new(-32000, -32000, -31840, -31972), &monitors), Some(MAIN.bounds));
+        assert_eq!(fullscreen_rect(Rect::new(0, 0, 10, 10), &[]), None);
+    }
+
+    #[test]
+    fn restores_in_workspace_coordinates() {
+        let monitors = [MAIN, RIGHT];
+        // At 100, 100 on the screen, but 52, 100 in the workspace.
+        let normal = Rect::new(52, 100, 852, 700);
+        assert_eq!(restored_rect(normal, WindowExStyle::empty(), &monitors), Some(normal));
+        let on_right = Rect::new(2000, 100, 2800, 700);
+        assert_eq!(restored_rect(on_right, WindowExStyle::empty(), &monitors), Some(on_right));
+        // The right monitor was unplugged while fullscreen.
+        let centered = Rect::new(584, 240, 1384, 840);
+        assert_eq!(restored_rect(on_right, WindowExStyle::empty(), &[MAIN]), Some(centered.offset(-48, 0)));
+        // Tool windows use screen coordinates.
+        assert_eq!(restored_rect(Rect::new(10, 100, 810, 700), WindowExStyle::TOOLWINDOW, &[MAIN]), Some(Rect::new(48, 100, 848, 700)));
+        assert_eq!(restored_rect(normal, WindowExStyle::empty(), &[]), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! [`Canvas`] on top of a GDI device context.
+//!
+//! Pens and brushes are created per call and selected only for the duration of that call, so the
+//! device context is left exactly as it was handed over.
+
+use crate::canvas::{Canvas, Stroke, Style, Vertex};
+use crate::color::Color;
+use crate::geometry::{Point, Rect, Size};
+use crate::image::Rgba;
+use crate::palette::SysColor;
+use crate::win32::*;
+
+/// Like `PS_SOLID`, but wide outlines of closed shapes stay inside the bounding rectangle.
+const PS_INSIDEFRAME: CInt = 6;
+
+fn colorref(color: Rgba) -> COLORREF {
+    color.rgb().to_colorref()
+}
+
+fn check(ok: BOOL) -> Result<(), Win32Error> {
+    if ok != 0 {
+        Ok(())
+    } else {
+        Err(get_last_error())
+    }
+}
+
+/// A GDI object selected into a device context until dropped.
+///
+/// Objects we created are deleted again, stock objects are only deselected.
+struct Selected {
+    hdc: HDC,
+    previous: HGDIOBJ,
+    object: HGDIOBJ,
+    owned: bool,
+}
+
+impl Selected {
+    unsafe fn new(hdc: HDC, object: HGDIOBJ, owned: bool) -> Result<Self, Win32Error> {
+        if object.is_null() {
+            return Err(get_last_error());
+        }
+        let previous = SelectObject(hdc, object);
+        Ok(Self { hdc, previous, object, owned })
+    }
+
+    unsafe fn pen(hdc: HDC, stroke: Option<Stroke>, style: CInt) -> Result<Self, Win32Error> {
+        match stroke {
+            Some(stroke) => {
+                let pen = CreatePen(style, stroke.width.max(1) as CInt, colorref(stroke.color));
+                Self::new(hdc, pen, true)
+            }
+            None => Self::new(hdc, GetStockObject(NULL_PEN), false),
+        }
+    }
+
+    unsafe fn brush(hdc: HDC, fill: Option<Rgba>) -> Result<Self, Win32Error> {
+        match fill {
+            Some(color) => Self::new(hdc, CreateSolidBrush(colorref(color)), true),
+            None => Self::new(hdc, GetStockObject(NULL_BRUSH), false),
+        }
+    }
+}
+
+impl Drop for Selected {
+    fn drop(&mut self) {
+        unsafe {
+            SelectObject(self.hdc, self.previous);
+            if self.owned {
+                DeleteObject(self.object);
+            }
+        }
+    }
+}
+
+/// Draws on a device context, such as the one `BeginPaint` returns.
+///
+/// Text uses whatever font is selected into the device context, so unlike the rest it will not
+/// match [`crate::canvas::ImageCanvas`] pixel for pixel.
+pub struct GdiCanvas {
+    hdc: HDC,
+    size: Size,
+}
+
+impl GdiCanvas {
+    /// # Safety
+    /// `hdc` must be a valid device context for as long as the canvas is used.
+    pub unsafe fn new(hdc: HDC, size: Size) -> Self {
+        Self { hdc, size }
+    }
+
+    pub fn hdc(&self) -> HDC {
+        self.hdc
+    }
+
+    /// `TextOutW` does not break lines, so each one is placed below the previous by hand.
+    fn text_line_height(&self) -> CInt {
+        let mut size = SIZE::default();
+        let sample: Vec<u16> = "Xg".encode_utf16().collect();
+        if unsafe { GetTextExtentPoint32W(self.hdc, sample.as_ptr(), sample.len() as CInt, &mut size) } != 0 {
+            size.cy
+        } else {
+            16
+        }
+    }
+}
+
+impl Canvas for GdiCanvas {
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn fill_sys_color(&mut self, rect: Rect, color: SysColor) -> Result<(), Win32Error> {
+        unsafe { fill_rect_with_sys_color(self.hdc, &rect.into(), color) }
+    }
+
+    fn line(&mut self, from: Point, to: Point, stroke: Stroke) -> Result<(), Win32Error> {
+        unsafe {
+            let _pen = Selected::pen(self.hdc, Some(stroke), PS_SOLID)?;
+            check(MoveToEx(self.hdc, from.x, from.y, core::ptr::null_mut()))?;
+            check(LineTo(self.hdc, to.x, to.y))
+        }
+    }
+
+    fn rect(&mut self, rect: Rect, style: Style) -> Result<(), Win32Error> {
+        unsafe {
+            // `Rectangle` leaves out the right and bottom edge when there is no pen, `FillRect`
+            // covers exactly the rectangle.
+            if let Some(fill) = style.fill {
+                let brush = Selected::brush(self.hdc, Some(fill))?;
+                check(FillRect(self.hdc, &rect.into(), brush.object))?;
+            }
+            if style.stroke.is_some() {
+                let _pen = Selected::pen(self.hdc, style.stroke, PS_INSIDEFRAME)?;
+                let _brush = Selected::brush(self.hdc, None)?;
+                check(Rectangle(self.hdc, rect.left, rect.top, rect.right, rect.bottom))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn ellipse(&mut self, bounds: Rect, style: Style) -> Result<(), Win32Error> {
+        // Without a pen the shape shrinks by a pixel on the right and bottom, grow it back.
+        let Rect { left, top, mut right, mut bottom } = bounds;
+        if style.stroke.is_none() {
+            right += 1;
+            bottom += 1;
+        }
+        unsafe {
+            let _pen = Selected::pen(self.hdc, style.stroke, PS_INSIDEFRAME)?;
+            let _brush = Selected::brush(self.hdc, style.fill)?;
+            check(Ellipse(self.hdc, left, top, right, bottom))
+        }
+    }
+
+    fn polygon(&mut self, points: &[Point], style: Style) -> Result<(), Win32Error> {
+        if points.len() < 2 {
+            return Ok(());
+        }
+        let points: Vec<POINT> = points.iter().map(|&p| p.into()).collect();
+        unsafe {
+            let _pen = Selected::pen(self.hdc, style.stroke, PS_SOLID)?;
+            let _brush = Selected::brush(self.hdc, style.fill)?;
+            check(Polygon(self.hdc, points.as_ptr(), points.len() as CInt))
+        }
+    }
+
+    fn gradient_triangle(&mut self, vertices: [Vertex; 3]) -> Result<(), Win32Error> {
+        let vertices = vertices.map(|v| {
+            let [red, green, blue, alpha] = Color::from(v.color).to_color16();
+            TRIVERTIX { x: v.position.x, y: v.position.y, red, green, blue, alpha }
+        });
+        let mesh = GradientTriangle { vertex1: 0, vertex2: 1, vertex3: 2 };
+        unsafe {
+            check(GradientFill(
+                self.hdc,
+                vertices.as_ptr(),
+                vertices.len() as CUlong,
+                (&mesh as *const GradientTriangle).cast(),
+                1,
+                GRADIENT_FILL_TRIANGLE,
+            ))
+        }
+    }
+
+    fn text(&mut self, origin: Point, text: &str, color: Rgba) -> Result<(), Win32Error> {
+        unsafe {
+            let old_color = SetTextColor(self.hdc, colorref(color));
+            let old_mode = SetBkMode(self.hdc, TRANSPARENT);
+            let mut result = Ok(());
+            let height = self.text_line_height();
+            for (i, line) in text.split('\n').enumerate() {
+                let wide: Vec<u16> = line.encode_utf16().collect();
+                // Lines past `i32::MAX` can't be on the device anyway.
+                let Some(y) = (i as CInt).checked_mul(height).and_then(|offset| origin.y.checked_add(offset)) else {
+                    break;
+                };
+                result = check(TextOutW(self.hdc, origin.x, y, wide.as_ptr(), wide.len() as CInt));
+                if result.is_err() {
+                    break;
+                }
+            }
+            SetBkMode(self.hdc, old_mode);
+            SetTextColor(self.hdc, old_color);
+            result
+        }
+    }
+}
@@ -0,0 +1,90 @@
+/// A position in pixels, the platform independent twin of `POINT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A width and height in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Size {
+    pub const fn new(width: i32, height: i32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// A rectangle in pixels, the platform independent twin of `RECT`.
+///
+/// As with `RECT` the right and bottom edges are exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub const fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Self { left, top, right, bottom }
+    }
+
+    pub const fn from_origin_size(origin: Point, size: Size) -> Self {
+        Self::new(origin.x, origin.y, origin.x + size.width, origin.y + size.height)
+    }
+
+    pub const fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub const fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub const fn origin(&self) -> Point {
+        Point::new(self.left, self.top)
+    }
+
+    pub const fn size(&self) -> Size {
+        Size::new(self.width(), self.height())
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        // Compared rather than subtracted, which could overflow for far apart edges.
+        self.right <= self.left || self.bottom <= self.top
+    }
+
+    pub const fn contains(&self, point: Point) -> bool {
+        point.x >= self.left && point.x < self.right && point.y >= self.top && point.y < self.bottom
+    }
+
+    pub const fn offset(&self, dx: i32, dy: i32) -> Self {
+        Self::new(self.left + dx, self.top + dy, self.right + dx, self.bottom + dy)
+    }
+
+    /// The overlapping part of both rectangles, if there is one.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect::new(
+            self.left.max(other.left),
+            self.top.max(other.top),
+            self.right.min(other.right),
+            self.bottom.min(other.bottom),
+        );
+        if rect.is_empty() {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+}
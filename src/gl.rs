@@ -0,0 +1,146 @@
+//! The part of the OpenGL core profile the triangle renderer uses.
+//!
+//! Load it with [`Gl::load`] once a context is current, on Windows with a
+//! `gl_loader::WglResolver`. Adding a function is one more line below.
+
+use core::ffi::{c_char, c_void};
+
+pub type GLenum = u32;
+pub type GLbitfield = u32;
+pub type GLboolean = u8;
+pub type GLubyte = u8;
+pub type GLint = i32;
+pub type GLuint = u32;
+pub type GLsizei = i32;
+pub type GLfloat = f32;
+pub type GLchar = c_char;
+pub type GLsizeiptr = isize;
+pub type GLintptr = isize;
+
+pub const GL_FALSE: GLboolean = 0;
+pub const GL_TRUE: GLboolean = 1;
+pub const GL_NO_ERROR: GLenum = 0;
+pub const GL_DEPTH_BUFFER_BIT: GLbitfield = 0x0000_0100;
+pub const GL_STENCIL_BUFFER_BIT: GLbitfield = 0x0000_0400;
+pub const GL_COLOR_BUFFER_BIT: GLbitfield = 0x0000_4000;
+pub const GL_TRIANGLES: GLenum = 0x0004;
+pub const GL_LESS: GLenum = 0x0201;
+pub const GL_LEQUAL: GLenum = 0x0203;
+pub const GL_BACK: GLenum = 0x0405;
+pub const GL_CULL_FACE: GLenum = 0x0B44;
+pub const GL_DEPTH_TEST: GLenum = 0x0B71;
+pub const GL_BLEND: GLenum = 0x0BE2;
+pub const GL_TEXTURE_2D: GLenum = 0x0DE1;
+pub const GL_UNSIGNED_BYTE: GLenum = 0x1401;
+pub const GL_UNSIGNED_SHORT: GLenum = 0x1403;
+pub const GL_UNSIGNED_INT: GLenum = 0x1405;
+pub const GL_FLOAT: GLenum = 0x1406;
+pub const GL_RGBA: GLenum = 0x1908;
+pub const GL_VENDOR: GLenum = 0x1F00;
+pub const GL_RENDERER: GLenum = 0x1F01;
+pub const GL_VERSION: GLenum = 0x1F02;
+pub const GL_SRC_ALPHA: GLenum = 0x0302;
+pub const GL_ONE_MINUS_SRC_ALPHA: GLenum = 0x0303;
+pub const GL_ARRAY_BUFFER: GLenum = 0x8892;
+pub const GL_ELEMENT_ARRAY_BUFFER: GLenum = 0x8893;
+pub const GL_STATIC_DRAW: GLenum = 0x88E4;
+pub const GL_DYNAMIC_DRAW: GLenum = 0x88E8;
+pub const GL_FRAGMENT_SHADER: GLenum = 0x8B30;
+pub const GL_VERTEX_SHADER: GLenum = 0x8B31;
+pub const GL_COMPILE_STATUS: GLenum = 0x8B81;
+pub const GL_LINK_STATUS: GLenum = 0x8B82;
+pub const GL_INFO_LOG_LENGTH: GLenum = 0x8B84;
+pub const GL_SHADING_LANGUAGE_VERSION: GLenum = 0x8B8C;
+pub const GL_FRAMEBUFFER_SRGB: GLenum = 0x8DB9;
+
+crate::gl_api! {
+    /// OpenGL functions, call them only while the context they were loaded with is current.
+    pub struct Gl {
+        (1, 0) fn glClear(mask: GLbitfield);
+        (1, 0) fn glClearColor(red: GLfloat, green: GLfloat, blue: GLfloat, alpha: GLfloat);
+        (1, 0) fn glViewport(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+        (1, 0) fn glEnable(cap: GLenum);
+        (1, 0) fn glDisable(cap: GLenum);
+        (1, 0) fn glBlendFunc(sfactor: GLenum, dfactor: GLenum);
+        (1, 0) fn glDepthFunc(func: GLenum);
+        (1, 0) fn glCullFace(mode: GLenum);
+        (1, 0) fn glGetError() -> GLenum;
+        (1, 0) fn glGetIntegerv(pname: GLenum, data: *mut GLint);
+        (1, 0) fn glGetString(name: GLenum) -> *const GLubyte;
+        (1, 0) fn glPixelStorei(pname: GLenum, param: GLint);
+        (1, 0) fn glReadPixels(
+            x: GLint, y: GLint, width: GLsizei, height: GLsizei, format: GLenum, kind: GLenum, pixels: *mut c_void,
+        );
+        (1, 0) fn glTexParameteri(target: GLenum, pname: GLenum, param: GLint);
+        (1, 0) fn glTexImage2D(
+            target: GLenum, level: GLint, internal_format: GLint, width: GLsizei, height: GLsizei, border: GLint,
+            format: GLenum, kind: GLenum, pixels: *const c_void,
+        );
+        (1, 1) fn glDrawArrays(mode: GLenum, first: GLint, count: GLsizei);
+        (1, 1) fn glDrawElements(mode: GLenum, count: GLsizei, kind: GLenum, indices: *const c_void);
+        (1, 1) fn glGenTextures(n: GLsizei, textures: *mut GLuint);
+        (1, 1) fn glBindTexture(target: GLenum, texture: GLuint);
+        (1, 1) fn glDeleteTextures(n: GLsizei, textures: *const GLuint);
+        (1, 3) fn glActiveTexture(texture: GLenum);
+        (1, 5) fn glGenBuffers(n: GLsizei, buffers: *mut GLuint);
+        (1, 5) fn glBindBuffer(target: GLenum, buffer: GLuint);
+        (1, 5) fn glBufferData(target: GLenum, size: GLsizeiptr, data: *const c_void, usage: GLenum);
+        (1, 5) fn glBufferSubData(target: GLenum, offset: GLintptr, size: GLsizeiptr, data: *const c_void);
+        (1, 5) fn glDeleteBuffers(n: GLsizei, buffers: *const GLuint);
+        (2, 0) fn glCreateShader(kind: GLenum) -> GLuint;
+        (2, 0) fn glShaderSource(shader: GLuint, count: GLsizei, string: *const *const GLchar, length: *const GLint);
+        (2, 0) fn glCompileShader(shader: GLuint);
+        (2, 0) fn glGetShaderiv(shader: GLuint, pname: GLenum, params: *mut GLint);
+        (2, 0) fn glGetShaderInfoLog(shader: GLuint, buf_size: GLsizei, length: *mut GLsizei, info_log: *mut GLchar);
+        (2, 0) fn glDeleteShader(shader: GLuint);
+        (2, 0) fn glCreateProgram() -> GLuint;
+        (2, 0) fn glAttachShader(program: GLuint, shader: GLuint);
+        (2, 0) fn glLinkProgram(program: GLuint);
+        (2, 0) fn glGetProgramiv(program: GLuint, pname: GLenum, params: *mut GLint);
+        (2, 0) fn glGetProgramInfoLog(program: GLuint, buf_size: GLsizei, length: *mut GLsizei, info_log: *mut GLchar);
+        (2, 0) fn glUseProgram(program: GLuint);
+        (2, 0) fn glDeleteProgram(program: GLuint);
+        (2, 0) fn glGetAttribLocation(program: GLuint, name: *const GLchar) -> GLint;
+        (2, 0) fn glGetUniformLocation(program: GLuint, name: *const GLchar) -> GLint;
+        (2, 0) fn glUniform1i(location: GLint, v0: GLint);
+        (2, 0) fn glUniform1f(location: GLint, v0: GLfloat);
+        (2, 0) fn glUniform4f(location: GLint, v0: GLfloat, v1: GLfloat, v2: GLfloat, v3: GLfloat);
+        (2, 0) fn glUniformMatrix4fv(location: GLint, count: GLsizei, transpose: GLboolean, value: *const GLfloat);
+        (2, 0) fn glEnableVertexAttribArray(index: GLuint);
+        (2, 0) fn glVertexAttribPointer(
+            index: GLuint, size: GLint, kind: GLenum, normalized: GLboolean, stride: GLsizei, pointer: *const c_void,
+        );
+        (3, 0) fn glGetStringi(name: GLenum, index: GLuint) -> *const GLubyte;
+        (3, 0) fn glGenVertexArrays(n: GLsizei, arrays: *mut GLuint);
+        (3, 0) fn glBindVertexArray(array: GLuint);
+        (3, 0) fn glDeleteVertexArrays(n: GLsizei, arrays: *const GLuint);
+        (3, 0) fn glGenerateMipmap(target: GLenum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_loader::{SymbolResolver, LIBRARY_VERSION};
+
+    /// Pretends only the `opengl32.dll` exports exist, like a context without a real driver.
+    struct LibraryOnly;
+
+    impl SymbolResolver for LibraryOnly {
+        fn context_proc(&mut self, _: &str) -> Option<*const c_void> {
+            None
+        }
+
+        fn library_proc(&mut self, _: &str) -> Option<*const c_void> {
+            Some(core::ptr::dangling())
+        }
+    }
+
+    #[test]
+    fn newer_functions_need_the_context() {
+        let error = Gl::load(&mut LibraryOnly).unwrap_err();
+        let expected: Vec<_> = Gl::FUNCTIONS.iter().filter(|f| f.version > LIBRARY_VERSION).copied().collect();
+        assert_eq!(error.missing, expected);
+        assert_eq!(error.missing[0].name, "glActiveTexture");
+    }
+}
@@ -0,0 +1,287 @@
+//! Resolving OpenGL entry points into typed function pointers.
+//!
+//! The API is written down once with [`gl_api!`](crate::gl_api), one line per function, and the
+//! macro turns that into a struct of function pointers plus a `load` function. Where each
+//! pointer comes from is up to a [`SymbolResolver`], so the fallback rules can be tested with a
+//! table of fake functions instead of a driver.
+//!
+//! On Windows `wglGetProcAddress` only knows functions newer than OpenGL 1.1, the older ones are
+//! plain exports of `opengl32.dll`. [`resolve`] asks the context first and the library only for
+//! 1.1 functions, so a newer function the driver lacks is reported instead of quietly picking up
+//! whatever the library might export.
+
+use core::ffi::c_void;
+use core::fmt;
+
+/// The OpenGL version that introduced a function, as (major, minor).
+pub type Version = (u8, u8);
+
+/// Everything in OpenGL 1.1 is exported by `opengl32.dll` itself.
+pub const LIBRARY_VERSION: Version = (1, 1);
+
+/// An entry point listed in a [`gl_api!`](crate::gl_api) description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FunctionInfo {
+    pub name: &'static str,
+    pub version: Version,
+}
+
+/// Where function addresses come from.
+pub trait SymbolResolver {
+    /// Asks the current context, like `wglGetProcAddress`.
+    fn context_proc(&mut self, name: &str) -> Option<*const c_void>;
+
+    /// Asks the OpenGL library itself, like `GetProcAddress` on `opengl32.dll`.
+    fn library_proc(&mut self, name: &str) -> Option<*const c_void>;
+}
+
+/// Finds `function`, falling back to the library only for OpenGL 1.1 functions.
+pub fn resolve(resolver: &mut impl SymbolResolver, function: &FunctionInfo) -> Option<*const c_void> {
+    let found = resolver.context_proc(function.name).filter(|proc| !proc.is_null());
+    if found.is_some() || function.version > LIBRARY_VERSION {
+        return found;
+    }
+    resolver.library_proc(function.name).filter(|proc| !proc.is_null())
+}
+
+/// Some functions could not be found, all of them are listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    pub missing: Vec<FunctionInfo>,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing OpenGL functions:")?;
+        for (i, function) in self.missing.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            let (major, minor) = function.version;
+            write!(f, "{}{} ({}.{})", separator, function.name, major, minor)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Declares a struct of OpenGL function pointers and how to load them.
+///
+/// Each line gives the version that introduced a function and its C signature. The struct gets
+/// a method per function that calls through the pointer, `load` that resolves all of them with a
+/// [`SymbolResolver`], and `FUNCTIONS` listing what it needs.
+///
+/// ```
+/// use triangle_from_scratch::gl_api;
+///
+/// gl_api! {
+///     pub struct Tiny {
+///         (1, 1) fn glClear(mask: u32);
+///         (2, 0) fn glCreateShader(kind: u32) -> u32;
+///     }
+/// }
+///
+/// assert_eq!(Tiny::FUNCTIONS[1].name, "glCreateShader");
+/// assert_eq!(Tiny::FUNCTIONS[1].version, (2, 0));
+/// ```
+#[macro_export]
+macro_rules! gl_api {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $api:ident {
+            $( ($major:literal, $minor:literal) fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?; )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[allow(non_snake_case)]
+        $vis struct $api {
+            $( $name: unsafe extern "system" fn($($ty),*) $(-> $ret)?, )*
+        }
+
+        #[allow(non_snake_case, clippy::too_many_arguments, clippy::missing_safety_doc)]
+        impl $api {
+            pub const FUNCTIONS: &'static [$crate::gl_loader::FunctionInfo] = &[
+                $( $crate::gl_loader::FunctionInfo { name: stringify!($name), version: ($major, $minor) }, )*
+            ];
+
+            /// Resolves every function, or lists all that are missing.
+            pub fn load(
+                resolver: &mut impl $crate::gl_loader::SymbolResolver,
+            ) -> Result<Self, $crate::gl_loader::LoadError> {
+                let mut missing = Vec::new();
+                let mut procs = Self::FUNCTIONS.iter().map(|function| {
+                    let proc = $crate::gl_loader::resolve(resolver, function);
+                    if proc.is_none() {
+                        missing.push(*function);
+                    }
+                    proc.unwrap_or(core::ptr::null())
+                });
+                let procs: [*const core::ffi::c_void; Self::FUNCTIONS.len()] =
+                    core::array::from_fn(|_| procs.next().unwrap());
+                if !missing.is_empty() {
+                    return Err($crate::gl_loader::LoadError { missing });
+                }
+                let mut procs = procs.into_iter();
+                // Safety: every pointer was found under the name it is declared with.
+                unsafe {
+                    Ok(Self {
+                        $( $name: core::mem::transmute::<
+                            *const core::ffi::c_void,
+                            unsafe extern "system" fn($($ty),*) $(-> $ret)?,
+                        >(procs.next().unwrap()), )*
+                    })
+                }
+            }
+
+            $(
+                #[inline]
+                pub unsafe fn $name(&self, $($arg: $ty),*) $(-> $ret)? {
+                    (self.$name)($($arg),*)
+                }
+            )*
+        }
+
+        impl core::fmt::Debug for $api {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!($api)).finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+#[cfg(windows)]
+mod windows {
+    use core::ffi::c_void;
+    use std::ffi::CString;
+
+    use super::SymbolResolver;
+    use crate::wgl::get_proc_address;
+    use crate::win32::*;
+
+    /// Resolves through `wglGetProcAddress` and `opengl32.dll`, a context must be current.
+    #[derive(Debug)]
+    pub struct WglResolver {
+        library: HMODULE,
+    }
+
+    impl WglResolver {
+        /// See [`LoadLibraryW`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-loadlibraryw)
+        pub fn new() -> Result<Self, Win32Error> {
+            let name = wide_null("opengl32.dll");
+            let library = unsafe { LoadLibraryW(name.as_ptr()) };
+            if library.is_null() {
+                Err(get_last_error())
+            } else {
+                Ok(Self { library })
+            }
+        }
+    }
+
+    impl SymbolResolver for WglResolver {
+        fn context_proc(&mut self, name: &str) -> Option<*const c_void> {
+            get_proc_address(&CString::new(name).ok()?)
+        }
+
+        fn library_proc(&mut self, name: &str) -> Option<*const c_void> {
+            let name = CString::new(name).ok()?;
+            let proc = unsafe { GetProcAddress(self.library, name.as_ptr()) };
+            (!proc.is_null()).then_some(proc)
+        }
+    }
+
+    impl Drop for WglResolver {
+        fn drop(&mut self) {
+            unsafe { FreeLibrary(self.library) };
+        }
+    }
+}
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "system" fn add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    unsafe extern "system" fn negate(a: i32) -> i32 {
+        -a
+    }
+
+    unsafe extern "system" fn nothing() {}
+
+    crate::gl_api! {
+        struct Mock {
+            (1, 1) fn glAdd(a: i32, b: i32) -> i32;
+            (1, 1) fn glNothing();
+            (2, 0) fn glNegate(a: i32) -> i32;
+        }
+    }
+
+    /// Separate symbol tables for the context and the library.
+    #[derive(Default)]
+    struct Tables {
+        context: Vec<(&'static str, *const c_void)>,
+        library: Vec<(&'static str, *const c_void)>,
+        asked_library: Vec<String>,
+    }
+
+    impl SymbolResolver for Tables {
+        fn context_proc(&mut self, name: &str) -> Option<*const c_void> {
+            self.context.iter().find(|(n, _)| *n == name).map(|&(_, proc)| proc)
+        }
+
+        fn library_proc(&mut self, name: &str) -> Option<*const c_void> {
+            self.asked_library.push(name.to_owned());
+            self.library.iter().find(|(n, _)| *n == name).map(|&(_, proc)| proc)
+        }
+    }
+
+    const ADD: *const c_void = add as *const c_void;
+    const NEGATE: *const c_void = negate as *const c_void;
+    const NOTHING: *const c_void = nothing as *const c_void;
+
+    #[test]
+    fn loads_and_calls_through_pointers() {
+        let mut tables = Tables {
+            context: vec![("glNegate", NEGATE)],
+            library: vec![("glAdd", ADD), ("glNothing", NOTHING)],
+            ..Default::default()
+        };
+        let gl = Mock::load(&mut tables).unwrap();
+        unsafe {
+            assert_eq!(gl.glAdd(2, 3), 5);
+            assert_eq!(gl.glNegate(4), -4);
+            gl.glNothing();
+        }
+        // The library is only asked for what the context did not have.
+        assert_eq!(tables.asked_library, ["glAdd", "glNothing"]);
+    }
+
+    #[test]
+    fn library_is_only_a_fallback_for_old_functions() {
+        let mut tables = Tables { library: vec![("glNegate", NEGATE)], ..Default::default() };
+        let newer = FunctionInfo { name: "glNegate", version: (2, 0) };
+        assert_eq!(resolve(&mut tables, &newer), None);
+        assert!(tables.asked_library.is_empty());
+        // The context wins over the library, null counts as missing.
+        let mut tables = Tables {
+            context: vec![("glAdd", core::ptr::null())],
+            library: vec![("glAdd", ADD)],
+            ..Default::default()
+        };
+        assert_eq!(resolve(&mut tables, &FunctionInfo { name: "glAdd", version: (1, 0) }), Some(ADD));
+    }
+
+    #[test]
+    fn lists_every_missing_function() {
+        let mut tables = Tables { library: vec![("glNothing", NOTHING)], ..Default::default() };
+        let error = Mock::load(&mut tables).unwrap_err();
+        assert_eq!(
+            error.missing,
+            [FunctionInfo { name: "glAdd", version: (1, 1) }, FunctionInfo { name: "glNegate", version: (2, 0) }]
+        );
+        assert_eq!(error.to_string(), "missing OpenGL functions: glAdd (1.1), glNegate (2.0)");
+    }
+}
@@ -0,0 +1,418 @@
+//! Reading and writing `.ico` files.
+//!
+//! An icon file is a directory of images of different sizes. Each entry holds either a whole PNG
+//! file or a DIB whose header claims twice the real height: the color pixels are followed by a 1
+//! bit AND mask that marks transparent pixels. Windows builds an `HICON` from the bytes of one
+//! entry, so this only has to find the entries and pick the one closest to the size the screen
+//! needs. Entries stay undecoded except BMP ones, which [`IconEntry::to_image`] can read back.
+
+use core::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::bmp::{self, BitDepth};
+use crate::dib::{dib_to_image, stride, Orientation};
+use crate::image::Image;
+use crate::png;
+
+const DIRECTORY_SIZE: usize = 6;
+const ENTRY_SIZE: usize = 16;
+const INFO_HEADER_SIZE: usize = 40;
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// The DPI at which Windows' icon sizes (16 for small icons, 32 for large ones) are given.
+pub const BASE_DPI: u32 = 96;
+
+/// Why an icon file did not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcoError {
+    /// Not an icon directory, e.g. a cursor or some other file.
+    NotAnIcon,
+    /// The file ends before the directory does.
+    Truncated,
+    /// Entry `index` points outside the file or is neither a PNG nor a DIB.
+    BadEntry(usize),
+    /// The directory lists no images.
+    Empty,
+}
+
+impl fmt::Display for IcoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IcoError::NotAnIcon => write!(f, "not an icon file"),
+            IcoError::Truncated => write!(f, "the icon directory is cut short"),
+            IcoError::BadEntry(index) => write!(f, "icon entry {} is broken", index),
+            IcoError::Empty => write!(f, "the icon file has no images"),
+        }
+    }
+}
+
+impl std::error::Error for IcoError {}
+
+/// How an entry's image is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryFormat {
+    Png,
+    /// A DIB with an AND mask.
+    Bmp,
+}
+
+/// One image of an icon, with its size as the image data gives it (the directory's own numbers
+/// are often wrong).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconEntry {
+    pub width: u32,
+    pub height: u32,
+    pub bits_per_pixel: u16,
+    pub format: EntryFormat,
+    /// Exactly the bytes `CreateIconFromResourceEx` wants.
+    pub data: Vec<u8>,
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// The stride of the AND mask rows, 1 bit per pixel.
+fn mask_stride(width: u32) -> usize {
+    stride(width, 1)
+}
+
+impl IconEntry {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&PNG_SIGNATURE) {
+            // IHDR is always the first chunk: width, height, bit depth, color type.
+            if data.get(12..16)? != b"IHDR" {
+                return None;
+            }
+            let channels = match *data.get(25)? {
+                0 => 1,
+                2 => 3,
+                3 => 1,
+                4 => 2,
+                6 => 4,
+                _ => return None,
+            };
+            return Some(Self {
+                width: read_u32_be(data, 16)?,
+                height: read_u32_be(data, 20)?,
+                bits_per_pixel: u16::from(*data.get(24)?) * channels,
+                format: EntryFormat::Png,
+                data: data.to_vec(),
+            });
+        }
+        if (read_u32(data, 0)? as usize) < INFO_HEADER_SIZE {
+            return None;
+        }
+        let width = read_u32(data, 4)? as i32;
+        let height = read_u32(data, 8)? as i32;
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        Some(Self {
+            width: width as u32,
+            height: height as u32 / 2,
+            bits_per_pixel: read_u16(data, 14)?,
+            format: EntryFormat::Bmp,
+            data: data.to_vec(),
+        })
+    }
+
+    /// A PNG entry, the usual format for 256 pixel icons.
+    pub fn png(image: &Image) -> Self {
+        Self::parse(&png::encode(image)).expect("the encoder writes a valid header")
+    }
+
+    /// A 32 bit DIB entry, which every version of Windows reads. The AND mask marks fully
+    /// transparent pixels for programs that ignore alpha.
+    ///
+    /// `None` for an empty image, icon headers have no way to say so.
+    pub fn bmp(image: &Image) -> Option<Self> {
+        if image.width() == 0 || image.height() == 0 {
+            return None;
+        }
+        let mut data = bmp::encode_dib(image, BitDepth::Bgra32);
+        let doubled = Orientation::BottomUp.header_height(image.height() * 2);
+        data[8..12].copy_from_slice(&doubled.to_le_bytes());
+        let mask_stride = mask_stride(image.width());
+        let mut mask = vec![0; mask_stride * image.height() as usize];
+        for (y, row) in mask.chunks_exact_mut(mask_stride).enumerate() {
+            let pixels = image.row(image.height() - 1 - y as u32);
+            for (x, pixel) in pixels.iter().enumerate() {
+                if pixel.a == 0 {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        let size_image = (data.len() - INFO_HEADER_SIZE + mask.len()) as u32;
+        data[20..24].copy_from_slice(&size_image.to_le_bytes());
+        data.extend_from_slice(&mask);
+        Some(Self::parse(&data).expect("the header was just written"))
+    }
+
+    /// Decodes a 24 or 32 bit BMP entry, `None` for PNG and palette entries.
+    ///
+    /// 32 bit entries use their alpha unless it is all zero (as in old icons), otherwise the AND
+    /// mask decides which pixels are transparent.
+    pub fn to_image(&self) -> Option<Image> {
+        if self.format != EntryFormat::Bmp {
+            return None;
+        }
+        let offset = read_u32(&self.data, 0)? as usize;
+        let pixels = self.data.get(offset..)?;
+        let mut image =
+            dib_to_image(pixels, self.width, self.height, self.bits_per_pixel, Orientation::BottomUp, true)?;
+        let color_size = stride(self.width, self.bits_per_pixel) * self.height as usize;
+        let has_alpha = self.bits_per_pixel == 32 && image.pixels().iter().any(|pixel| pixel.a != 0);
+        if !has_alpha {
+            let mask_stride = mask_stride(self.width);
+            let mask = pixels.get(color_size..color_size + mask_stride * self.height as usize)?;
+            for (y, row) in mask.chunks_exact(mask_stride).enumerate() {
+                let pixels = image.row_mut(self.height - 1 - y as u32);
+                for (x, pixel) in pixels.iter_mut().enumerate() {
+                    pixel.a = if row[x / 8] & (0x80 >> (x % 8)) != 0 { 0 } else { 255 };
+                }
+            }
+        }
+        Some(image)
+    }
+}
+
+/// The pixel size of an icon that is `base` pixels at 96 DPI, e.g. 32 becomes 48 at 144 DPI.
+pub fn icon_size(base: u32, dpi: u32) -> u32 {
+    (base * dpi + BASE_DPI / 2) / BASE_DPI
+}
+
+/// The images of an `.ico` file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IconFile {
+    pub entries: Vec<IconEntry>,
+}
+
+impl IconFile {
+    pub fn parse(bytes: &[u8]) -> Result<Self, IcoError> {
+        let (Some(reserved), Some(kind), Some(count)) = (read_u16(bytes, 0), read_u16(bytes, 2), read_u16(bytes, 4))
+        else {
+            return Err(IcoError::Truncated);
+        };
+        if reserved != 0 || kind != 1 {
+            return Err(IcoError::NotAnIcon);
+        }
+        if count == 0 {
+            return Err(IcoError::Empty);
+        }
+        let entries = (0..count as usize)
+            .map(|index| {
+                let at = DIRECTORY_SIZE + index * ENTRY_SIZE;
+                let (Some(size), Some(offset)) = (read_u32(bytes, at + 8), read_u32(bytes, at + 12)) else {
+                    return Err(IcoError::Truncated);
+                };
+                let data = bytes.get(offset as usize..).and_then(|rest| rest.get(..size as usize));
+                data.and_then(IconEntry::parse).ok_or(IcoError::BadEntry(index))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Reads and parses an `.ico` file, parse errors come back as `InvalidData`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::parse(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// An icon of `images`, 256 pixel (and larger) ones as PNG and the rest as BMP, like the
+    /// icon editors do. Empty images are left out.
+    pub fn from_images<'a>(images: impl IntoIterator<Item = &'a Image>) -> Self {
+        let entries = images
+            .into_iter()
+            .filter(|image| image.width() > 0 && image.height() > 0)
+            .filter_map(|image| if image.width() >= 256 { Some(IconEntry::png(image)) } else { IconEntry::bmp(image) })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[0, 0, 1, 0]);
+        bytes.extend_from_slice(&(self.entries.len() as u16).to_le_bytes());
+        let mut offset = DIRECTORY_SIZE + ENTRY_SIZE * self.entries.len();
+        for entry in &self.entries {
+            // 256 and up is written as 0.
+            bytes.push(if entry.width >= 256 { 0 } else { entry.width as u8 });
+            bytes.push(if entry.height >= 256 { 0 } else { entry.height as u8 });
+            // No palette, reserved, one plane.
+            bytes.extend_from_slice(&[0, 0, 1, 0]);
+            bytes.extend_from_slice(&entry.bits_per_pixel.to_le_bytes());
+            bytes.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += entry.data.len();
+        }
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.data);
+        }
+        bytes
+    }
+
+    /// The entry that looks best at `size` pixels: an exact match, else the smallest larger one
+    /// (scaling down looks better than up), else the largest. Deeper colors break ties.
+    pub fn best(&self, size: u32) -> Option<&IconEntry> {
+        self.entries.iter().min_by_key(|entry| {
+            let extent = entry.width.max(entry.height);
+            let rank = if extent >= size { (0, extent - size) } else { (1, size - extent) };
+            (rank, u16::MAX - entry.bits_per_pixel)
+        })
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{IconEntry, IconFile};
+    use crate::win32::*;
+
+    /// An icon we made, destroyed on drop.
+    ///
+    /// Windows and classes don't take ownership of their icons, so keep this alive for as long as
+    /// a window shows it.
+    #[derive(Debug)]
+    pub struct OwnedIcon(HICON);
+
+    impl OwnedIcon {
+        pub fn handle(&self) -> HICON {
+            self.0
+        }
+
+        /// Keeps the icon for the rest of the process, e.g. for a window class.
+        pub fn leak(self) -> HICON {
+            let icon = self.0;
+            core::mem::forget(self);
+            icon
+        }
+    }
+
+    impl Drop for OwnedIcon {
+        fn drop(&mut self) {
+            unsafe { DestroyIcon(self.0) };
+        }
+    }
+
+    impl IconEntry {
+        /// An icon scaled to `size` pixels, see [`CreateIconFromResourceEx`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createiconfromresourceex)
+        pub fn create(&self, size: u32) -> Result<OwnedIcon, Win32Error> {
+            let icon = unsafe {
+                CreateIconFromResourceEx(
+                    self.data.as_ptr(),
+                    self.data.len() as DWORD,
+                    1,
+                    ICON_RESOURCE_VERSION,
+                    size as CInt,
+                    size as CInt,
+                    LR_DEFAULTCOLOR,
+                )
+            };
+            if icon.is_null() {
+                Err(get_last_error())
+            } else {
+                Ok(OwnedIcon(icon))
+            }
+        }
+    }
+
+    impl IconFile {
+        /// The [best](IconFile::best) entry for `size` as an icon of that size.
+        pub fn create(&self, size: u32) -> Result<OwnedIcon, Win32Error> {
+            // `parse` refuses files without entries, an empty one can only be built by hand.
+            let entry = self.best(size).ok_or(Win32Error(ERROR_INVALID_PARAMETER))?;
+            entry.create(size)
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Rgba;
+
+    fn sample(size: u32) -> Image {
+        Image::from_fn(size, size, |x, y| {
+            if x == 0 {
+                Rgba::TRANSPARENT
+            } else {
+                Rgba::new((x * 7) as u8, (y * 3) as u8, 200, (255 - y) as u8)
+            }
+        })
+    }
+
+    #[test]
+    fn round_trip() {
+        let images = [sample(16), sample(33), sample(256)];
+        let file = IconFile::from_images(&images);
+        let parsed = IconFile::parse(&file.encode()).unwrap();
+        assert_eq!(parsed, file);
+        let formats: Vec<_> = parsed.entries.iter().map(|entry| (entry.width, entry.format)).collect();
+        assert_eq!(formats, [(16, EntryFormat::Bmp), (33, EntryFormat::Bmp), (256, EntryFormat::Png)]);
+        assert_eq!(parsed.entries[0].to_image(), Some(images[0].clone()));
+        assert_eq!(parsed.entries[1].to_image(), Some(images[1].clone()));
+        assert_eq!(parsed.entries[2].to_image(), None);
+        assert_eq!(parsed.entries[2].bits_per_pixel, 32);
+    }
+
+    #[test]
+    fn and_mask_is_used_without_alpha() {
+        let image = sample(8);
+        let mut entry = IconEntry::bmp(&image).unwrap();
+        // Clear the alpha bytes of the color pixels, like an icon from before alpha existed.
+        let colors = stride(8, 32) * 8;
+        for pixel in entry.data[INFO_HEADER_SIZE..INFO_HEADER_SIZE + colors].chunks_exact_mut(4) {
+            pixel[3] = 0;
+        }
+        let decoded = entry.to_image().unwrap();
+        for (x, y) in [(0, 0), (0, 7), (1, 0), (7, 7)] {
+            assert_eq!(decoded.get(x, y).unwrap().a, if x == 0 { 0 } else { 255 });
+        }
+    }
+
+    #[test]
+    fn empty_images_are_left_out() {
+        assert_eq!(IconEntry::bmp(&Image::new(0, 4)), None);
+        assert_eq!(IconEntry::bmp(&Image::new(4, 0)), None);
+        let file = IconFile::from_images(&[Image::new(0, 0), sample(16), Image::new(300, 0)]);
+        assert_eq!(file.entries.len(), 1);
+    }
+
+    #[test]
+    fn best_size_for_the_dpi() {
+        let file = IconFile::from_images(&[sample(16), sample(32), sample(48), sample(256)]);
+        let best = |dpi| file.best(icon_size(32, dpi)).map(|entry| entry.width);
+        assert_eq!(icon_size(16, 96), 16);
+        assert_eq!(icon_size(32, 120), 40);
+        assert_eq!(best(96), Some(32));
+        assert_eq!(best(120), Some(48));
+        assert_eq!(best(144), Some(48));
+        assert_eq!(best(192), Some(256));
+        assert_eq!(file.best(512).map(|entry| entry.width), Some(256));
+        assert_eq!(file.best(8).map(|entry| entry.width), Some(16));
+        assert_eq!(IconFile::default().best(32), None);
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let bytes = IconFile::from_images(&[sample(16)]).encode();
+        assert_eq!(IconFile::parse(&bytes[..4]), Err(IcoError::Truncated));
+        assert_eq!(IconFile::parse(&bytes[..bytes.len() - 1]), Err(IcoError::BadEntry(0)));
+        let mut cursor = bytes.clone();
+        cursor[2] = 2;
+        assert_eq!(IconFile::parse(&cursor), Err(IcoError::NotAnIcon));
+        assert_eq!(IconFile::parse(&[0, 0, 1, 0, 0, 0]), Err(IcoError::Empty));
+    }
+}
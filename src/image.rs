@@ -0,0 +1,169 @@
+use std::io;
+use std::path::Path;
+
+use crate::bmp;
+use crate::palette::Rgb;
+use crate::png;
+
+/// An 8 bit per channel color with straight (not premultiplied) alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(C)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Rgba = Rgba::new(0, 0, 0, 0);
+    pub const BLACK: Rgba = Rgba::opaque(0, 0, 0);
+    pub const WHITE: Rgba = Rgba::opaque(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn opaque(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 255)
+    }
+
+    pub const fn rgb(self) -> Rgb {
+        Rgb::new(self.r, self.g, self.b)
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(rgb: Rgb) -> Self {
+        Rgba::opaque(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+/// An owned RGBA image, stored row by row from the top left corner.
+///
+/// This is the in-memory pixel type shared by the framebuffer window, the software renderers and
+/// anything that saves or compares frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgba>,
+}
+
+impl Image {
+    /// A fully transparent image.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::filled(width, height, Rgba::TRANSPARENT)
+    }
+
+    pub fn filled(width: u32, height: u32, color: Rgba) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width as usize * height as usize],
+        }
+    }
+
+    /// Wraps existing pixels, `None` if there are not exactly `width * height` of them.
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Rgba>) -> Option<Self> {
+        if pixels.len() == width as usize * height as usize {
+            Some(Self { width, height, pixels })
+        } else {
+            None
+        }
+    }
+
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(u32, u32) -> Rgba) -> Self {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+        Self { width, height, pixels }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Rgba] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Rgba] {
+        &mut self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<Rgba> {
+        self.pixels
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<Rgba> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+
+    /// Writes one pixel, positions outside the image are ignored.
+    pub fn set(&mut self, x: u32, y: u32, color: Rgba) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = color;
+        }
+    }
+
+    pub fn row(&self, y: u32) -> &[Rgba] {
+        let start = y as usize * self.width as usize;
+        &self.pixels[start..start + self.width as usize]
+    }
+
+    pub fn row_mut(&mut self, y: u32) -> &mut [Rgba] {
+        let start = y as usize * self.width as usize;
+        &mut self.pixels[start..start + self.width as usize]
+    }
+
+    pub fn fill(&mut self, color: Rgba) {
+        self.pixels.fill(color);
+    }
+
+    /// Changes the size, keeping the overlapping top left part and filling the rest with `fill`.
+    pub fn resize(&mut self, width: u32, height: u32, fill: Rgba) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        let old = core::mem::replace(self, Image::filled(width, height, fill));
+        for y in 0..height.min(old.height) {
+            let columns = width.min(old.width) as usize;
+            self.row_mut(y)[..columns].copy_from_slice(&old.row(y)[..columns]);
+        }
+    }
+
+    /// Writes the image to a `.png` or `.bmp` file, picked by the extension.
+    ///
+    /// BMP files are 24 bit unless the image has transparent pixels.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        let bytes = match extension.as_deref() {
+            Some("png") => png::encode(self),
+            Some("bmp") => bmp::encode(self, bmp::BitDepth::for_image(self)),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("can only save .png and .bmp files, not {}", path.display()),
+                ))
+            }
+        };
+        std::fs::write(path, bytes)
+    }
+}
@@ -1,2 +1,47 @@
+pub mod accelerator;
+pub mod animation;
+pub mod bmp;
+pub mod canvas;
+pub mod clipboard;
+pub mod color;
+pub mod demo;
+pub mod dib;
+pub mod dispatch;
+pub mod drag_drop;
+pub mod error;
+pub mod executor;
+pub mod font;
+pub mod fullscreen;
+pub mod geometry;
+pub mod gl;
+pub mod gl_loader;
+pub mod icon;
+pub mod image;
+pub mod math3d;
+pub mod menu;
+pub mod message;
+pub mod message_box;
+pub mod monitor;
+pub mod palette;
+pub mod panic_guard;
+pub mod pipeline;
+pub mod pixel_format;
+pub mod png;
+pub mod record;
+pub mod scene;
+pub mod snapshot;
+pub mod style;
+pub mod trace;
+
 #[cfg(windows)]
-pub mod win32;
\ No newline at end of file
+pub mod gdi_canvas;
+#[cfg(windows)]
+pub mod pixel_window;
+#[cfg(windows)]
+pub mod screenshot;
+#[cfg(windows)]
+pub mod wgl;
+#[cfg(windows)]
+pub mod win32;
+#[cfg(windows)]
+pub mod window;
@@ -1,92 +1,327 @@
 // We need void pointers often due C using them
+#[cfg(windows)]
 use core::ptr::null_mut;
-use std::ffi::c_void;
 
+#[cfg(windows)]
+use core::cell::Cell;
+#[cfg(windows)]
+use core::time::Duration;
+#[cfg(windows)]
+use std::path::PathBuf;
+#[cfg(windows)]
+use std::rc::Rc;
+
+#[cfg(windows)]
+use triangle_from_scratch::accelerator::{AcceleratorTable, Accelerators};
+#[cfg(windows)]
+use triangle_from_scratch::animation::{Animator, Easing, Lerp, Tween};
+#[cfg(windows)]
+use triangle_from_scratch::canvas::Vertex;
+#[cfg(windows)]
+use triangle_from_scratch::dispatch::UiDispatcher;
+#[cfg(windows)]
+use triangle_from_scratch::drag_drop::{accept_files, FilesDropped};
+#[cfg(windows)]
+use triangle_from_scratch::demo::render_cube;
+#[cfg(windows)]
+use triangle_from_scratch::fullscreen::Fullscreen;
+#[cfg(windows)]
+use triangle_from_scratch::gdi_canvas::GdiCanvas;
+#[cfg(windows)]
+use triangle_from_scratch::geometry::{Point, Size};
+#[cfg(windows)]
+use triangle_from_scratch::icon::{icon_size, IconFile, OwnedIcon};
+#[cfg(windows)]
+use triangle_from_scratch::message::{loword, MessageClass, WM_COMMAND};
+#[cfg(windows)]
+use triangle_from_scratch::monitor::{center, Monitor};
+#[cfg(windows)]
+use triangle_from_scratch::panic_guard::{catch_panic, panic_pending, panic_result, resume_panic};
+#[cfg(windows)]
+use triangle_from_scratch::record::capture;
+#[cfg(windows)]
+use triangle_from_scratch::scene::Scene;
+#[cfg(windows)]
+use triangle_from_scratch::style::WindowStyle;
+#[cfg(windows)]
+use triangle_from_scratch::trace::{Filter, Tracer};
+#[cfg(windows)]
 use triangle_from_scratch::win32::*;
+#[cfg(windows)]
+use triangle_from_scratch::window::{invalidate, set_icon, IconSize};
 
-// This function defines all events which the generated window will respond to.
-// If the code given to us through Msg is not defined and included in the match
-// statement, the window will continue its default procedure instead.
+// What the window keeps behind `GWLP_USERDATA`.
+#[cfg(windows)]
+struct WindowData {
+    paint_count: i32,
+    scene: Scene,
+    /// Where the scene came from, `None` for the built in one.
+    scene_path: Option<PathBuf>,
+    fullscreen: Fullscreen,
+    /// Brings scenes loaded on other threads back, set once the window exists.
+    ui: Option<UiDispatcher<SceneLoaded>>,
+    /// Repaints the window while the scene grows in, set once the window exists.
+    animator: Animator,
+    /// How far the scene has grown out of its center, from 0 to 1.
+    growth: Rc<Cell<f32>>,
+}
+
+// A scene file read by a worker thread.
+#[cfg(windows)]
+struct SceneLoaded {
+    path: PathBuf,
+    scene: std::io::Result<Scene>,
+}
+
+// The commands of the keyboard shortcuts, below `menu::FIRST_COMMAND_ID` so menus never reuse them.
+#[cfg(windows)]
+const ID_RELOAD: u16 = 50;
+#[cfg(windows)]
+const ID_CLOSE: u16 = 51;
+#[cfg(windows)]
+const ID_FULLSCREEN: u16 = 52;
+
+// Loads the scene at `path` on another thread, so a slow disk doesn't freeze the window.
+#[cfg(windows)]
+fn reload_scene(data: &WindowData, path: PathBuf) {
+    let Some(ui) = data.ui.as_ref().map(UiDispatcher::handle) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let scene = Scene::load(&path);
+        // If the window was closed meanwhile, nobody wants the scene anymore.
+        let _ = ui.send(SceneLoaded { path, scene });
+    });
+}
+
+// Grows the scene out of its center, the animator repaints until it is done.
+#[cfg(windows)]
+fn grow_in(data: &mut WindowData) {
+    data.animator.stop_all();
+    let growth = Rc::clone(&data.growth);
+    let tween = Tween::new(0.0, 1.0, Duration::from_millis(400)).easing(Easing::CubicOut);
+    data.animator.play(tween, move |value| growth.set(value));
+}
+
+// The scene with every vertex `growth` of the way from the center to where it belongs.
+#[cfg(windows)]
+fn grown(scene: &Scene, growth: f32) -> Scene {
+    let count = scene.vertices.len().max(1) as i32;
+    let x: i32 = scene.vertices.iter().map(|vertex| vertex.position.x).sum();
+    let y: i32 = scene.vertices.iter().map(|vertex| vertex.position.y).sum();
+    let center = Point::new(x / count, y / count);
+    let mut frame = scene.clone();
+    for vertex in &mut frame.vertices {
+        *vertex = Vertex { position: center.lerp(&vertex.position, growth), ..*vertex };
+    }
+    frame
+}
+
+// Replaces the scene with a loaded one, keeping the old one if loading failed.
+#[cfg(windows)]
+fn scene_loaded(data: &mut WindowData, loaded: SceneLoaded) {
+    match loaded.scene {
+        Ok(scene) => {
+            data.scene = scene;
+            data.scene_path = Some(loaded.path);
+            grow_in(data);
+        }
+        Err(e) => eprintln!("Could not load the scene {}: {}", loaded.path.display(), e),
+    }
+}
+
+// Set to anything to log the messages the window gets to standard error.
+#[cfg(windows)]
+const TRACE_VAR: &str = "TRIANGLE_TRACE";
+
+#[cfg(windows)]
+thread_local! {
+    // Mouse messages would drown out everything else.
+    static TRACER: Option<Tracer> = std::env::var_os(TRACE_VAR)
+        .map(|_| Tracer::to_stderr().filter(Filter::everything().hide(MessageClass::Mouse)));
+}
+
+// Panics can't unwind into Windows, `main` resumes them once `DispatchMessageW` returns.
+// Until then the window data may be half updated, so Windows handles everything.
+#[cfg(windows)]
 pub unsafe extern "system" fn window_procedure(
     h_wnd: HWND,
     msg: UINT,
     w_param: WPARAM,
     l_param: LPARAM,
 ) -> LRESULT {
+    if panic_pending() {
+        return DefWindowProcW(h_wnd, msg, w_param, l_param);
+    }
+    let handle = || catch_panic(panic_result(msg), || handle_message(h_wnd, msg, w_param, l_param));
+    TRACER.with(|tracer| match tracer {
+        Some(tracer) => tracer.trace(&capture(h_wnd, msg, w_param, l_param), handle),
+        None => handle(),
+    })
+}
+
+// This function defines all events which the generated window will respond to.
+// If the code given to us through Msg is not defined and included in the match
+// statement, the window will continue its default procedure instead.
+#[cfg(windows)]
+unsafe fn handle_message(h_wnd: HWND, msg: UINT, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    let data = GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData;
+    if let Some(ui) = data.as_ref().and_then(|data| data.ui.as_ref()) {
+        let mut loaded = Vec::new();
+        if ui.handle_message(msg, |scene| loaded.push(scene)) {
+            for scene in loaded {
+                scene_loaded(&mut *data, scene);
+            }
+            return 0;
+        }
+    }
     match msg {
         WM_CLOSE => drop(DestroyWindow(h_wnd)),
-        WM_DESTROY => {
-            let ptr = GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut i32;
-            drop(Box::from_raw(ptr));
-            println!("Cleaned up the box.");
-            PostQuitMessage(0)
+        WM_DESTROY => PostQuitMessage(0),
+        // The last message the window gets. Clear the pointer first, so nothing can reach the
+        // freed box, not even the dispatch check above.
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData;
+            SetWindowLongPtrW(h_wnd, GWLP_USERDATA, 0);
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            return DefWindowProcW(h_wnd, msg, w_param, l_param);
         }
         WM_PAINT => {
-            let ptr = GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut i32;
-            //println!("Current ptr: {}", *ptr);
-            *ptr += 1;
+            let data = &mut *(GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData);
+            data.paint_count += 1;
             let mut ps = PAINTSTRUCT::default();
             let hdc = BeginPaint(h_wnd, &mut ps);
-            let _success = FillRect(hdc, &ps.rc_paint, (COLOR_WINDOW + 1) as HBRUSH);
-
-            // Create an array of TRIVERTEX structures that describe
-            // positional and color values for each vertex.
-            let vertex = [ 
-            TRIVERTIX { 
-                x: 150,
-                y: 0,
-                red: 0xff00,
-                green: 0x8000,
-                blue: 0x0000,
-                alpha: 0x0000},
-            TRIVERTIX { 
-                x: 0,
-                y: 150,
-                red: 0x9000,
-                green: 0x0000,
-                blue: 0x9000,
-                alpha: 0x0000},
-            TRIVERTIX { 
-                x: 300,
-                y: 150,
-                red: 0x900,
-                green: 0x8000,
-                blue: 0x9000,
-                alpha: 0x0000}
-            ];
-
-            // Create a GRADIENT_TRIANGLE structure that
-            // references the TRIVERTEX vertices.
-            let g_triangle: *const GradientTriangle = &GradientTriangle {vertex1: 0, vertex2: 1, vertex3: 2};
-            // Draw a shaded triangle.
-            let _triangle_check = unsafe {GradientFill(hdc, &vertex[0], 
-                3, g_triangle as *const c_void, 
-                1, GRADIENT_FILL_TRIANGLE)};
-            //println!("{}", triangle_check);
+            // After `BeginPaint`, which would otherwise swallow the next frame's invalidation.
+            data.animator.tick();
+            let size = get_client_rect(h_wnd).map(|rect| rect.size()).unwrap_or_default();
+            let mut canvas = GdiCanvas::new(hdc, size);
+            let growth = data.growth.get();
+            let _ = if growth < 1.0 {
+                grown(&data.scene, growth).paint(&mut canvas, ps.rc_paint.into())
+            } else {
+                data.scene.paint(&mut canvas, ps.rc_paint.into())
+            };
             EndPaint(h_wnd, &ps);
         }
+        WM_DROPFILES => {
+            let data = &mut *(GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData);
+            let dropped = FilesDropped::from_hdrop(w_param as HDROP);
+            // Only one scene is shown at a time, so the first file wins.
+            if let Some(path) = dropped.paths.into_iter().next() {
+                reload_scene(data, path);
+            }
+        }
+        WM_COMMAND => match loword(w_param) {
+            ID_RELOAD => {
+                let data = &mut *(GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData);
+                if let Some(path) = data.scene_path.clone() {
+                    reload_scene(data, path);
+                }
+            }
+            ID_CLOSE => drop(DestroyWindow(h_wnd)),
+            ID_FULLSCREEN => {
+                let data = &mut *(GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData);
+                // Switching sends `WM_SIZE` and friends right back to us, so don't hold on to `data`.
+                let mut fullscreen = core::mem::take(&mut data.fullscreen);
+                if let Err(e) = fullscreen.toggle(h_wnd) {
+                    eprintln!("Could not switch fullscreen: {}", e);
+                }
+                // A handler of those may have destroyed the window, and the data with it.
+                if let Some(data) = (GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData).as_mut() {
+                    data.fullscreen = fullscreen;
+                }
+            }
+            _ => return DefWindowProcW(h_wnd, msg, w_param, l_param),
+        },
         WM_NCCREATE => {
-            println!("NC Create");
             let createstruct: *mut CREATESTRUCTW = l_param as *mut _;
             if createstruct.is_null() {
                 return 0;
             }
-            let boxed_i32_ptr: *mut i32 = (*createstruct).lp_create_params.cast();
-            SetWindowLongPtrW(h_wnd, GWLP_USERDATA, boxed_i32_ptr as LongPtr);
+            let data: *mut WindowData = (*createstruct).lp_create_params.cast();
+            SetWindowLongPtrW(h_wnd, GWLP_USERDATA, data as LongPtr);
             return 1;
         }
-        WM_CREATE => println!("Create"),
+        WM_CREATE => {
+            let data = &mut *(GetWindowLongPtrW(h_wnd, GWLP_USERDATA) as *mut WindowData);
+            match UiDispatcher::new(h_wnd) {
+                Ok(ui) => data.ui = Some(ui),
+                Err(e) => eprintln!("Scenes can't be reloaded: {}", e),
+            }
+            // The animator goes with the window data, so it never outlives the window.
+            data.animator = Animator::new().on_repaint(move || {
+                let _ = invalidate(h_wnd, None);
+            });
+            grow_in(data);
+        }
         _ => return DefWindowProcW(h_wnd, msg, w_param, l_param),
     }
     0
 }
 
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("triangle_from_scratch draws with the Win32 API and only runs on Windows.");
+}
+
+// The scene named on the command line, or the built in one.
+#[cfg(windows)]
+fn load_scene() -> (Scene, Option<PathBuf>) {
+    let Some(path) = std::env::args_os().nth(1).map(PathBuf::from) else {
+        return (Scene::default_scene(), None);
+    };
+    match Scene::load(&path) {
+        Ok(scene) => (scene, Some(path)),
+        Err(e) => {
+            eprintln!("Could not load the scene {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// The color cube as the window's icons, sized for its DPI. They have to outlive the window.
+#[cfg(windows)]
+fn set_cube_icons(hwnd: HWND) -> Vec<OwnedIcon> {
+    let images = [16, 32, 48, 256].map(|size| render_cube(size, size, 0.6));
+    let file = IconFile::from_images(&images);
+    let dpi = unsafe { GetDpiForWindow(hwnd) };
+    let mut icons = Vec::new();
+    for size in [IconSize::Small, IconSize::Big] {
+        if let Ok(icon) = file.create(icon_size(size.base_size(), dpi)) {
+            unsafe { set_icon(hwnd, size, icon.handle()) };
+            icons.push(icon);
+        }
+    }
+    icons
+}
 
+// F5 reloads the scene file after editing it, Ctrl+W closes the window.
+#[cfg(windows)]
+fn shortcuts() -> AcceleratorTable {
+    let mut accelerators = Accelerators::new();
+    accelerators.add("F5", ID_RELOAD).unwrap();
+    accelerators.add("Ctrl+W", ID_CLOSE).unwrap();
+    accelerators.add("F11", ID_FULLSCREEN).unwrap();
+    AcceleratorTable::new(&accelerators).unwrap()
+}
+
+#[cfg(windows)]
 fn main() {
+    let (scene, scene_path) = load_scene();
     let h_instance = get_process_handle();
     let sample_window_class_wn = wide_null("Sample Window Class");
     let sample_window_name_wn = wide_null("Sample Window Name");
-    let lparam: *mut i32 = Box::leak(Box::new(5_i32));
+    let lparam: *mut WindowData = Box::leak(Box::new(WindowData {
+        paint_count: 5,
+        scene,
+        scene_path,
+        fullscreen: Fullscreen::new(),
+        ui: None,
+        animator: Animator::new(),
+        growth: Rc::new(Cell::new(1.0)),
+    }));
 
     let mut wc = WNDCLASSW::default();
     wc.lpfn_wnd_proc = Some(window_procedure);
@@ -100,27 +335,40 @@ fn main() {
       panic!("Could not register the window class, error code: {}", last_error);
     });
 
+    // 800 by 600 at 96 DPI in the middle of the primary monitor, or wherever Windows likes.
+    let (x, y, width, height) = match Monitor::primary() {
+        Ok(monitor) => {
+            let size = Size::new((800.0 * monitor.scale()) as i32, (600.0 * monitor.scale()) as i32);
+            let rect = center(size, monitor.work_area);
+            (rect.left, rect.top, rect.width(), rect.height())
+        }
+        Err(_) => (CW_USEDEFAULT, CW_USEDEFAULT, CW_USEDEFAULT, CW_USEDEFAULT),
+    };
     let hwnd = unsafe {
         CreateWindowExW(
             0,
             sample_window_class_wn.as_ptr(),
             sample_window_name_wn.as_ptr(),
-            WS_OVERLAPPEDWINDOW,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
+            WindowStyle::OVERLAPPEDWINDOW.bits(),
+            x,
+            y,
+            width,
+            height,
             core::ptr::null_mut(),
             core::ptr::null_mut(),
             h_instance,
             lparam.cast(),
         )
     };
+    resume_panic();
     if hwnd.is_null() {
         panic!("Failed to create a window.");
     }
 
+    unsafe { accept_files(hwnd, true) };
+    let _icons = set_cube_icons(hwnd);
     let _previously_visible = unsafe { ShowWindow(hwnd, SW_SHOW) };
+    let accelerators = shortcuts();
     let mut msg = MSG::default();
 
     loop {
@@ -130,11 +378,12 @@ fn main() {
         } else if message_return == -1 {
             let last_error = unsafe { GetLastError() };
             panic!("Error with `GetMessageW`, error code: {}", last_error);
-        } else {
+        } else if !unsafe { accelerators.translate(hwnd, &msg) } {
             unsafe {
                 TranslateMessage(&msg);
                 DispatchMessageW(&msg);
             }
         }
+        resume_panic();
     }
 }
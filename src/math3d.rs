@@ -0,0 +1,319 @@
+//! Vectors and 4x4 matrices for the software 3D pipeline.
+//!
+//! Conventions follow OpenGL: right handed eye space looking down -Z, column vectors multiplied
+//! on the right (`projection * view * model * v`) and clip space Z from -W to W.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// The same direction with length 1, zero stays zero.
+    pub fn normalize(self) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+
+    /// Appends `w`, 1 for a point and 0 for a direction.
+    pub const fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+    fn mul(self, factor: f32) -> Vec3 {
+        Vec3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// A homogeneous position, or any four values interpolated together (like a color).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vec4 {
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+
+    pub fn dot(self, other: Vec4) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// `self` at `t` = 0, `other` at `t` = 1.
+    pub fn lerp(self, other: Vec4, t: f32) -> Vec4 {
+        self + (other - self) * t
+    }
+
+    /// Drops W after dividing by it.
+    pub fn project(self) -> Vec3 {
+        Vec3::new(self.x / self.w, self.y / self.w, self.z / self.w)
+    }
+}
+
+impl From<[f32; 4]> for Vec4 {
+    fn from([x, y, z, w]: [f32; 4]) -> Self {
+        Vec4::new(x, y, z, w)
+    }
+}
+
+impl Add for Vec4 {
+    type Output = Vec4;
+    fn add(self, other: Vec4) -> Vec4 {
+        Vec4::new(self.x + other.x, self.y + other.y, self.z + other.z, self.w + other.w)
+    }
+}
+
+impl Sub for Vec4 {
+    type Output = Vec4;
+    fn sub(self, other: Vec4) -> Vec4 {
+        Vec4::new(self.x - other.x, self.y - other.y, self.z - other.z, self.w - other.w)
+    }
+}
+
+impl Mul<f32> for Vec4 {
+    type Output = Vec4;
+    fn mul(self, factor: f32) -> Vec4 {
+        Vec4::new(self.x * factor, self.y * factor, self.z * factor, self.w * factor)
+    }
+}
+
+/// A 4x4 matrix stored row by row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub rows: [[f32; 4]; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4::from_rows([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    pub const fn from_rows(rows: [[f32; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    pub fn row(&self, i: usize) -> Vec4 {
+        self.rows[i].into()
+    }
+
+    pub fn column(&self, i: usize) -> Vec4 {
+        Vec4::new(self.rows[0][i], self.rows[1][i], self.rows[2][i], self.rows[3][i])
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        Mat4::from_rows(core::array::from_fn(|i| self.column(i).to_array()))
+    }
+
+    pub fn translation(offset: Vec3) -> Mat4 {
+        Mat4::from_rows([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(factors: Vec3) -> Mat4 {
+        Mat4::from_rows([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counterclockwise by `radians` when looking from +X towards the origin.
+    pub fn rotation_x(radians: f32) -> Mat4 {
+        let (sin, cos) = radians.sin_cos();
+        Mat4::from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counterclockwise by `radians` when looking from +Y towards the origin.
+    pub fn rotation_y(radians: f32) -> Mat4 {
+        let (sin, cos) = radians.sin_cos();
+        Mat4::from_rows([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counterclockwise by `radians` when looking from +Z towards the origin.
+    pub fn rotation_z(radians: f32) -> Mat4 {
+        let (sin, cos) = radians.sin_cos();
+        Mat4::from_rows([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Like `gluPerspective`: `fov_y` in radians, `aspect` is width / height.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        let depth = near - far;
+        Mat4::from_rows([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [0.0, 0.0, (far + near) / depth, 2.0 * far * near / depth],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Like `glOrtho`.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        let (width, height, depth) = (right - left, top - bottom, far - near);
+        Mat4::from_rows([
+            [2.0 / width, 0.0, 0.0, -(right + left) / width],
+            [0.0, 2.0 / height, 0.0, -(top + bottom) / height],
+            [0.0, 0.0, -2.0 / depth, -(far + near) / depth],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Like `gluLookAt`: a view matrix for a camera at `eye` looking at `target`.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+        let forward = (target - eye).normalize();
+        let side = forward.cross(up).normalize();
+        let up = side.cross(forward);
+        Mat4::from_rows([
+            [side.x, side.y, side.z, -side.dot(eye)],
+            [up.x, up.y, up.z, -up.dot(eye)],
+            [-forward.x, -forward.y, -forward.z, forward.dot(eye)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec4 {
+        *self * point.extend(1.0)
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, other: Mat4) -> Mat4 {
+        Mat4::from_rows(core::array::from_fn(|i| core::array::from_fn(|j| self.row(i).dot(other.column(j)))))
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+    fn mul(self, v: Vec4) -> Vec4 {
+        Vec4::new(self.row(0).dot(v), self.row(1).dot(v), self.row(2).dot(v), self.row(3).dot(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec4, b: Vec4) -> bool {
+        (a - b).to_array().iter().all(|d| d.abs() < 1e-5)
+    }
+
+    #[test]
+    fn transforms_compose_right_to_left() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        assert_eq!(m.transform_point(Vec3::new(1.0, 1.0, 1.0)), Vec4::new(3.0, 4.0, 5.0, 1.0));
+        let quarter = Mat4::rotation_z(core::f32::consts::FRAC_PI_2);
+        assert!(close(quarter.transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec4::new(0.0, 1.0, 0.0, 1.0)));
+        assert_eq!(m * Mat4::IDENTITY, m);
+        assert_eq!(m.transpose().transpose(), m);
+    }
+
+    #[test]
+    fn perspective_maps_near_and_far_to_the_depth_range() {
+        let p = Mat4::perspective(core::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+        let near = p.transform_point(Vec3::new(1.0, 1.0, -1.0)).project();
+        let far = p.transform_point(Vec3::new(0.0, 0.0, -10.0)).project();
+        assert!((near.z + 1.0).abs() < 1e-5 && (far.z - 1.0).abs() < 1e-5);
+        // A 90 degree field of view puts (1, 1) at the corner at distance 1.
+        assert!((near.x - 1.0).abs() < 1e-5 && (near.y - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn look_at_moves_the_target_onto_the_negative_z_axis() {
+        let view = Mat4::look_at(Vec3::new(3.0, 2.0, 5.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let target = view.transform_point(Vec3::new(1.0, 0.0, 0.0));
+        let distance = (Vec3::new(3.0, 2.0, 5.0) - Vec3::new(1.0, 0.0, 0.0)).length();
+        assert!(close(target, Vec4::new(0.0, 0.0, -distance, 1.0)));
+    }
+}
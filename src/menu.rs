@@ -0,0 +1,547 @@
+//! Menu bars and context menus described as data, with a closure per item.
+//!
+//! A [`Menu`] is built once, then handed to [`Commands::add`], which gives every item a command id,
+//! keeps the closures and returns the [`Layout`] used to create the actual `HMENU`. When the user
+//! picks an item, `WM_COMMAND` carries that id back and [`Commands::dispatch`] runs the closure,
+//! flipping check marks and radio bullets on the way. None of that needs a window, so the
+//! dispatch rules are tested directly.
+//!
+//! Radio items next to each other in the same menu form a group, like Windows draws them.
+
+use std::collections::BTreeMap;
+
+/// The first id [`Commands`] hands out, well clear of the dialog ids like `IDOK`.
+pub const FIRST_COMMAND_ID: u16 = 100;
+
+enum ItemKind {
+    Action(Box<dyn FnMut()>),
+    Check(bool, Box<dyn FnMut(bool)>),
+    Radio(bool, Box<dyn FnMut()>),
+    Separator,
+    Submenu(Menu),
+}
+
+/// One entry of a [`Menu`].
+pub struct MenuItem {
+    label: String,
+    enabled: bool,
+    kind: ItemKind,
+}
+
+impl MenuItem {
+    /// A plain item. `&` marks the access key and a tab separates the shortcut text, as in
+    /// `"&Save\tCtrl+S"`.
+    pub fn action(label: &str, action: impl FnMut() + 'static) -> Self {
+        Self::new(label, ItemKind::Action(Box::new(action)))
+    }
+
+    /// An item with a check mark that flips when chosen, the closure gets the new state.
+    pub fn check(label: &str, checked: bool, action: impl FnMut(bool) + 'static) -> Self {
+        Self::new(label, ItemKind::Check(checked, Box::new(action)))
+    }
+
+    /// One choice of a radio group, the closure runs whenever it is chosen.
+    ///
+    /// If no item of a group is selected the first one is, if several are the first of them wins.
+    pub fn radio(label: &str, selected: bool, action: impl FnMut() + 'static) -> Self {
+        Self::new(label, ItemKind::Radio(selected, Box::new(action)))
+    }
+
+    pub fn submenu(label: &str, menu: Menu) -> Self {
+        Self::new(label, ItemKind::Submenu(menu))
+    }
+
+    pub fn separator() -> Self {
+        Self::new("", ItemKind::Separator)
+    }
+
+    /// Disabled items are grayed out and can't be chosen, enabled by default.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    fn new(label: &str, kind: ItemKind) -> Self {
+        Self { label: label.to_owned(), enabled: true, kind }
+    }
+}
+
+impl core::fmt::Debug for MenuItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let mut item = f.debug_struct("MenuItem");
+        item.field("label", &self.label).field("enabled", &self.enabled);
+        match &self.kind {
+            ItemKind::Action(_) => item.field("kind", &"Action"),
+            ItemKind::Check(checked, _) => item.field("checked", checked),
+            ItemKind::Radio(selected, _) => item.field("selected", selected),
+            ItemKind::Separator => item.field("kind", &"Separator"),
+            ItemKind::Submenu(menu) => item.field("submenu", menu),
+        };
+        item.finish()
+    }
+}
+
+/// A menu bar or popup menu, as a list of items.
+#[derive(Debug, Default)]
+pub struct Menu {
+    items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn item(mut self, item: MenuItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Short for `.item(MenuItem::action(label, action))`.
+    pub fn action(self, label: &str, action: impl FnMut() + 'static) -> Self {
+        self.item(MenuItem::action(label, action))
+    }
+
+    pub fn separator(self) -> Self {
+        self.item(MenuItem::separator())
+    }
+
+    pub fn submenu(self, label: &str, menu: Menu) -> Self {
+        self.item(MenuItem::submenu(label, menu))
+    }
+}
+
+/// What choosing a command does to its check mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toggle {
+    None,
+    Check,
+    /// Selects this item of the group spanning the ids `first..=last`.
+    Radio { first: u16, last: u16 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    Command { id: u16, toggle: Toggle },
+    Separator,
+    Submenu(Layout),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub label: String,
+    pub enabled: bool,
+    pub kind: EntryKind,
+}
+
+/// A menu with ids instead of closures, what the `HMENU` is made from.
+///
+/// Check marks are not stored here but in [`Commands`], so a popup created again later shows
+/// the current state.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Layout {
+    pub entries: Vec<Entry>,
+}
+
+/// What [`Commands::dispatch`] did, so the caller can update the `HMENU` to match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatch {
+    /// A plain item ran.
+    Ran,
+    /// A check item flipped to `checked`.
+    Checked { id: u16, checked: bool },
+    /// `id` is now the selected item of the radio group `first..=last`.
+    Selected { first: u16, last: u16, id: u16 },
+}
+
+struct Command {
+    toggle: Toggle,
+    checked: bool,
+    action: Box<dyn FnMut(bool)>,
+}
+
+/// The closures of every menu of a window, by command id.
+///
+/// Menu bars and context menus of one window share this, so their ids never collide.
+pub struct Commands {
+    commands: BTreeMap<u16, Command>,
+    next_id: u16,
+}
+
+impl Default for Commands {
+    fn default() -> Self {
+        Self { commands: BTreeMap::new(), next_id: FIRST_COMMAND_ID }
+    }
+}
+
+impl core::fmt::Debug for Commands {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_map().entries(self.commands.iter().map(|(id, command)| (id, (command.toggle, command.checked)))).finish()
+    }
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the closures of `menu`, giving each item the next free id.
+    ///
+    /// # Panics
+    /// If the 16 bit ids run out.
+    pub fn add(&mut self, menu: Menu) -> Layout {
+        let mut entries = Vec::with_capacity(menu.items.len());
+        let mut items = menu.items.into_iter().peekable();
+        while let Some(item) = items.next() {
+            let kind = match item.kind {
+                ItemKind::Action(mut action) => {
+                    let id = self.insert(Toggle::None, false, Box::new(move |_| action()));
+                    EntryKind::Command { id, toggle: Toggle::None }
+                }
+                ItemKind::Check(checked, action) => {
+                    let id = self.insert(Toggle::Check, checked, action);
+                    EntryKind::Command { id, toggle: Toggle::Check }
+                }
+                ItemKind::Radio(selected, action) => {
+                    // Gather the rest of the group first, its ids are needed by every member.
+                    let mut group = vec![(item.label, item.enabled, selected, action)];
+                    while let Some(MenuItem { label, enabled, kind: ItemKind::Radio(selected, action) }) =
+                        items.next_if(|next| matches!(next.kind, ItemKind::Radio(..)))
+                    {
+                        group.push((label, enabled, selected, action));
+                    }
+                    let first = self.next_id;
+                    let last = u16::try_from(group.len() - 1)
+                        .ok()
+                        .and_then(|rest| first.checked_add(rest))
+                        .expect("ran out of menu command ids");
+                    let toggle = Toggle::Radio { first, last };
+                    let chosen = group.iter().position(|member| member.2).unwrap_or(0);
+                    for (index, (label, enabled, _, mut action)) in group.into_iter().enumerate() {
+                        let id = self.insert(toggle, index == chosen, Box::new(move |_| action()));
+                        entries.push(Entry { label, enabled, kind: EntryKind::Command { id, toggle } });
+                    }
+                    continue;
+                }
+                ItemKind::Separator => EntryKind::Separator,
+                ItemKind::Submenu(menu) => EntryKind::Submenu(self.add(menu)),
+            };
+            entries.push(Entry { label: item.label, enabled: item.enabled, kind });
+        }
+        Layout { entries }
+    }
+
+    fn insert(&mut self, toggle: Toggle, checked: bool, action: Box<dyn FnMut(bool)>) -> u16 {
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).expect("ran out of menu command ids");
+        self.commands.insert(id, Command { toggle, checked, action });
+        id
+    }
+
+    pub fn contains(&self, id: u16) -> bool {
+        self.commands.contains_key(&id)
+    }
+
+    /// Whether a check item is checked or a radio item selected.
+    pub fn is_checked(&self, id: u16) -> bool {
+        self.commands.get(&id).is_some_and(|command| command.checked)
+    }
+
+    /// Runs the closure of `id`, `None` if it is not one of ours.
+    pub fn dispatch(&mut self, id: u16) -> Option<Dispatch> {
+        let command = self.commands.get_mut(&id)?;
+        let toggle = command.toggle;
+        let dispatch = match toggle {
+            Toggle::None => Dispatch::Ran,
+            Toggle::Check => {
+                command.checked = !command.checked;
+                Dispatch::Checked { id, checked: command.checked }
+            }
+            Toggle::Radio { first, last } => {
+                for (&other, command) in self.commands.range_mut(first..=last) {
+                    command.checked = other == id;
+                }
+                Dispatch::Selected { first, last, id }
+            }
+        };
+        let command = self.commands.get_mut(&id)?;
+        (command.action)(command.checked);
+        Some(dispatch)
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use core::ptr::null;
+
+    use super::{Commands, Dispatch, EntryKind, Layout, Toggle};
+    use crate::geometry::Point;
+    use crate::message::{Params, WM_COMMAND};
+    use crate::win32::*;
+
+    /// A menu we still own, destroyed on drop along with its submenus.
+    #[derive(Debug)]
+    pub struct OwnedMenu(HMENU);
+
+    impl OwnedMenu {
+        pub fn handle(&self) -> HMENU {
+            self.0
+        }
+
+        /// Gives up ownership, e.g. after `SetMenu` made the menu part of a window.
+        pub fn into_raw(self) -> HMENU {
+            let menu = self.0;
+            core::mem::forget(self);
+            menu
+        }
+    }
+
+    impl Drop for OwnedMenu {
+        fn drop(&mut self) {
+            unsafe { DestroyMenu(self.0) };
+        }
+    }
+
+    fn check_flag(checked: bool) -> UINT {
+        if checked {
+            MF_CHECKED
+        } else {
+            MF_UNCHECKED
+        }
+    }
+
+    /// Updates the marks of `menu` (and its submenus) after a dispatch.
+    fn apply(menu: HMENU, dispatch: Dispatch) {
+        unsafe {
+            match dispatch {
+                Dispatch::Ran => (),
+                Dispatch::Checked { id, checked } => {
+                    CheckMenuItem(menu, id.into(), MF_BYCOMMAND | check_flag(checked));
+                }
+                Dispatch::Selected { first, last, id } => {
+                    CheckMenuRadioItem(menu, first.into(), last.into(), id.into(), MF_BYCOMMAND);
+                }
+            }
+        }
+    }
+
+    impl Commands {
+        fn append(&self, menu: &OwnedMenu, layout: &Layout) -> Result<(), Win32Error> {
+            for entry in &layout.entries {
+                let label = wide_null(&entry.label);
+                let enabled = if entry.enabled { MF_ENABLED } else { MF_GRAYED };
+                let mut submenu = None;
+                let (flags, item, label) = match &entry.kind {
+                    EntryKind::Command { id, toggle } => {
+                        let checked = *toggle == Toggle::Check && self.is_checked(*id);
+                        (MF_STRING | enabled | check_flag(checked), usize::from(*id), label.as_ptr())
+                    }
+                    EntryKind::Separator => (MF_SEPARATOR, 0, null()),
+                    EntryKind::Submenu(layout) => {
+                        let menu = submenu.insert(self.create(layout, true)?);
+                        (MF_POPUP | enabled, menu.handle() as usize, label.as_ptr())
+                    }
+                };
+                if unsafe { AppendMenuW(menu.handle(), flags, item, label) } == 0 {
+                    return Err(get_last_error());
+                }
+                // The parent owns an appended submenu now.
+                submenu.map(OwnedMenu::into_raw);
+            }
+            // AppendMenuW can't make radio items, selecting one of a group turns them all into ones.
+            for entry in &layout.entries {
+                if let EntryKind::Command { id, toggle: Toggle::Radio { first, last } } = entry.kind {
+                    if self.is_checked(id) {
+                        apply(menu.handle(), Dispatch::Selected { first, last, id });
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        fn create(&self, layout: &Layout, popup: bool) -> Result<OwnedMenu, Win32Error> {
+            let menu = unsafe { if popup { CreatePopupMenu() } else { CreateMenu() } };
+            if menu.is_null() {
+                return Err(get_last_error());
+            }
+            let menu = OwnedMenu(menu);
+            self.append(&menu, layout)?;
+            Ok(menu)
+        }
+
+        /// Makes a menu bar, for [`WindowBuilder::menu`](crate::window::WindowBuilder::menu) or
+        /// [`set_menu_bar`].
+        pub fn create_menu_bar(&self, layout: &Layout) -> Result<OwnedMenu, Win32Error> {
+            self.create(layout, false)
+        }
+
+        pub fn create_popup_menu(&self, layout: &Layout) -> Result<OwnedMenu, Win32Error> {
+            self.create(layout, true)
+        }
+
+        /// Shows `layout` as a context menu at `screen_point` and waits for a choice, see
+        /// [`TrackPopupMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-trackpopupmenu)
+        ///
+        /// Returns the chosen id without running it, pass it to [`Commands::dispatch`]. That way
+        /// nothing borrows the commands while the menu runs its own message loop.
+        ///
+        /// # Safety
+        /// `hwnd` must be a window handle.
+        pub unsafe fn track_popup(
+            &self,
+            hwnd: HWND,
+            layout: &Layout,
+            screen_point: Point,
+        ) -> Result<Option<u16>, Win32Error> {
+            let menu = self.create_popup_menu(layout)?;
+            let flags = TPM_LEFTALIGN | TPM_RIGHTBUTTON | TPM_RETURNCMD | TPM_NONOTIFY;
+            let id = TrackPopupMenu(menu.handle(), flags, screen_point.x, screen_point.y, 0, hwnd, null());
+            Ok((id != 0).then_some(id as u16))
+        }
+
+        /// Runs the command of a `WM_COMMAND` sent by a menu or an accelerator, `None` for other
+        /// messages and ids we don't know. The window's menu bar is updated to match.
+        ///
+        /// # Safety
+        /// The parameters must be those the window procedure of `hwnd` received.
+        pub unsafe fn handle(&mut self, hwnd: HWND, msg: UINT, w_param: WPARAM, l_param: LPARAM) -> Option<LRESULT> {
+            if msg != WM_COMMAND {
+                return None;
+            }
+            let Params::Command { id, notification: 0 | 1, control: 0 } = Params::decode(msg, w_param, l_param)
+            else {
+                return None;
+            };
+            let dispatch = self.dispatch(id)?;
+            let bar = GetMenu(hwnd);
+            if !bar.is_null() {
+                apply(bar, dispatch);
+            }
+            Some(0)
+        }
+    }
+
+    /// Replaces the menu bar of `hwnd`, the old one is destroyed, see
+    /// [`SetMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setmenu)
+    ///
+    /// # Safety
+    /// `hwnd` must be a window handle.
+    pub unsafe fn set_menu_bar(hwnd: HWND, menu: OwnedMenu) -> Result<(), Win32Error> {
+        let old = GetMenu(hwnd);
+        if SetMenu(hwnd, menu.handle()) == 0 {
+            return Err(get_last_error());
+        }
+        menu.into_raw();
+        if !old.is_null() {
+            drop(OwnedMenu(old));
+        }
+        DrawMenuBar(hwnd);
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    #[test]
+    fn ids_follow_the_items() {
+        let mut commands = Commands::new();
+        let layout = commands.add(
+            Menu::new()
+                .submenu("&File", Menu::new().action("&Open", || ()).separator().action("E&xit", || ()))
+                .item(MenuItem::check("&Grid", false, |_| ()).enabled(false)),
+        );
+        let EntryKind::Submenu(file) = &layout.entries[0].kind else { panic!("{:?}", layout) };
+        let ids: Vec<_> = file.entries.iter().map(|entry| entry.kind.clone()).collect();
+        assert_eq!(
+            ids,
+            [
+                EntryKind::Command { id: FIRST_COMMAND_ID, toggle: Toggle::None },
+                EntryKind::Separator,
+                EntryKind::Command { id: FIRST_COMMAND_ID + 1, toggle: Toggle::None },
+            ]
+        );
+        assert_eq!(layout.entries[1].kind, EntryKind::Command { id: FIRST_COMMAND_ID + 2, toggle: Toggle::Check });
+        assert!(!layout.entries[1].enabled);
+        // A second menu, like a context menu, continues the numbering.
+        let popup = commands.add(Menu::new().action("&Copy", || ()));
+        assert_eq!(popup.entries[0].kind, EntryKind::Command { id: FIRST_COMMAND_ID + 3, toggle: Toggle::None });
+    }
+
+    #[test]
+    fn dispatch_runs_the_closure() {
+        let runs = Rc::new(Cell::new(0));
+        let grid = Rc::new(Cell::new(true));
+        let mut commands = Commands::new();
+        let counter = runs.clone();
+        let shown = grid.clone();
+        commands.add(
+            Menu::new()
+                .action("&Run", move || counter.set(counter.get() + 1))
+                .item(MenuItem::check("&Grid", true, move |checked| shown.set(checked))),
+        );
+        assert_eq!(commands.dispatch(FIRST_COMMAND_ID), Some(Dispatch::Ran));
+        assert_eq!(runs.get(), 1);
+        let check = FIRST_COMMAND_ID + 1;
+        assert!(commands.is_checked(check));
+        assert_eq!(commands.dispatch(check), Some(Dispatch::Checked { id: check, checked: false }));
+        assert!(!grid.get() && !commands.is_checked(check));
+        assert_eq!(commands.dispatch(check), Some(Dispatch::Checked { id: check, checked: true }));
+        assert!(grid.get());
+        assert_eq!(commands.dispatch(FIRST_COMMAND_ID + 2), None);
+        assert_eq!(commands.dispatch(1), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ran out of menu command ids")]
+    fn radio_groups_run_out_of_ids() {
+        let mut commands = Commands { commands: BTreeMap::new(), next_id: u16::MAX - 1 };
+        // Three ids needed, two left.
+        commands.add(
+            Menu::new()
+                .item(MenuItem::radio("a", true, || ()))
+                .item(MenuItem::radio("b", false, || ()))
+                .item(MenuItem::radio("c", false, || ())),
+        );
+    }
+
+    #[test]
+    fn radio_groups_end_at_other_items() {
+        let chosen = Rc::new(RefCell::new(Vec::new()));
+        let radio = |name: &'static str, selected| {
+            let chosen = chosen.clone();
+            MenuItem::radio(name, selected, move || chosen.borrow_mut().push(name))
+        };
+        let mut commands = Commands::new();
+        let layout = commands.add(
+            Menu::new()
+                .item(radio("small", false))
+                .item(radio("large", true))
+                .separator()
+                .item(radio("red", false))
+                .item(radio("blue", false)),
+        );
+        let first = FIRST_COMMAND_ID;
+        assert_eq!(layout.entries[1].kind, EntryKind::Command {
+            id: first + 1,
+            toggle: Toggle::Radio { first, last: first + 1 },
+        });
+        assert_eq!(layout.entries[4].kind, EntryKind::Command {
+            id: first + 3,
+            toggle: Toggle::Radio { first: first + 2, last: first + 3 },
+        });
+        let checked = |commands: &Commands| (first..first + 4).map(|id| commands.is_checked(id)).collect::<Vec<_>>();
+        // Nothing selected in the second group means its first item.
+        assert_eq!(checked(&commands), [false, true, true, false]);
+        assert_eq!(commands.dispatch(first + 3), Some(Dispatch::Selected { first: first + 2, last: first + 3, id: first + 3 }));
+        assert_eq!(commands.dispatch(first), Some(Dispatch::Selected { first, last: first + 1, id: first }));
+        assert_eq!(checked(&commands), [true, false, false, true]);
+        assert_eq!(*chosen.borrow(), ["blue", "small"]);
+    }
+}
@@ -0,0 +1,572 @@
+//! Window message ids and what their `WPARAM` and `LPARAM` mean.
+//!
+//! Decoding is pure bit fiddling, so it works on recorded messages as well as live ones.
+
+use crate::geometry::Point;
+
+pub const WM_CREATE: u32 = 0x0001;
+pub const WM_DESTROY: u32 = 0x0002;
+pub const WM_MOVE: u32 = 0x0003;
+pub const WM_SIZE: u32 = 0x0005;
+pub const WM_SETTEXT: u32 = 0x000C;
+pub const WM_GETTEXT: u32 = 0x000D;
+pub const WM_PAINT: u32 = 0x000F;
+pub const WM_CLOSE: u32 = 0x0010;
+pub const WM_QUIT: u32 = 0x0012;
+pub const WM_ERASEBKGND: u32 = 0x0014;
+pub const WM_SETTINGCHANGE: u32 = 0x001A;
+pub const WM_GETMINMAXINFO: u32 = 0x0024;
+pub const WM_DRAWITEM: u32 = 0x002B;
+pub const WM_MEASUREITEM: u32 = 0x002C;
+pub const WM_DELETEITEM: u32 = 0x002D;
+pub const WM_COMPAREITEM: u32 = 0x0039;
+pub const WM_WINDOWPOSCHANGING: u32 = 0x0046;
+pub const WM_WINDOWPOSCHANGED: u32 = 0x0047;
+pub const WM_COPYDATA: u32 = 0x004A;
+pub const WM_NOTIFY: u32 = 0x004E;
+pub const WM_HELP: u32 = 0x0053;
+pub const WM_STYLECHANGING: u32 = 0x007C;
+pub const WM_STYLECHANGED: u32 = 0x007D;
+pub const WM_SETICON: u32 = 0x0080;
+pub const WM_NCCREATE: u32 = 0x0081;
+pub const WM_NCDESTROY: u32 = 0x0082;
+pub const WM_NCCALCSIZE: u32 = 0x0083;
+pub const WM_KEYDOWN: u32 = 0x0100;
+pub const WM_KEYUP: u32 = 0x0101;
+pub const WM_CHAR: u32 = 0x0102;
+pub const WM_SYSKEYDOWN: u32 = 0x0104;
+pub const WM_SYSKEYUP: u32 = 0x0105;
+pub const WM_SYSCHAR: u32 = 0x0106;
+pub const WM_COMMAND: u32 = 0x0111;
+pub const WM_TIMER: u32 = 0x0113;
+pub const WM_MOUSEMOVE: u32 = 0x0200;
+pub const WM_LBUTTONDOWN: u32 = 0x0201;
+pub const WM_LBUTTONUP: u32 = 0x0202;
+pub const WM_LBUTTONDBLCLK: u32 = 0x0203;
+pub const WM_RBUTTONDOWN: u32 = 0x0204;
+pub const WM_RBUTTONUP: u32 = 0x0205;
+pub const WM_RBUTTONDBLCLK: u32 = 0x0206;
+pub const WM_MBUTTONDOWN: u32 = 0x0207;
+pub const WM_MBUTTONUP: u32 = 0x0208;
+pub const WM_MBUTTONDBLCLK: u32 = 0x0209;
+pub const WM_MOUSEWHEEL: u32 = 0x020A;
+pub const WM_SIZING: u32 = 0x0214;
+pub const WM_MOVING: u32 = 0x0216;
+pub const WM_DROPFILES: u32 = 0x0233;
+pub const WM_DPICHANGED: u32 = 0x02E0;
+/// The first message id free for a window class's private messages.
+pub const WM_USER: u32 = 0x0400;
+/// The first message id free for an application's private messages.
+pub const WM_APP: u32 = 0x8000;
+/// Ids from here up are handed out by `RegisterWindowMessageW`.
+pub const FIRST_REGISTERED_MESSAGE: u32 = 0xC000;
+
+/// The low 16 bits, `LOWORD`.
+pub const fn loword(value: usize) -> u16 {
+    value as u16
+}
+
+/// Bits 16 to 31, `HIWORD`.
+pub const fn hiword(value: usize) -> u16 {
+    (value >> 16) as u16
+}
+
+/// The signed client coordinates packed into the `LPARAM` of mouse messages
+/// (`GET_X_LPARAM` and `GET_Y_LPARAM`).
+pub const fn point_from_lparam(l_param: isize) -> Point {
+    Point::new(loword(l_param as usize) as i16 as i32, hiword(l_param as usize) as i16 as i32)
+}
+
+/// The parameters of a message, for the messages we know how to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Params {
+    /// Mouse buttons and moves. `keys` holds the `MK_*` flags of the buttons and modifiers held.
+    Mouse { position: Point, keys: u16 },
+    /// `WM_MOUSEWHEEL`, the position is in screen coordinates. One notch is a `delta` of 120.
+    Wheel { position: Point, delta: i16, keys: u16 },
+    /// Key down and up messages, the bit fields of `LPARAM` spelled out.
+    Key {
+        virtual_key: u16,
+        repeat_count: u16,
+        scan_code: u8,
+        extended: bool,
+        previously_down: bool,
+        released: bool,
+    },
+    /// A UTF-16 code unit typed.
+    Char { code_unit: u16, repeat_count: u16 },
+    /// `WM_SIZE`, `kind` is one of the `SIZE_*` values.
+    Size { kind: usize, width: u16, height: u16 },
+    /// `WM_MOVE`, the client area's new top left corner in screen coordinates.
+    Move { position: Point },
+    /// `WM_COMMAND`, `control` is the sending control's window or 0 for menus and accelerators.
+    Command { id: u16, notification: u16, control: isize },
+    Timer { id: usize },
+    /// Anything else, or messages whose `LPARAM` points at a structure.
+    Raw { w_param: usize, l_param: isize },
+}
+
+impl Params {
+    pub fn decode(message: u32, w_param: usize, l_param: isize) -> Self {
+        let key_data = l_param as usize;
+        match message {
+            WM_MOUSEMOVE | WM_LBUTTONDOWN..=WM_MBUTTONDBLCLK => {
+                Params::Mouse { position: point_from_lparam(l_param), keys: loword(w_param) }
+            }
+            WM_MOUSEWHEEL => Params::Wheel {
+                position: point_from_lparam(l_param),
+                delta: hiword(w_param) as i16,
+                keys: loword(w_param),
+            },
+            WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => Params::Key {
+                virtual_key: loword(w_param),
+                repeat_count: loword(key_data),
+                scan_code: (key_data >> 16) as u8,
+                extended: key_data & (1 << 24) != 0,
+                previously_down: key_data & (1 << 30) != 0,
+                released: key_data & (1 << 31) != 0,
+            },
+            WM_CHAR | WM_SYSCHAR => Params::Char { code_unit: loword(w_param), repeat_count: loword(key_data) },
+            WM_SIZE => Params::Size { kind: w_param, width: loword(key_data), height: hiword(key_data) },
+            WM_MOVE => Params::Move { position: point_from_lparam(l_param) },
+            WM_COMMAND => Params::Command { id: loword(w_param), notification: hiword(w_param), control: l_param },
+            WM_TIMER => Params::Timer { id: w_param },
+            _ => Params::Raw { w_param, l_param },
+        }
+    }
+}
+
+/// What a message id is, as far as the id alone tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageName {
+    /// A system message, by its `WM_*` name.
+    Known(&'static str),
+    /// `WM_USER + offset`, private to a window class (common controls use these too).
+    User(u32),
+    /// `WM_APP + offset`, private to an application.
+    App(u32),
+    /// Returned by `RegisterWindowMessageW`, its string can be looked up at run time.
+    Registered(u32),
+    /// A system id without a documented name.
+    Unknown(u32),
+}
+
+impl MessageName {
+    pub fn of(message: u32) -> Self {
+        match message {
+            WM_USER..=0x7FFF => MessageName::User(message - WM_USER),
+            WM_APP..=0xBFFF => MessageName::App(message - WM_APP),
+            FIRST_REGISTERED_MESSAGE..=0xFFFF => MessageName::Registered(message),
+            _ => match NAMES.binary_search_by_key(&message, |&(id, _)| id) {
+                Ok(index) => MessageName::Known(NAMES[index].1),
+                Err(_) => MessageName::Unknown(message),
+            },
+        }
+    }
+}
+
+impl core::fmt::Display for MessageName {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            MessageName::Known(name) => f.write_str(name),
+            MessageName::User(offset) => write!(f, "WM_USER+{}", offset),
+            MessageName::App(offset) => write!(f, "WM_APP+{}", offset),
+            MessageName::Registered(id) => write!(f, "Registered({:#06x})", id),
+            MessageName::Unknown(id) => write!(f, "Unknown({:#06x})", id),
+        }
+    }
+}
+
+/// A rough grouping of messages, for choosing what to trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageClass {
+    /// Creation, sizing, activation, focus and everything else about the window itself.
+    Window,
+    /// The `WM_NC*` messages about the frame and caption.
+    NonClient,
+    /// Painting and control colors.
+    Paint,
+    Mouse,
+    /// Keys and characters.
+    Keyboard,
+    /// Menus and commands.
+    Menu,
+    Timer,
+    Ime,
+    Clipboard,
+    Dde,
+    Mdi,
+    User,
+    App,
+    Registered,
+    Unknown,
+}
+
+impl MessageClass {
+    pub const ALL: [MessageClass; 15] = [
+        MessageClass::Window,
+        MessageClass::NonClient,
+        MessageClass::Paint,
+        MessageClass::Mouse,
+        MessageClass::Keyboard,
+        MessageClass::Menu,
+        MessageClass::Timer,
+        MessageClass::Ime,
+        MessageClass::Clipboard,
+        MessageClass::Dde,
+        MessageClass::Mdi,
+        MessageClass::User,
+        MessageClass::App,
+        MessageClass::Registered,
+        MessageClass::Unknown,
+    ];
+
+    pub fn of(message: u32) -> Self {
+        let name = match MessageName::of(message) {
+            MessageName::Known(name) => name,
+            MessageName::User(_) => return MessageClass::User,
+            MessageName::App(_) => return MessageClass::App,
+            MessageName::Registered(_) => return MessageClass::Registered,
+            MessageName::Unknown(_) => return MessageClass::Unknown,
+        };
+        match message {
+            0x0100..=0x0109 | 0x0312 => MessageClass::Keyboard,
+            0x0200..=0x020E | 0x02A1 | 0x02A3 | 0x0020 | 0x0021 | 0x0215 => MessageClass::Mouse,
+            0x0300..=0x030E | 0x031D => MessageClass::Clipboard,
+            0x03E0..=0x03E8 => MessageClass::Dde,
+            WM_TIMER | 0x0118 => MessageClass::Timer,
+            WM_COMMAND | 0x0112 | 0x0211 | 0x0212 | 0x0213 => MessageClass::Menu,
+            WM_PAINT | WM_ERASEBKGND | 0x0088 | 0x0317 | 0x0318 | 0x0026 | 0x0027 => MessageClass::Paint,
+            _ if name.starts_with("WM_NC") => MessageClass::NonClient,
+            _ if name.starts_with("WM_CTLCOLOR") => MessageClass::Paint,
+            _ if name.starts_with("WM_IME_") => MessageClass::Ime,
+            _ if name.starts_with("WM_MDI") => MessageClass::Mdi,
+            _ if name.contains("MENU") => MessageClass::Menu,
+            _ => MessageClass::Window,
+        }
+    }
+}
+
+/// Every documented system message below `WM_USER` (and `WM_SYSTIMER`, which is not documented
+/// but shows up constantly), sorted by id. Where two names share an id the current one is used.
+const NAMES: [(u32, &str); 252] = [
+    (0x0000, "WM_NULL"),
+    (0x0001, "WM_CREATE"),
+    (0x0002, "WM_DESTROY"),
+    (0x0003, "WM_MOVE"),
+    (0x0005, "WM_SIZE"),
+    (0x0006, "WM_ACTIVATE"),
+    (0x0007, "WM_SETFOCUS"),
+    (0x0008, "WM_KILLFOCUS"),
+    (0x000A, "WM_ENABLE"),
+    (0x000B, "WM_SETREDRAW"),
+    (0x000C, "WM_SETTEXT"),
+    (0x000D, "WM_GETTEXT"),
+    (0x000E, "WM_GETTEXTLENGTH"),
+    (0x000F, "WM_PAINT"),
+    (0x0010, "WM_CLOSE"),
+    (0x0011, "WM_QUERYENDSESSION"),
+    (0x0012, "WM_QUIT"),
+    (0x0013, "WM_QUERYOPEN"),
+    (0x0014, "WM_ERASEBKGND"),
+    (0x0015, "WM_SYSCOLORCHANGE"),
+    (0x0016, "WM_ENDSESSION"),
+    (0x0018, "WM_SHOWWINDOW"),
+    (0x0019, "WM_CTLCOLOR"),
+    (0x001A, "WM_SETTINGCHANGE"),
+    (0x001B, "WM_DEVMODECHANGE"),
+    (0x001C, "WM_ACTIVATEAPP"),
+    (0x001D, "WM_FONTCHANGE"),
+    (0x001E, "WM_TIMECHANGE"),
+    (0x001F, "WM_CANCELMODE"),
+    (0x0020, "WM_SETCURSOR"),
+    (0x0021, "WM_MOUSEACTIVATE"),
+    (0x0022, "WM_CHILDACTIVATE"),
+    (0x0023, "WM_QUEUESYNC"),
+    (0x0024, "WM_GETMINMAXINFO"),
+    (0x0026, "WM_PAINTICON"),
+    (0x0027, "WM_ICONERASEBKGND"),
+    (0x0028, "WM_NEXTDLGCTL"),
+    (0x002A, "WM_SPOOLERSTATUS"),
+    (0x002B, "WM_DRAWITEM"),
+    (0x002C, "WM_MEASUREITEM"),
+    (0x002D, "WM_DELETEITEM"),
+    (0x002E, "WM_VKEYTOITEM"),
+    (0x002F, "WM_CHARTOITEM"),
+    (0x0030, "WM_SETFONT"),
+    (0x0031, "WM_GETFONT"),
+    (0x0032, "WM_SETHOTKEY"),
+    (0x0033, "WM_GETHOTKEY"),
+    (0x0037, "WM_QUERYDRAGICON"),
+    (0x0039, "WM_COMPAREITEM"),
+    (0x003D, "WM_GETOBJECT"),
+    (0x0041, "WM_COMPACTING"),
+    (0x0044, "WM_COMMNOTIFY"),
+    (0x0046, "WM_WINDOWPOSCHANGING"),
+    (0x0047, "WM_WINDOWPOSCHANGED"),
+    (0x0048, "WM_POWER"),
+    (0x004A, "WM_COPYDATA"),
+    (0x004B, "WM_CANCELJOURNAL"),
+    (0x004E, "WM_NOTIFY"),
+    (0x0050, "WM_INPUTLANGCHANGEREQUEST"),
+    (0x0051, "WM_INPUTLANGCHANGE"),
+    (0x0052, "WM_TCARD"),
+    (0x0053, "WM_HELP"),
+    (0x0054, "WM_USERCHANGED"),
+    (0x0055, "WM_NOTIFYFORMAT"),
+    (0x007B, "WM_CONTEXTMENU"),
+    (0x007C, "WM_STYLECHANGING"),
+    (0x007D, "WM_STYLECHANGED"),
+    (0x007E, "WM_DISPLAYCHANGE"),
+    (0x007F, "WM_GETICON"),
+    (0x0080, "WM_SETICON"),
+    (0x0081, "WM_NCCREATE"),
+    (0x0082, "WM_NCDESTROY"),
+    (0x0083, "WM_NCCALCSIZE"),
+    (0x0084, "WM_NCHITTEST"),
+    (0x0085, "WM_NCPAINT"),
+    (0x0086, "WM_NCACTIVATE"),
+    (0x0087, "WM_GETDLGCODE"),
+    (0x0088, "WM_SYNCPAINT"),
+    (0x00A0, "WM_NCMOUSEMOVE"),
+    (0x00A1, "WM_NCLBUTTONDOWN"),
+    (0x00A2, "WM_NCLBUTTONUP"),
+    (0x00A3, "WM_NCLBUTTONDBLCLK"),
+    (0x00A4, "WM_NCRBUTTONDOWN"),
+    (0x00A5, "WM_NCRBUTTONUP"),
+    (0x00A6, "WM_NCRBUTTONDBLCLK"),
+    (0x00A7, "WM_NCMBUTTONDOWN"),
+    (0x00A8, "WM_NCMBUTTONUP"),
+    (0x00A9, "WM_NCMBUTTONDBLCLK"),
+    (0x00AB, "WM_NCXBUTTONDOWN"),
+    (0x00AC, "WM_NCXBUTTONUP"),
+    (0x00AD, "WM_NCXBUTTONDBLCLK"),
+    (0x00FE, "WM_INPUT_DEVICE_CHANGE"),
+    (0x00FF, "WM_INPUT"),
+    (0x0100, "WM_KEYDOWN"),
+    (0x0101, "WM_KEYUP"),
+    (0x0102, "WM_CHAR"),
+    (0x0103, "WM_DEADCHAR"),
+    (0x0104, "WM_SYSKEYDOWN"),
+    (0x0105, "WM_SYSKEYUP"),
+    (0x0106, "WM_SYSCHAR"),
+    (0x0107, "WM_SYSDEADCHAR"),
+    (0x0109, "WM_UNICHAR"),
+    (0x010D, "WM_IME_STARTCOMPOSITION"),
+    (0x010E, "WM_IME_ENDCOMPOSITION"),
+    (0x010F, "WM_IME_COMPOSITION"),
+    (0x0110, "WM_INITDIALOG"),
+    (0x0111, "WM_COMMAND"),
+    (0x0112, "WM_SYSCOMMAND"),
+    (0x0113, "WM_TIMER"),
+    (0x0114, "WM_HSCROLL"),
+    (0x0115, "WM_VSCROLL"),
+    (0x0116, "WM_INITMENU"),
+    (0x0117, "WM_INITMENUPOPUP"),
+    (0x0118, "WM_SYSTIMER"),
+    (0x0119, "WM_GESTURE"),
+    (0x011A, "WM_GESTURENOTIFY"),
+    (0x011F, "WM_MENUSELECT"),
+    (0x0120, "WM_MENUCHAR"),
+    (0x0121, "WM_ENTERIDLE"),
+    (0x0122, "WM_MENURBUTTONUP"),
+    (0x0123, "WM_MENUDRAG"),
+    (0x0124, "WM_MENUGETOBJECT"),
+    (0x0125, "WM_UNINITMENUPOPUP"),
+    (0x0126, "WM_MENUCOMMAND"),
+    (0x0127, "WM_CHANGEUISTATE"),
+    (0x0128, "WM_UPDATEUISTATE"),
+    (0x0129, "WM_QUERYUISTATE"),
+    (0x0132, "WM_CTLCOLORMSGBOX"),
+    (0x0133, "WM_CTLCOLOREDIT"),
+    (0x0134, "WM_CTLCOLORLISTBOX"),
+    (0x0135, "WM_CTLCOLORBTN"),
+    (0x0136, "WM_CTLCOLORDLG"),
+    (0x0137, "WM_CTLCOLORSCROLLBAR"),
+    (0x0138, "WM_CTLCOLORSTATIC"),
+    (0x01E1, "MN_GETHMENU"),
+    (0x0200, "WM_MOUSEMOVE"),
+    (0x0201, "WM_LBUTTONDOWN"),
+    (0x0202, "WM_LBUTTONUP"),
+    (0x0203, "WM_LBUTTONDBLCLK"),
+    (0x0204, "WM_RBUTTONDOWN"),
+    (0x0205, "WM_RBUTTONUP"),
+    (0x0206, "WM_RBUTTONDBLCLK"),
+    (0x0207, "WM_MBUTTONDOWN"),
+    (0x0208, "WM_MBUTTONUP"),
+    (0x0209, "WM_MBUTTONDBLCLK"),
+    (0x020A, "WM_MOUSEWHEEL"),
+    (0x020B, "WM_XBUTTONDOWN"),
+    (0x020C, "WM_XBUTTONUP"),
+    (0x020D, "WM_XBUTTONDBLCLK"),
+    (0x020E, "WM_MOUSEHWHEEL"),
+    (0x0210, "WM_PARENTNOTIFY"),
+    (0x0211, "WM_ENTERMENULOOP"),
+    (0x0212, "WM_EXITMENULOOP"),
+    (0x0213, "WM_NEXTMENU"),
+    (0x0214, "WM_SIZING"),
+    (0x0215, "WM_CAPTURECHANGED"),
+    (0x0216, "WM_MOVING"),
+    (0x0218, "WM_POWERBROADCAST"),
+    (0x0219, "WM_DEVICECHANGE"),
+    (0x0220, "WM_MDICREATE"),
+    (0x0221, "WM_MDIDESTROY"),
+    (0x0222, "WM_MDIACTIVATE"),
+    (0x0223, "WM_MDIRESTORE"),
+    (0x0224, "WM_MDINEXT"),
+    (0x0225, "WM_MDIMAXIMIZE"),
+    (0x0226, "WM_MDITILE"),
+    (0x0227, "WM_MDICASCADE"),
+    (0x0228, "WM_MDIICONARRANGE"),
+    (0x0229, "WM_MDIGETACTIVE"),
+    (0x0230, "WM_MDISETMENU"),
+    (0x0231, "WM_ENTERSIZEMOVE"),
+    (0x0232, "WM_EXITSIZEMOVE"),
+    (0x0233, "WM_DROPFILES"),
+    (0x0234, "WM_MDIREFRESHMENU"),
+    (0x0238, "WM_POINTERDEVICECHANGE"),
+    (0x0239, "WM_POINTERDEVICEINRANGE"),
+    (0x023A, "WM_POINTERDEVICEOUTOFRANGE"),
+    (0x0240, "WM_TOUCH"),
+    (0x0241, "WM_NCPOINTERUPDATE"),
+    (0x0242, "WM_NCPOINTERDOWN"),
+    (0x0243, "WM_NCPOINTERUP"),
+    (0x0245, "WM_POINTERUPDATE"),
+    (0x0246, "WM_POINTERDOWN"),
+    (0x0247, "WM_POINTERUP"),
+    (0x0249, "WM_POINTERENTER"),
+    (0x024A, "WM_POINTERLEAVE"),
+    (0x024B, "WM_POINTERACTIVATE"),
+    (0x024C, "WM_POINTERCAPTURECHANGED"),
+    (0x024D, "WM_TOUCHHITTESTING"),
+    (0x024E, "WM_POINTERWHEEL"),
+    (0x024F, "WM_POINTERHWHEEL"),
+    (0x0251, "WM_POINTERROUTEDTO"),
+    (0x0252, "WM_POINTERROUTEDAWAY"),
+    (0x0253, "WM_POINTERROUTEDRELEASED"),
+    (0x0281, "WM_IME_SETCONTEXT"),
+    (0x0282, "WM_IME_NOTIFY"),
+    (0x0283, "WM_IME_CONTROL"),
+    (0x0284, "WM_IME_COMPOSITIONFULL"),
+    (0x0285, "WM_IME_SELECT"),
+    (0x0286, "WM_IME_CHAR"),
+    (0x0288, "WM_IME_REQUEST"),
+    (0x0290, "WM_IME_KEYDOWN"),
+    (0x0291, "WM_IME_KEYUP"),
+    (0x02A0, "WM_NCMOUSEHOVER"),
+    (0x02A1, "WM_MOUSEHOVER"),
+    (0x02A2, "WM_NCMOUSELEAVE"),
+    (0x02A3, "WM_MOUSELEAVE"),
+    (0x02B1, "WM_WTSSESSION_CHANGE"),
+    (0x02E0, "WM_DPICHANGED"),
+    (0x02E2, "WM_DPICHANGED_BEFOREPARENT"),
+    (0x02E3, "WM_DPICHANGED_AFTERPARENT"),
+    (0x02E4, "WM_GETDPISCALEDSIZE"),
+    (0x0300, "WM_CUT"),
+    (0x0301, "WM_COPY"),
+    (0x0302, "WM_PASTE"),
+    (0x0303, "WM_CLEAR"),
+    (0x0304, "WM_UNDO"),
+    (0x0305, "WM_RENDERFORMAT"),
+    (0x0306, "WM_RENDERALLFORMATS"),
+    (0x0307, "WM_DESTROYCLIPBOARD"),
+    (0x0308, "WM_DRAWCLIPBOARD"),
+    (0x0309, "WM_PAINTCLIPBOARD"),
+    (0x030A, "WM_VSCROLLCLIPBOARD"),
+    (0x030B, "WM_SIZECLIPBOARD"),
+    (0x030C, "WM_ASKCBFORMATNAME"),
+    (0x030D, "WM_CHANGECBCHAIN"),
+    (0x030E, "WM_HSCROLLCLIPBOARD"),
+    (0x030F, "WM_QUERYNEWPALETTE"),
+    (0x0310, "WM_PALETTEISCHANGING"),
+    (0x0311, "WM_PALETTECHANGED"),
+    (0x0312, "WM_HOTKEY"),
+    (0x0317, "WM_PRINT"),
+    (0x0318, "WM_PRINTCLIENT"),
+    (0x0319, "WM_APPCOMMAND"),
+    (0x031A, "WM_THEMECHANGED"),
+    (0x031D, "WM_CLIPBOARDUPDATE"),
+    (0x031E, "WM_DWMCOMPOSITIONCHANGED"),
+    (0x031F, "WM_DWMNCRENDERINGCHANGED"),
+    (0x0320, "WM_DWMCOLORIZATIONCOLORCHANGED"),
+    (0x0321, "WM_DWMWINDOWMAXIMIZEDCHANGE"),
+    (0x0323, "WM_DWMSENDICONICTHUMBNAIL"),
+    (0x0326, "WM_DWMSENDICONICLIVEPREVIEWBITMAP"),
+    (0x033F, "WM_GETTITLEBARINFOEX"),
+    (0x03E0, "WM_DDE_INITIATE"),
+    (0x03E1, "WM_DDE_TERMINATE"),
+    (0x03E2, "WM_DDE_ADVISE"),
+    (0x03E3, "WM_DDE_UNADVISE"),
+    (0x03E4, "WM_DDE_ACK"),
+    (0x03E5, "WM_DDE_DATA"),
+    (0x03E6, "WM_DDE_REQUEST"),
+    (0x03E7, "WM_DDE_POKE"),
+    (0x03E8, "WM_DDE_EXECUTE"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_coordinates_are_signed() {
+        let l_param = (0xfff6_u32 << 16 | 0x0014) as isize;
+        assert_eq!(
+            Params::decode(WM_LBUTTONDOWN, 1, l_param),
+            Params::Mouse { position: Point::new(20, -10), keys: 1 }
+        );
+        assert_eq!(
+            Params::decode(WM_MOUSEWHEEL, (0xff88_usize << 16) | 4, 0),
+            Params::Wheel { position: Point::new(0, 0), delta: -120, keys: 4 }
+        );
+    }
+
+    #[test]
+    fn key_bit_fields() {
+        // Escape released: repeat 1, scan code 1, previously down, transition.
+        let l_param = 0xc001_0001_u32 as i32 as isize;
+        assert_eq!(
+            Params::decode(WM_KEYUP, 0x1b, l_param),
+            Params::Key {
+                virtual_key: 0x1b,
+                repeat_count: 1,
+                scan_code: 1,
+                extended: false,
+                previously_down: true,
+                released: true,
+            }
+        );
+        assert_eq!(Params::decode(WM_SIZE, 0, 0x0096_012c), Params::Size { kind: 0, width: 300, height: 150 });
+        assert_eq!(Params::decode(WM_PAINT, 0, 0), Params::Raw { w_param: 0, l_param: 0 });
+    }
+
+    #[test]
+    fn names_cover_every_range() {
+        assert!(NAMES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(MessageName::of(WM_PAINT), MessageName::Known("WM_PAINT"));
+        assert_eq!(MessageName::of(0x0000).to_string(), "WM_NULL");
+        assert_eq!(MessageName::of(0x03E8).to_string(), "WM_DDE_EXECUTE");
+        assert_eq!(MessageName::of(WM_USER + 5).to_string(), "WM_USER+5");
+        assert_eq!(MessageName::of(WM_APP).to_string(), "WM_APP+0");
+        assert_eq!(MessageName::of(0xC0F3).to_string(), "Registered(0xc0f3)");
+        assert_eq!(MessageName::of(0x0004).to_string(), "Unknown(0x0004)");
+        assert_eq!(MessageName::of(0x1_0000).to_string(), "Unknown(0x10000)");
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(MessageClass::of(WM_NCCREATE), MessageClass::NonClient);
+        assert_eq!(MessageClass::of(0x00A1), MessageClass::NonClient);
+        assert_eq!(MessageClass::of(WM_MOUSEMOVE), MessageClass::Mouse);
+        assert_eq!(MessageClass::of(WM_KEYDOWN), MessageClass::Keyboard);
+        assert_eq!(MessageClass::of(WM_PAINT), MessageClass::Paint);
+        assert_eq!(MessageClass::of(0x0138), MessageClass::Paint);
+        assert_eq!(MessageClass::of(0x0117), MessageClass::Menu);
+        assert_eq!(MessageClass::of(0x0282), MessageClass::Ime);
+        assert_eq!(MessageClass::of(0x0302), MessageClass::Clipboard);
+        assert_eq!(MessageClass::of(0x0222), MessageClass::Mdi);
+        assert_eq!(MessageClass::of(WM_SIZE), MessageClass::Window);
+        assert_eq!(MessageClass::of(WM_USER), MessageClass::User);
+        assert_eq!(MessageClass::of(0xC000), MessageClass::Registered);
+    }
+}
@@ -0,0 +1,382 @@
+use core::ffi::c_void;
+use std::collections::VecDeque;
+
+use crate::error::Win32Error;
+
+// Values defined by Windows for `MessageBoxW`, see the `uType` parameter.
+pub const MB_OK: u32 = 0x0000_0000;
+pub const MB_OKCANCEL: u32 = 0x0000_0001;
+pub const MB_ABORTRETRYIGNORE: u32 = 0x0000_0002;
+pub const MB_YESNOCANCEL: u32 = 0x0000_0003;
+pub const MB_YESNO: u32 = 0x0000_0004;
+pub const MB_RETRYCANCEL: u32 = 0x0000_0005;
+pub const MB_ICONERROR: u32 = 0x0000_0010;
+pub const MB_ICONQUESTION: u32 = 0x0000_0020;
+pub const MB_ICONWARNING: u32 = 0x0000_0030;
+pub const MB_ICONINFORMATION: u32 = 0x0000_0040;
+pub const MB_DEFBUTTON1: u32 = 0x0000_0000;
+pub const MB_DEFBUTTON2: u32 = 0x0000_0100;
+pub const MB_DEFBUTTON3: u32 = 0x0000_0200;
+pub const MB_APPLMODAL: u32 = 0x0000_0000;
+pub const MB_SYSTEMMODAL: u32 = 0x0000_1000;
+pub const MB_TASKMODAL: u32 = 0x0000_2000;
+
+// Values returned by `MessageBoxW` for the button that was pressed.
+pub const IDOK: i32 = 1;
+pub const IDCANCEL: i32 = 2;
+pub const IDABORT: i32 = 3;
+pub const IDRETRY: i32 = 4;
+pub const IDIGNORE: i32 = 5;
+pub const IDYES: i32 = 6;
+pub const IDNO: i32 = 7;
+
+/// The set of buttons shown in the message box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Buttons {
+    #[default]
+    Ok,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+    RetryCancel,
+    AbortRetryIgnore,
+}
+
+impl Buttons {
+    pub const fn flags(self) -> u32 {
+        match self {
+            Buttons::Ok => MB_OK,
+            Buttons::OkCancel => MB_OKCANCEL,
+            Buttons::YesNo => MB_YESNO,
+            Buttons::YesNoCancel => MB_YESNOCANCEL,
+            Buttons::RetryCancel => MB_RETRYCANCEL,
+            Buttons::AbortRetryIgnore => MB_ABORTRETRYIGNORE,
+        }
+    }
+
+    /// The results each button produces, from left to right.
+    pub const fn results(self) -> &'static [MessageBoxResult] {
+        use MessageBoxResult::*;
+        match self {
+            Buttons::Ok => &[Ok],
+            Buttons::OkCancel => &[Ok, Cancel],
+            Buttons::YesNo => &[Yes, No],
+            Buttons::YesNoCancel => &[Yes, No, Cancel],
+            Buttons::RetryCancel => &[Retry, Cancel],
+            Buttons::AbortRetryIgnore => &[Abort, Retry, Ignore],
+        }
+    }
+}
+
+/// The icon shown next to the message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Info,
+    Warning,
+    Error,
+    Question,
+}
+
+impl Icon {
+    pub const fn flags(self) -> u32 {
+        match self {
+            Icon::Info => MB_ICONINFORMATION,
+            Icon::Warning => MB_ICONWARNING,
+            Icon::Error => MB_ICONERROR,
+            Icon::Question => MB_ICONQUESTION,
+        }
+    }
+}
+
+/// Which button is focused when the box opens, counting from the left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefaultButton {
+    #[default]
+    First,
+    Second,
+    Third,
+}
+
+impl DefaultButton {
+    pub const fn flags(self) -> u32 {
+        match self {
+            DefaultButton::First => MB_DEFBUTTON1,
+            DefaultButton::Second => MB_DEFBUTTON2,
+            DefaultButton::Third => MB_DEFBUTTON3,
+        }
+    }
+
+    const fn position(self) -> usize {
+        self as usize
+    }
+}
+
+/// What the message box blocks while it is open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Modality {
+    /// Disables the owner window until the box is closed.
+    #[default]
+    Application,
+    /// Like `Application`, but the box also stays on top of every other window.
+    System,
+    /// Disables every top level window of the calling thread, useful without an owner.
+    Task,
+}
+
+impl Modality {
+    pub const fn flags(self) -> u32 {
+        match self {
+            Modality::Application => MB_APPLMODAL,
+            Modality::System => MB_SYSTEMMODAL,
+            Modality::Task => MB_TASKMODAL,
+        }
+    }
+}
+
+/// The button the user pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBoxResult {
+    Ok = IDOK as isize,
+    Cancel = IDCANCEL as isize,
+    Abort = IDABORT as isize,
+    Retry = IDRETRY as isize,
+    Ignore = IDIGNORE as isize,
+    Yes = IDYES as isize,
+    No = IDNO as isize,
+}
+
+impl MessageBoxResult {
+    /// Converts the value returned by `MessageBoxW`.
+    pub const fn from_id(id: i32) -> Option<Self> {
+        match id {
+            IDOK => Some(Self::Ok),
+            IDCANCEL => Some(Self::Cancel),
+            IDABORT => Some(Self::Abort),
+            IDRETRY => Some(Self::Retry),
+            IDIGNORE => Some(Self::Ignore),
+            IDYES => Some(Self::Yes),
+            IDNO => Some(Self::No),
+            _ => None,
+        }
+    }
+}
+
+/// A message box description, shown through a [`MessageBoxBackend`].
+///
+/// ```
+/// # use triangle_from_scratch::message_box::*;
+/// let answer = MessageBox::new("Quit?", "Unsaved changes will be lost.")
+///     .buttons(Buttons::YesNo)
+///     .icon(Icon::Question)
+///     .default_button(DefaultButton::Second)
+///     .show_with(&mut ScriptedMessageBoxes::new([MessageBoxResult::Yes]));
+/// assert_eq!(answer, Ok(MessageBoxResult::Yes));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageBox {
+    pub title: String,
+    pub text: String,
+    pub owner: *mut c_void,
+    pub buttons: Buttons,
+    pub icon: Option<Icon>,
+    pub default_button: DefaultButton,
+    pub modality: Modality,
+}
+
+impl MessageBox {
+    pub fn new(title: &str, text: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            text: text.to_owned(),
+            owner: core::ptr::null_mut(),
+            buttons: Buttons::default(),
+            icon: None,
+            default_button: DefaultButton::default(),
+            modality: Modality::default(),
+        }
+    }
+
+    /// The window disabled while the box is open, an `HWND` on Windows.
+    pub fn owner(mut self, owner: *mut c_void) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    pub fn buttons(mut self, buttons: Buttons) -> Self {
+        self.buttons = buttons;
+        self
+    }
+
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn default_button(mut self, default_button: DefaultButton) -> Self {
+        self.default_button = default_button;
+        self
+    }
+
+    pub fn modality(mut self, modality: Modality) -> Self {
+        self.modality = modality;
+        self
+    }
+
+    /// The `uType` flags passed to `MessageBoxW`.
+    pub fn style(&self) -> u32 {
+        self.buttons.flags()
+            | self.icon.map_or(0, Icon::flags)
+            | self.default_button.flags()
+            | self.modality.flags()
+    }
+
+    /// The result of pressing the default button, i.e. what `Enter` answers.
+    ///
+    /// Windows falls back to the first button if the default points past the last one.
+    pub fn default_result(&self) -> MessageBoxResult {
+        let results = self.buttons.results();
+        *results.get(self.default_button.position()).unwrap_or(&results[0])
+    }
+
+    pub fn show_with(&self, backend: &mut impl MessageBoxBackend) -> Result<MessageBoxResult, Win32Error> {
+        backend.show(self)
+    }
+
+    /// Shows the box with [`SystemMessageBoxes`].
+    #[cfg(windows)]
+    pub fn show(&self) -> Result<MessageBoxResult, Win32Error> {
+        self.show_with(&mut SystemMessageBoxes)
+    }
+}
+
+/// Something able to show a [`MessageBox`] and report which button was pressed.
+pub trait MessageBoxBackend {
+    fn show(&mut self, message_box: &MessageBox) -> Result<MessageBoxResult, Win32Error>;
+}
+
+/// Shows real message boxes with [`MessageBoxW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-messageboxw)
+#[cfg(windows)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemMessageBoxes;
+
+#[cfg(windows)]
+impl MessageBoxBackend for SystemMessageBoxes {
+    fn show(&mut self, message_box: &MessageBox) -> Result<MessageBoxResult, Win32Error> {
+        use crate::win32::{get_last_error, wide_null, MessageBoxW};
+
+        let text = wide_null(&message_box.text);
+        let title = wide_null(&message_box.title);
+        let id = unsafe { MessageBoxW(message_box.owner, text.as_ptr(), title.as_ptr(), message_box.style()) };
+        MessageBoxResult::from_id(id).ok_or_else(get_last_error)
+    }
+}
+
+/// Answers message boxes from a pre-recorded script, for testing code that asks the user.
+///
+/// Every box shown is kept so tests can check what was asked. Once the script runs out each box
+/// is answered with its default button, as if the user pressed `Enter`.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptedMessageBoxes {
+    responses: VecDeque<MessageBoxResult>,
+    shown: Vec<MessageBox>,
+}
+
+impl ScriptedMessageBoxes {
+    pub fn new(responses: impl IntoIterator<Item = MessageBoxResult>) -> Self {
+        Self {
+            responses: responses.into_iter().collect(),
+            shown: Vec::new(),
+        }
+    }
+
+    /// Queues another answer after the ones already scripted.
+    pub fn push_response(&mut self, response: MessageBoxResult) {
+        self.responses.push_back(response);
+    }
+
+    /// Every message box shown so far, oldest first.
+    pub fn shown(&self) -> &[MessageBox] {
+        &self.shown
+    }
+
+    /// The number of scripted answers not yet used.
+    pub fn remaining(&self) -> usize {
+        self.responses.len()
+    }
+}
+
+impl MessageBoxBackend for ScriptedMessageBoxes {
+    /// # Panics
+    /// If the scripted answer is not one of the buttons on the box, since that is a bug in the test.
+    fn show(&mut self, message_box: &MessageBox) -> Result<MessageBoxResult, Win32Error> {
+        let response = match self.responses.pop_front() {
+            Some(response) => response,
+            None => message_box.default_result(),
+        };
+        assert!(
+            message_box.buttons.results().contains(&response),
+            "scripted answer {:?} is not a button of {:?} ({:?})",
+            response,
+            message_box.title,
+            message_box.buttons,
+        );
+        self.shown.push(message_box.clone());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_combines_every_flag() {
+        let message_box = MessageBox::new("t", "x")
+            .buttons(Buttons::YesNoCancel)
+            .icon(Icon::Warning)
+            .default_button(DefaultButton::Third)
+            .modality(Modality::Task);
+        assert_eq!(message_box.style(), MB_YESNOCANCEL | MB_ICONWARNING | MB_DEFBUTTON3 | MB_TASKMODAL);
+        assert_eq!(MessageBox::new("t", "x").style(), MB_OK);
+    }
+
+    #[test]
+    fn result_ids_round_trip() {
+        for buttons in [Buttons::Ok, Buttons::OkCancel, Buttons::YesNoCancel, Buttons::AbortRetryIgnore] {
+            for &result in buttons.results() {
+                assert_eq!(MessageBoxResult::from_id(result as i32), Some(result));
+            }
+        }
+        assert_eq!(MessageBoxResult::from_id(0), None);
+    }
+
+    #[test]
+    fn scripted_answers_in_order_then_falls_back_to_default() {
+        let mut backend = ScriptedMessageBoxes::new([MessageBoxResult::No]);
+        let confirm = MessageBox::new("Quit?", "Really?")
+            .buttons(Buttons::YesNo)
+            .default_button(DefaultButton::Second);
+        assert_eq!(confirm.show_with(&mut backend), Ok(MessageBoxResult::No));
+        assert_eq!(backend.remaining(), 0);
+        assert_eq!(confirm.show_with(&mut backend), Ok(MessageBoxResult::No));
+        let retry = MessageBox::new("Oops", "Try again?").buttons(Buttons::RetryCancel);
+        assert_eq!(retry.show_with(&mut backend), Ok(MessageBoxResult::Retry));
+        assert_eq!(backend.shown().len(), 3);
+        assert_eq!(backend.shown()[2].title, "Oops");
+    }
+
+    #[test]
+    fn default_past_the_last_button_falls_back_to_the_first() {
+        let message_box = MessageBox::new("t", "x")
+            .buttons(Buttons::OkCancel)
+            .default_button(DefaultButton::Third);
+        assert_eq!(message_box.default_result(), MessageBoxResult::Ok);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a button")]
+    fn scripted_answer_must_match_the_buttons() {
+        let mut backend = ScriptedMessageBoxes::new([MessageBoxResult::Yes]);
+        let _ = MessageBox::new("t", "x").buttons(Buttons::OkCancel).show_with(&mut backend);
+    }
+}
@@ -0,0 +1,214 @@
+//! The monitors of the desktop and where to put windows on them.
+//!
+//! All rectangles are in virtual screen coordinates, where the primary monitor starts at 0, 0 and
+//! the others may well be at negative coordinates. The work area is the part not covered by the
+//! taskbar and docked toolbars, which is where new windows belong.
+//!
+//! Monitors come and go (laptops get undocked), so a position saved in a previous run may now be
+//! off screen. [`restore`] puts it back where it was if that is still visible.
+
+use crate::geometry::{Point, Rect, Size};
+use crate::icon::BASE_DPI;
+
+/// One display, see [`MONITORINFO`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-monitorinfo)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monitor {
+    pub bounds: Rect,
+    pub work_area: Rect,
+    pub dpi: u32,
+    /// The one with the taskbar's start button, at the origin.
+    pub primary: bool,
+}
+
+impl Monitor {
+    /// A monitor with its work area, e.g. for tests.
+    pub const fn new(bounds: Rect, work_area: Rect, dpi: u32, primary: bool) -> Self {
+        Self { bounds, work_area, dpi, primary }
+    }
+
+    /// The DPI relative to 96, e.g. 1.5 at 144 DPI.
+    pub fn scale(&self) -> f32 {
+        self.dpi as f32 / BASE_DPI as f32
+    }
+}
+
+/// The primary monitor, or the first one if none says it is.
+pub fn primary(monitors: &[Monitor]) -> Option<&Monitor> {
+    monitors.iter().find(|monitor| monitor.primary).or_else(|| monitors.first())
+}
+
+fn area(rect: Rect) -> i64 {
+    rect.width() as i64 * rect.height() as i64
+}
+
+fn distance_squared(rect: Rect, point: Point) -> i64 {
+    let dx = (rect.left - point.x).max(point.x - rect.right + 1).max(0) as i64;
+    let dy = (rect.top - point.y).max(point.y - rect.bottom + 1).max(0) as i64;
+    dx * dx + dy * dy
+}
+
+/// The index of the area showing most of `window`, like `MonitorFromWindow` with
+/// `MONITOR_DEFAULTTONEAREST`: if it is in none of them, the one closest to its center.
+///
+/// Ties go to the earlier area. `None` only if there are no areas.
+pub fn monitor_for(window: Rect, areas: &[Rect]) -> Option<usize> {
+    let overlaps = areas.iter().map(|rect| rect.intersect(&window).map_or(0, area));
+    let (best, overlap) = overlaps.enumerate().fold(None, |best: Option<(usize, i64)>, (index, overlap)| {
+        match best {
+            Some((_, most)) if most >= overlap => best,
+            _ => Some((index, overlap)),
+        }
+    })?;
+    if overlap > 0 {
+        return Some(best);
+    }
+    let center = Point::new(window.left + window.width() / 2, window.top + window.height() / 2);
+    (0..areas.len()).min_by_key(|&index| distance_squared(areas[index], center))
+}
+
+/// Moves `window` as little as possible to be inside `area`, shrinking it first if it is bigger.
+pub fn clamp(window: Rect, area: Rect) -> Rect {
+    let size = Size::new(window.width().min(area.width()), window.height().min(area.height()));
+    let left = window.left.clamp(area.left, area.right - size.width);
+    let top = window.top.clamp(area.top, area.bottom - size.height);
+    Rect::from_origin_size(Point::new(left, top), size)
+}
+
+/// A window of `size` in the middle of `area`, shrunk if it doesn't fit.
+pub fn center(size: Size, area: Rect) -> Rect {
+    let origin = Point::new(
+        area.left + (area.width() - size.width) / 2,
+        area.top + (area.height() - size.height) / 2,
+    );
+    clamp(Rect::from_origin_size(origin, size), area)
+}
+
+/// Where a window saved at `saved` goes now: the same place if it is still partly in a work
+/// area (moved fully into the one showing most of it), otherwise centered on the primary monitor.
+///
+/// `None` if there are no monitors at all.
+pub fn restore(saved: Rect, monitors: &[Monitor]) -> Option<Rect> {
+    let work_areas: Vec<Rect> = monitors.iter().map(|monitor| monitor.work_area).collect();
+    let index = monitor_for(saved, &work_areas)?;
+    if work_areas[index].intersect(&saved).is_some() {
+        Some(clamp(saved, work_areas[index]))
+    } else {
+        Some(center(saved.size(), primary(monitors)?.work_area))
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use core::ptr::{null, null_mut};
+
+    use super::Monitor;
+    use crate::geometry::Point;
+    use crate::icon::BASE_DPI;
+    use crate::win32::*;
+
+    unsafe extern "system" fn collect_monitor(hmonitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data as *mut Vec<HMONITOR>);
+        monitors.push(hmonitor);
+        1
+    }
+
+    /// Every monitor of the desktop, see [`EnumDisplayMonitors`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-enumdisplaymonitors)
+    pub fn monitors() -> Result<Vec<Monitor>, Win32Error> {
+        let mut handles: Vec<HMONITOR> = Vec::new();
+        let listed = unsafe {
+            EnumDisplayMonitors(null_mut(), null(), Some(collect_monitor), &mut handles as *mut _ as LPARAM)
+        };
+        if listed == 0 {
+            return Err(get_last_error());
+        }
+        handles.into_iter().map(|handle| unsafe { Monitor::from_handle(handle) }).collect()
+    }
+
+    impl Monitor {
+        /// See [`GetMonitorInfoW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmonitorinfow)
+        ///
+        /// # Safety
+        /// `hmonitor` must be a monitor handle, they are only valid while the monitor is attached.
+        pub unsafe fn from_handle(hmonitor: HMONITOR) -> Result<Self, Win32Error> {
+            let mut info = MONITORINFO::default();
+            if GetMonitorInfoW(hmonitor, &mut info) == 0 {
+                return Err(get_last_error());
+            }
+            let (mut dpi, mut dpi_y) = (0, 0);
+            // Only fails without per monitor DPI awareness, where everything is 96 DPI anyway.
+            if GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi, &mut dpi_y) != 0 {
+                dpi = BASE_DPI;
+            }
+            Ok(Self {
+                bounds: info.rc_monitor.into(),
+                work_area: info.rc_work.into(),
+                dpi,
+                primary: info.dw_flags & MONITORINFOF_PRIMARY != 0,
+            })
+        }
+
+        /// The monitor showing most of `hwnd`, or the nearest one, see
+        /// [`MonitorFromWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-monitorfromwindow)
+        ///
+        /// # Safety
+        /// `hwnd` must be a window handle, [`Window::monitor`](crate::window::Window::monitor) is the
+        /// safe way.
+        pub unsafe fn from_window(hwnd: HWND) -> Result<Self, Win32Error> {
+            Self::from_handle(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST))
+        }
+
+        /// The primary monitor, see [`MonitorFromPoint`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-monitorfrompoint)
+        pub fn primary() -> Result<Self, Win32Error> {
+            unsafe { Self::from_handle(MonitorFromPoint(Point::new(0, 0).into(), MONITOR_DEFAULTTOPRIMARY)) }
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: Monitor = Monitor::new(Rect::new(-1280, 0, 0, 1024), Rect::new(-1280, 0, 0, 984), 96, false);
+    const MAIN: Monitor = Monitor::new(Rect::new(0, 0, 1920, 1080), Rect::new(0, 0, 1920, 1040), 144, true);
+
+    #[test]
+    fn picks_the_area_with_most_of_the_window() {
+        let areas = [Rect::new(0, 0, 1920, 1080), Rect::new(1920, 0, 4480, 1440), Rect::new(-1280, 0, 0, 1024)];
+        assert_eq!(monitor_for(Rect::new(100, 100, 900, 700), &areas), Some(0));
+        assert_eq!(monitor_for(Rect::new(1800, 100, 2600, 700), &areas), Some(1));
+        assert_eq!(monitor_for(Rect::new(-700, 100, 100, 700), &areas), Some(2));
+        // Exactly half on each, the first one listed wins.
+        assert_eq!(monitor_for(Rect::new(1520, 0, 2320, 600), &areas), Some(0));
+        // Off screen, the nearest wins.
+        assert_eq!(monitor_for(Rect::new(3000, 2000, 3400, 2300), &areas), Some(1));
+        assert_eq!(monitor_for(Rect::new(0, 0, 10, 10), &[]), None);
+    }
+
+    #[test]
+    fn centers_and_clamps() {
+        assert_eq!(center(Size::new(800, 600), MAIN.work_area), Rect::new(560, 220, 1360, 820));
+        assert_eq!(center(Size::new(3000, 600), LEFT.work_area), Rect::new(-1280, 192, 0, 792));
+        // Hanging off the bottom right is pulled back in.
+        assert_eq!(clamp(Rect::new(1500, 900, 2000, 1200), MAIN.work_area), Rect::new(1420, 740, 1920, 1040));
+        assert_eq!(clamp(Rect::new(-50, -50, 2000, 100), MAIN.work_area), Rect::new(0, 0, 1920, 150));
+        let inside = Rect::new(10, 10, 20, 20);
+        assert_eq!(clamp(inside, MAIN.work_area), inside);
+        assert_eq!(MAIN.scale(), 1.5);
+    }
+
+    #[test]
+    fn restores_onto_a_present_monitor() {
+        let monitors = [LEFT, MAIN];
+        let saved = Rect::new(-1000, 100, -200, 700);
+        assert_eq!(restore(saved, &monitors), Some(saved));
+        // Partly below the taskbar.
+        assert_eq!(restore(Rect::new(100, 800, 500, 1100), &monitors), Some(Rect::new(100, 740, 500, 1040)));
+        // The left monitor is gone, so it goes to the middle of the primary one.
+        assert_eq!(restore(saved, &[MAIN]), Some(Rect::new(560, 220, 1360, 820)));
+        assert_eq!(restore(saved, &[]), None);
+        assert_eq!(primary(&monitors), Some(&MAIN));
+    }
+}
@@ -0,0 +1,319 @@
+/// See [`GetSysColor`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsyscolor)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SysColor {
+    _3dDarkShadow = 21,
+    _3dLight = 22,
+    ActiveBorder = 10,
+    ActiveCaption = 2,
+    AppWorkspace = 12,
+    /// Button face, also "3D face" color.
+    ButtonFace = 15,
+    /// Button highlight, also "3D highlight" color.
+    ButtonHighlight = 20,
+    /// Button shadow, also "3D shadow" color.
+    ButtonShadow = 16,
+    ButtonText = 18,
+    CaptionText = 9,
+    /// Desktop background color
+    Desktop = 1,
+    GradientActiveCaption = 27,
+    GradientInactiveCaption = 28,
+    GrayText = 17,
+    Highlight = 13,
+    HighlightText = 14,
+    HotLight = 26,
+    InactiveBorder = 11,
+    InactiveCaption = 3,
+    InactiveCaptionText = 19,
+    InfoBackground = 24,
+    InfoText = 23,
+    Menu = 4,
+    MenuHighlight = 29,
+    MenuBar = 30,
+    MenuText = 7,
+    ScrollBar = 0,
+    Window = 5,
+    WindowFrame = 6,
+    WindowText = 8,
+}
+
+impl SysColor {
+    /// Every system color, in index order.
+    pub const ALL: [SysColor; 30] = [
+        SysColor::ScrollBar,
+        SysColor::Desktop,
+        SysColor::ActiveCaption,
+        SysColor::InactiveCaption,
+        SysColor::Menu,
+        SysColor::Window,
+        SysColor::WindowFrame,
+        SysColor::MenuText,
+        SysColor::WindowText,
+        SysColor::CaptionText,
+        SysColor::ActiveBorder,
+        SysColor::InactiveBorder,
+        SysColor::AppWorkspace,
+        SysColor::Highlight,
+        SysColor::HighlightText,
+        SysColor::ButtonFace,
+        SysColor::ButtonShadow,
+        SysColor::GrayText,
+        SysColor::ButtonText,
+        SysColor::InactiveCaptionText,
+        SysColor::ButtonHighlight,
+        SysColor::_3dDarkShadow,
+        SysColor::_3dLight,
+        SysColor::InfoText,
+        SysColor::InfoBackground,
+        SysColor::HotLight,
+        SysColor::GradientActiveCaption,
+        SysColor::GradientInactiveCaption,
+        SysColor::MenuHighlight,
+        SysColor::MenuBar,
+    ];
+
+    /// The `COLOR_*` index passed to `GetSysColor` and friends.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Looks up a color by its variant name, ignoring case and the leading underscore of the
+    /// `3d` colors.
+    pub fn from_name(name: &str) -> Option<SysColor> {
+        SysColor::ALL
+            .into_iter()
+            .find(|color| format!("{:?}", color).trim_start_matches('_').eq_ignore_ascii_case(name))
+    }
+}
+
+/// An 8 bit per channel RGB color, as stored in a `COLORREF`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Unpacks a [`COLORREF`](https://docs.microsoft.com/en-us/windows/win32/gdi/colorref), laid out as `0x00BBGGRR`.
+    pub const fn from_colorref(colorref: u32) -> Self {
+        Self {
+            r: colorref as u8,
+            g: (colorref >> 8) as u8,
+            b: (colorref >> 16) as u8,
+        }
+    }
+
+    /// Packs the color the way the `RGB` macro does.
+    pub const fn to_colorref(self) -> u32 {
+        self.r as u32 | (self.g as u32) << 8 | (self.b as u32) << 16
+    }
+}
+
+// One slot per `COLOR_*` index, index 25 is unused by Windows.
+const PALETTE_LEN: usize = 31;
+
+/// A snapshot of every [`SysColor`] as RGB.
+///
+/// On Windows [`Palette::current`] reads the live values, the built-in palettes give headless
+/// renderers and tests something deterministic to draw with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: [Rgb; PALETTE_LEN],
+}
+
+impl Palette {
+    /// Builds a palette by asking `f` for every system color.
+    pub fn from_fn(mut f: impl FnMut(SysColor) -> Rgb) -> Self {
+        let mut colors = [Rgb::default(); PALETTE_LEN];
+        for color in SysColor::ALL {
+            colors[color.index()] = f(color);
+        }
+        Self { colors }
+    }
+
+    pub fn get(&self, color: SysColor) -> Rgb {
+        self.colors[color.index()]
+    }
+
+    pub fn set(&mut self, color: SysColor, rgb: Rgb) {
+        self.colors[color.index()] = rgb;
+    }
+
+    /// Iterates every system color along with its value in this palette.
+    pub fn iter(&self) -> impl Iterator<Item = (SysColor, Rgb)> + '_ {
+        SysColor::ALL.into_iter().map(move |color| (color, self.get(color)))
+    }
+
+    /// The "Windows Classic" theme colors.
+    pub fn classic() -> Self {
+        Self::from_fn(|color| match color {
+            SysColor::ScrollBar => Rgb::new(212, 208, 200),
+            SysColor::Desktop => Rgb::new(58, 110, 165),
+            SysColor::ActiveCaption => Rgb::new(10, 36, 106),
+            SysColor::InactiveCaption => Rgb::new(128, 128, 128),
+            SysColor::Menu => Rgb::new(212, 208, 200),
+            SysColor::Window => Rgb::new(255, 255, 255),
+            SysColor::WindowFrame => Rgb::new(0, 0, 0),
+            SysColor::MenuText => Rgb::new(0, 0, 0),
+            SysColor::WindowText => Rgb::new(0, 0, 0),
+            SysColor::CaptionText => Rgb::new(255, 255, 255),
+            SysColor::ActiveBorder => Rgb::new(212, 208, 200),
+            SysColor::InactiveBorder => Rgb::new(212, 208, 200),
+            SysColor::AppWorkspace => Rgb::new(128, 128, 128),
+            SysColor::Highlight => Rgb::new(10, 36, 106),
+            SysColor::HighlightText => Rgb::new(255, 255, 255),
+            SysColor::ButtonFace => Rgb::new(212, 208, 200),
+            SysColor::ButtonShadow => Rgb::new(128, 128, 128),
+            SysColor::GrayText => Rgb::new(128, 128, 128),
+            SysColor::ButtonText => Rgb::new(0, 0, 0),
+            SysColor::InactiveCaptionText => Rgb::new(212, 208, 200),
+            SysColor::ButtonHighlight => Rgb::new(255, 255, 255),
+            SysColor::_3dDarkShadow => Rgb::new(64, 64, 64),
+            SysColor::_3dLight => Rgb::new(212, 208, 200),
+            SysColor::InfoText => Rgb::new(0, 0, 0),
+            SysColor::InfoBackground => Rgb::new(255, 255, 225),
+            SysColor::HotLight => Rgb::new(0, 0, 128),
+            SysColor::GradientActiveCaption => Rgb::new(166, 202, 240),
+            SysColor::GradientInactiveCaption => Rgb::new(192, 192, 192),
+            SysColor::MenuHighlight => Rgb::new(10, 36, 106),
+            SysColor::MenuBar => Rgb::new(212, 208, 200),
+        })
+    }
+
+    /// The default colors of a current Windows install with the light theme.
+    pub fn light() -> Self {
+        Self::from_fn(|color| match color {
+            SysColor::ScrollBar => Rgb::new(200, 200, 200),
+            SysColor::Desktop => Rgb::new(0, 0, 0),
+            SysColor::ActiveCaption => Rgb::new(153, 180, 209),
+            SysColor::InactiveCaption => Rgb::new(191, 205, 219),
+            SysColor::Menu => Rgb::new(240, 240, 240),
+            SysColor::Window => Rgb::new(255, 255, 255),
+            SysColor::WindowFrame => Rgb::new(100, 100, 100),
+            SysColor::MenuText => Rgb::new(0, 0, 0),
+            SysColor::WindowText => Rgb::new(0, 0, 0),
+            SysColor::CaptionText => Rgb::new(0, 0, 0),
+            SysColor::ActiveBorder => Rgb::new(180, 180, 180),
+            SysColor::InactiveBorder => Rgb::new(244, 247, 252),
+            SysColor::AppWorkspace => Rgb::new(171, 171, 171),
+            SysColor::Highlight => Rgb::new(0, 120, 215),
+            SysColor::HighlightText => Rgb::new(255, 255, 255),
+            SysColor::ButtonFace => Rgb::new(240, 240, 240),
+            SysColor::ButtonShadow => Rgb::new(160, 160, 160),
+            SysColor::GrayText => Rgb::new(109, 109, 109),
+            SysColor::ButtonText => Rgb::new(0, 0, 0),
+            SysColor::InactiveCaptionText => Rgb::new(0, 0, 0),
+            SysColor::ButtonHighlight => Rgb::new(255, 255, 255),
+            SysColor::_3dDarkShadow => Rgb::new(105, 105, 105),
+            SysColor::_3dLight => Rgb::new(227, 227, 227),
+            SysColor::InfoText => Rgb::new(0, 0, 0),
+            SysColor::InfoBackground => Rgb::new(255, 255, 225),
+            SysColor::HotLight => Rgb::new(0, 102, 204),
+            SysColor::GradientActiveCaption => Rgb::new(185, 209, 234),
+            SysColor::GradientInactiveCaption => Rgb::new(215, 228, 242),
+            SysColor::MenuHighlight => Rgb::new(0, 120, 215),
+            SysColor::MenuBar => Rgb::new(240, 240, 240),
+        })
+    }
+
+    /// A dark counterpart to [`Palette::light`]. Windows has no dark system colors of its own, so
+    /// these are picked to match the dark app mode.
+    pub fn dark() -> Self {
+        Self::from_fn(|color| match color {
+            SysColor::ScrollBar => Rgb::new(77, 77, 77),
+            SysColor::Desktop => Rgb::new(0, 0, 0),
+            SysColor::ActiveCaption => Rgb::new(32, 32, 32),
+            SysColor::InactiveCaption => Rgb::new(43, 43, 43),
+            SysColor::Menu => Rgb::new(43, 43, 43),
+            SysColor::Window => Rgb::new(32, 32, 32),
+            SysColor::WindowFrame => Rgb::new(60, 60, 60),
+            SysColor::MenuText => Rgb::new(255, 255, 255),
+            SysColor::WindowText => Rgb::new(255, 255, 255),
+            SysColor::CaptionText => Rgb::new(255, 255, 255),
+            SysColor::ActiveBorder => Rgb::new(60, 60, 60),
+            SysColor::InactiveBorder => Rgb::new(43, 43, 43),
+            SysColor::AppWorkspace => Rgb::new(25, 25, 25),
+            SysColor::Highlight => Rgb::new(0, 120, 215),
+            SysColor::HighlightText => Rgb::new(255, 255, 255),
+            SysColor::ButtonFace => Rgb::new(51, 51, 51),
+            SysColor::ButtonShadow => Rgb::new(30, 30, 30),
+            SysColor::GrayText => Rgb::new(155, 155, 155),
+            SysColor::ButtonText => Rgb::new(255, 255, 255),
+            SysColor::InactiveCaptionText => Rgb::new(170, 170, 170),
+            SysColor::ButtonHighlight => Rgb::new(85, 85, 85),
+            SysColor::_3dDarkShadow => Rgb::new(15, 15, 15),
+            SysColor::_3dLight => Rgb::new(70, 70, 70),
+            SysColor::InfoText => Rgb::new(255, 255, 255),
+            SysColor::InfoBackground => Rgb::new(43, 43, 43),
+            SysColor::HotLight => Rgb::new(96, 205, 255),
+            SysColor::GradientActiveCaption => Rgb::new(32, 32, 32),
+            SysColor::GradientInactiveCaption => Rgb::new(43, 43, 43),
+            SysColor::MenuHighlight => Rgb::new(65, 65, 65),
+            SysColor::MenuBar => Rgb::new(43, 43, 43),
+        })
+    }
+
+    /// Reads every system color from the running Windows session.
+    #[cfg(windows)]
+    pub fn current() -> Self {
+        Self::from_fn(crate::win32::get_sys_color)
+    }
+}
+
+impl Default for Palette {
+    /// The light palette, which is what a fresh Windows install shows.
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_lists_every_color_once_in_index_order() {
+        for pair in SysColor::ALL.windows(2) {
+            assert!(pair[0].index() < pair[1].index());
+        }
+        assert!(SysColor::ALL.iter().all(|color| color.index() < PALETTE_LEN));
+    }
+
+    #[test]
+    fn colors_are_found_by_name() {
+        assert_eq!(SysColor::from_name("window"), Some(SysColor::Window));
+        assert_eq!(SysColor::from_name("3dLight"), Some(SysColor::_3dLight));
+        assert_eq!(SysColor::from_name("Windows"), None);
+    }
+
+    #[test]
+    fn colorref_round_trips() {
+        let rgb = Rgb::new(0x12, 0x34, 0x56);
+        assert_eq!(rgb.to_colorref(), 0x0056_3412);
+        assert_eq!(Rgb::from_colorref(0xFF56_3412), rgb);
+    }
+
+    #[test]
+    fn built_in_palettes_differ_where_it_matters() {
+        let classic = Palette::classic();
+        let light = Palette::light();
+        let dark = Palette::dark();
+        assert_eq!(light.get(SysColor::Window), Rgb::new(255, 255, 255));
+        assert_ne!(light.get(SysColor::Window), dark.get(SysColor::Window));
+        assert_ne!(classic.get(SysColor::ButtonFace), light.get(SysColor::ButtonFace));
+        assert_eq!(light.iter().count(), SysColor::ALL.len());
+    }
+
+    #[test]
+    fn set_overrides_a_single_entry() {
+        let mut palette = Palette::classic();
+        palette.set(SysColor::Window, Rgb::new(1, 2, 3));
+        assert_eq!(palette.get(SysColor::Window), Rgb::new(1, 2, 3));
+        assert_eq!(palette.get(SysColor::WindowText), Palette::classic().get(SysColor::WindowText));
+    }
+}
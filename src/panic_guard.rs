@@ -0,0 +1,114 @@
+//! Keeping panics from unwinding through Windows.
+//!
+//! A window procedure is called by `DispatchMessageW` (or `SendMessageW`, `CreateWindowExW` and
+//! many others), so a panic in it would unwind through system code, which is undefined behavior
+//! and aborts the process with current Rust. Instead the procedure catches the panic with
+//! [`catch_panic`], which stashes the payload and returns a harmless result to Windows. Once the
+//! Windows call returns into Rust code, [`resume_panic`] picks the panic up again, so it ends
+//! the program (or reaches a `catch_unwind`) as if nothing was in between.
+//!
+//! The stash is per thread, like window procedures. Only the first panic is kept: anything
+//! panicking while one is pending is usually a consequence of the first.
+
+use core::any::Any;
+use core::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::message::{WM_CREATE, WM_NCCREATE};
+
+type Payload = Box<dyn Any + Send>;
+
+thread_local! {
+    static STASHED: RefCell<Option<Payload>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, or if it panics stashes the panic and returns `fallback`.
+pub fn catch_panic<R>(fallback: R, f: impl FnOnce() -> R) -> R {
+    // Whatever `f` touched is only used again after the panic was resumed, or not at all.
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            STASHED.with(|stashed| {
+                stashed.borrow_mut().get_or_insert(payload);
+            });
+            fallback
+        }
+    }
+}
+
+/// Whether a panic waits to be resumed. Window procedures leave the handlers alone meanwhile.
+pub fn panic_pending() -> bool {
+    STASHED.with(|stashed| stashed.borrow().is_some())
+}
+
+/// Takes the stashed panic, for callers that want to report it rather than resume it.
+pub fn take_panic() -> Option<Payload> {
+    STASHED.with(|stashed| stashed.borrow_mut().take())
+}
+
+/// Continues the stashed panic, if there is one. Call this right after every Windows function
+/// that may have called a window procedure.
+pub fn resume_panic() {
+    if let Some(payload) = take_panic() {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// What a window procedure returns for `msg` after its handler panicked.
+///
+/// Creation is refused, since the window would be missing whatever the handler was setting up.
+/// Everything else gets 0, which means "handled" for most messages.
+pub const fn panic_result(msg: u32) -> isize {
+    match msg {
+        // `FALSE` makes `CreateWindowExW` fail.
+        WM_NCCREATE => 0,
+        // -1 destroys the window and makes `CreateWindowExW` fail.
+        WM_CREATE => -1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(payload: &Payload) -> &str {
+        payload.downcast_ref::<&str>().copied().unwrap_or_default()
+    }
+
+    #[test]
+    fn stashes_and_resumes() {
+        assert_eq!(catch_panic(0, || 5), 5);
+        assert!(!panic_pending());
+        assert_eq!(catch_panic(-1, || panic!("first")), -1);
+        assert!(panic_pending());
+        // Later panics are consequences, the first one is what gets resumed.
+        catch_panic((), || panic!("second"));
+        let resumed = panic::catch_unwind(resume_panic).unwrap_err();
+        assert_eq!(message(&resumed), "first");
+        assert!(!panic_pending());
+        // Nothing to resume.
+        resume_panic();
+    }
+
+    #[test]
+    fn survives_an_extern_call() {
+        // Stands in for a window procedure called by `DispatchMessageW`.
+        extern "system" fn procedure(msg: u32) -> isize {
+            catch_panic(panic_result(msg), || panic!("no window data for {:#x}", msg))
+        }
+        let dispatch: extern "system" fn(u32) -> isize = procedure;
+        assert_eq!(dispatch(WM_CREATE), -1);
+        assert!(take_panic().is_some());
+        assert!(!panic_pending());
+        assert_eq!(dispatch(WM_NCCREATE), 0);
+        assert!(panic::catch_unwind(resume_panic).is_err());
+    }
+
+    #[test]
+    fn other_threads_have_their_own_stash() {
+        catch_panic((), || panic!("here"));
+        std::thread::spawn(|| assert!(!panic_pending())).join().unwrap();
+        assert_eq!(message(&take_panic().unwrap()), "here");
+    }
+}
@@ -0,0 +1,425 @@
+//! A software rasterizer for 3D triangles.
+//!
+//! Vertices go through one 4x4 matrix into clip space, get clipped against the view volume there
+//! (so triangles reaching behind the camera still come out right), then are divided by W and
+//! mapped onto the image. Colors are interpolated perspective correct, the depth buffer keeps the
+//! window space Z from 0 (near) to 1 (far). Edges follow the top-left rule, so triangles sharing an
+//! edge never both draw a pixel on it.
+//!
+//! The output is an ordinary [`Image`], which makes it easy to compare in snapshot tests.
+
+use crate::image::{Image, Rgba};
+use crate::math3d::{Mat4, Vec3, Vec4};
+
+/// A corner of a triangle in model space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex3 {
+    pub position: Vec3,
+    pub color: Rgba,
+}
+
+impl Vertex3 {
+    pub const fn new(position: Vec3, color: Rgba) -> Self {
+        Self { position, color }
+    }
+}
+
+/// When an incoming fragment replaces what is already there, like `glDepthFunc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepthCompare {
+    Never,
+    #[default]
+    Less,
+    LessEqual,
+    Equal,
+    Greater,
+    GreaterEqual,
+    NotEqual,
+    Always,
+}
+
+impl DepthCompare {
+    pub fn passes(self, incoming: f32, stored: f32) -> bool {
+        match self {
+            DepthCompare::Never => false,
+            DepthCompare::Less => incoming < stored,
+            DepthCompare::LessEqual => incoming <= stored,
+            DepthCompare::Equal => incoming == stored,
+            DepthCompare::Greater => incoming > stored,
+            DepthCompare::GreaterEqual => incoming >= stored,
+            DepthCompare::NotEqual => incoming != stored,
+            DepthCompare::Always => true,
+        }
+    }
+}
+
+/// Which side of triangles to skip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cull {
+    None,
+    #[default]
+    Back,
+    Front,
+}
+
+/// Which winding, as seen on screen, faces the viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrontFace {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Everything that affects how triangles are drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderState {
+    /// Model space to clip space, usually `projection * view * model`.
+    pub transform: Mat4,
+    pub depth_compare: DepthCompare,
+    /// Whether passing fragments update the depth buffer.
+    pub depth_write: bool,
+    pub cull: Cull,
+    pub front_face: FrontFace,
+}
+
+impl Default for RenderState {
+    /// No transform, `Less` depth test with writes, back faces culled.
+    fn default() -> Self {
+        Self {
+            transform: Mat4::IDENTITY,
+            depth_compare: DepthCompare::default(),
+            depth_write: true,
+            cull: Cull::default(),
+            front_face: FrontFace::default(),
+        }
+    }
+}
+
+/// One depth value per pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthBuffer {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl DepthBuffer {
+    /// Cleared to the far plane.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, values: vec![1.0; width as usize * height as usize] }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.values[y as usize * self.width as usize + x as usize])
+    }
+
+    pub fn clear(&mut self, value: f32) {
+        self.values.fill(value);
+    }
+}
+
+/// A vertex after the transform, colors are kept as floats until the pixel is written.
+#[derive(Debug, Clone, Copy)]
+struct ClipVertex {
+    position: Vec4,
+    color: Vec4,
+}
+
+impl ClipVertex {
+    fn lerp(self, other: ClipVertex, t: f32) -> ClipVertex {
+        ClipVertex { position: self.position.lerp(other.position, t), color: self.color.lerp(other.color, t) }
+    }
+}
+
+/// Keeps W away from 0 even for projections that do not clip at a near plane.
+const MIN_W: f32 = 1e-5;
+
+/// How far inside each plane of the view volume a clip space position is, negative is outside.
+fn plane_distances(p: Vec4) -> [f32; 7] {
+    [p.w + p.x, p.w - p.x, p.w + p.y, p.w - p.y, p.w + p.z, p.w - p.z, p.w - MIN_W]
+}
+
+/// Sutherland-Hodgman against every plane of [`plane_distances`] in turn.
+fn clip_polygon(mut polygon: Vec<ClipVertex>) -> Vec<ClipVertex> {
+    for plane in 0..7 {
+        if polygon.is_empty() {
+            break;
+        }
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, &a) in polygon.iter().enumerate() {
+            let b = polygon[(i + 1) % polygon.len()];
+            let (da, db) = (plane_distances(a.position)[plane], plane_distances(b.position)[plane]);
+            if da >= 0.0 {
+                clipped.push(a);
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                clipped.push(a.lerp(b, da / (da - db)));
+            }
+        }
+        polygon = clipped;
+    }
+    polygon
+}
+
+/// A vertex on the image: pixel position, depth from 0 to 1 and what perspective correct
+/// interpolation needs.
+#[derive(Debug, Clone, Copy)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    depth: f32,
+    inverse_w: f32,
+    color: Vec4,
+}
+
+/// The edge function, positive when `(px, py)` is right of `a` to `b` as seen on screen.
+fn edge(a: &ScreenVertex, b: &ScreenVertex, px: f32, py: f32) -> f32 {
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
+
+/// Whether pixels exactly on the edge from `a` to `b` belong to the triangle, for clockwise
+/// (on screen) triangles.
+fn is_top_left(a: &ScreenVertex, b: &ScreenVertex) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
+
+/// Draws triangles into an image with a depth buffer.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    image: Image,
+    depth: DepthBuffer,
+    pub state: RenderState,
+}
+
+impl Renderer {
+    /// A transparent image with a cleared depth buffer.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { image: Image::new(width, height), depth: DepthBuffer::new(width, height), state: RenderState::default() }
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub fn into_image(self) -> Image {
+        self.image
+    }
+
+    pub fn depth(&self) -> &DepthBuffer {
+        &self.depth
+    }
+
+    /// Fills the image with `color` and the depth buffer with the far plane.
+    pub fn clear(&mut self, color: Rgba) {
+        self.image.fill(color);
+        self.depth.clear(1.0);
+    }
+
+    /// Draws indexed triangles, returning how many pixels were written.
+    ///
+    /// # Panics
+    /// If an index is out of bounds of `vertices`.
+    pub fn draw(&mut self, vertices: &[Vertex3], triangles: &[[usize; 3]]) -> usize {
+        triangles.iter().map(|triangle| self.draw_triangle(triangle.map(|i| vertices[i]))).sum()
+    }
+
+    /// Draws one triangle, returning how many pixels were written.
+    pub fn draw_triangle(&mut self, vertices: [Vertex3; 3]) -> usize {
+        let polygon = vertices
+            .iter()
+            .map(|v| ClipVertex {
+                position: self.state.transform.transform_point(v.position),
+                color: Vec4::new(v.color.r as f32, v.color.g as f32, v.color.b as f32, v.color.a as f32),
+            })
+            .collect();
+        let polygon: Vec<ScreenVertex> = clip_polygon(polygon).into_iter().map(|v| self.to_screen(v)).collect();
+        let mut written = 0;
+        for i in 1..polygon.len().saturating_sub(1) {
+            written += self.rasterize([polygon[0], polygon[i], polygon[i + 1]]);
+        }
+        written
+    }
+
+    fn to_screen(&self, v: ClipVertex) -> ScreenVertex {
+        let inverse_w = 1.0 / v.position.w;
+        let ndc = v.position.project();
+        ScreenVertex {
+            x: (ndc.x + 1.0) * 0.5 * self.image.width() as f32,
+            y: (1.0 - ndc.y) * 0.5 * self.image.height() as f32,
+            depth: (ndc.z + 1.0) * 0.5,
+            inverse_w,
+            color: v.color,
+        }
+    }
+
+    fn culled(&self, area: f32) -> bool {
+        // Y points down on screen, so counterclockwise triangles have a negative area.
+        let counterclockwise = area < 0.0;
+        let front = counterclockwise == (self.state.front_face == FrontFace::CounterClockwise);
+        match self.state.cull {
+            Cull::None => false,
+            Cull::Back => !front,
+            Cull::Front => front,
+        }
+    }
+
+    fn rasterize(&mut self, [a, mut b, mut c]: [ScreenVertex; 3]) -> usize {
+        let mut area = edge(&a, &b, c.x, c.y);
+        if area == 0.0 || self.culled(area) {
+            return 0;
+        }
+        // Walk every triangle clockwise on screen so the top-left rule has one form.
+        if area < 0.0 {
+            core::mem::swap(&mut b, &mut c);
+            area = -area;
+        }
+        let (width, height) = (self.image.width() as f32, self.image.height() as f32);
+        let left = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+        let top = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+        let right = a.x.max(b.x).max(c.x).ceil().min(width) as u32;
+        let bottom = a.y.max(b.y).max(c.y).ceil().min(height) as u32;
+        let edges = [(&b, &c), (&c, &a), (&a, &b)];
+        let top_left = edges.map(|(from, to)| is_top_left(from, to));
+        let mut written = 0;
+        for y in top..bottom {
+            for x in left..right {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let weights = edges.map(|(from, to)| edge(from, to, px, py));
+                let inside = weights.iter().zip(top_left).all(|(&w, top_left)| w > 0.0 || (w == 0.0 && top_left));
+                if !inside {
+                    continue;
+                }
+                let [wa, wb, wc] = weights.map(|w| w / area);
+                let depth = wa * a.depth + wb * b.depth + wc * c.depth;
+                let index = y as usize * self.depth.width as usize + x as usize;
+                if !self.state.depth_compare.passes(depth, self.depth.values[index]) {
+                    continue;
+                }
+                if self.state.depth_write {
+                    self.depth.values[index] = depth;
+                }
+                // Attributes are linear in model space, so interpolate them divided by W.
+                let [qa, qb, qc] = [wa * a.inverse_w, wb * b.inverse_w, wc * c.inverse_w];
+                let color = (a.color * qa + b.color * qb + c.color * qc) * (1.0 / (qa + qb + qc));
+                let [r, g, bl, al] = color.to_array().map(|channel| channel.round().clamp(0.0, 255.0) as u8);
+                self.image.set(x, y, Rgba::new(r, g, bl, al));
+                written += 1;
+            }
+        }
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba = Rgba::opaque(255, 0, 0);
+    const BLUE: Rgba = Rgba::opaque(0, 0, 255);
+
+    /// A square covering the whole view at depth `z`, counterclockwise.
+    fn square(z: f32, color: Rgba) -> [[Vertex3; 3]; 2] {
+        let corner = |x, y| Vertex3::new(Vec3::new(x, y, z), color);
+        let [a, b, c, d] = [corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0)];
+        [[a, b, c], [a, c, d]]
+    }
+
+    fn draw_square(renderer: &mut Renderer, z: f32, color: Rgba) -> usize {
+        square(z, color).into_iter().map(|triangle| renderer.draw_triangle(triangle)).sum()
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        let mut renderer = Renderer::new(16, 16);
+        renderer.state.depth_compare = DepthCompare::Always;
+        assert_eq!(draw_square(&mut renderer, 0.0, RED), 16 * 16);
+        assert!(renderer.image().pixels().iter().all(|&p| p == RED));
+    }
+
+    #[test]
+    fn depth_compare_decides_who_wins() {
+        for (compare, first, expected) in [
+            (DepthCompare::Less, -0.5, RED),
+            (DepthCompare::Less, 0.5, BLUE),
+            (DepthCompare::Greater, 0.5, RED),
+            (DepthCompare::Always, 0.5, BLUE),
+        ] {
+            let mut renderer = Renderer::new(8, 8);
+            renderer.state.depth_compare = compare;
+            if compare == DepthCompare::Greater {
+                renderer.depth.clear(0.0);
+            }
+            // Red is at `first`, blue at the opposite depth.
+            draw_square(&mut renderer, first, RED);
+            draw_square(&mut renderer, -first, BLUE);
+            assert_eq!(renderer.image().get(4, 4), Some(expected), "{:?} with red at {}", compare, first);
+        }
+        let mut renderer = Renderer::new(8, 8);
+        renderer.state.depth_write = false;
+        draw_square(&mut renderer, 0.5, RED);
+        assert_eq!(renderer.depth().get(0, 0), Some(1.0));
+    }
+
+    #[test]
+    fn back_faces_are_culled() {
+        let [front, _] = square(0.0, RED);
+        let [a, b, c] = front;
+        let back = [a, c, b];
+        let mut renderer = Renderer::new(8, 8);
+        assert_eq!(renderer.draw_triangle(back), 0);
+        assert!(renderer.draw_triangle(front) > 0);
+        renderer.state.cull = Cull::Front;
+        assert_eq!(renderer.draw_triangle(front), 0);
+        renderer.state.front_face = FrontFace::Clockwise;
+        assert_eq!(renderer.draw_triangle(back), 0);
+        renderer.state.cull = Cull::None;
+        renderer.state.depth_compare = DepthCompare::Always;
+        assert!(renderer.draw_triangle(back) > 0);
+    }
+
+    #[test]
+    fn clipping_handles_triangles_behind_the_camera() {
+        let mut renderer = Renderer::new(32, 32);
+        renderer.state.transform = Mat4::perspective(core::f32::consts::FRAC_PI_2, 1.0, 0.1, 10.0);
+        renderer.state.cull = Cull::None;
+        // Entirely behind the camera.
+        let behind = [(-1.0, 0.0), (1.0, 0.0), (0.0, 1.0)].map(|(x, y)| Vertex3::new(Vec3::new(x, y, 1.0), RED));
+        assert_eq!(renderer.draw_triangle(behind), 0);
+        // A floor that starts behind the camera and runs off into the distance.
+        let floor = [(-1.0, 5.0), (1.0, 5.0), (0.0, -5.0)].map(|(x, z)| Vertex3::new(Vec3::new(x, -0.5, z), BLUE));
+        let written = renderer.draw_triangle(floor);
+        assert!(written > 0 && written < 32 * 32);
+        // Nothing lands above the horizon.
+        assert!((0..16).all(|y| renderer.image().row(y).iter().all(|&p| p != BLUE)));
+        assert_eq!(renderer.image().get(16, 31), Some(BLUE));
+    }
+
+    #[test]
+    fn colors_are_perspective_correct() {
+        let size = 200;
+        let projection = Mat4::perspective(core::f32::consts::FRAC_PI_2, 1.0, 0.1, 10.0);
+        let mut renderer = Renderer::new(size, size);
+        renderer.state.transform = projection;
+        renderer.state.cull = Cull::None;
+        let near = Rgba::opaque(0, 0, 0);
+        let far = Rgba::opaque(255, 0, 0);
+        let vertex = |x, z, color| Vertex3::new(Vec3::new(x, -0.5, z), color);
+        let corners = [vertex(-1.0, -1.0, near), vertex(1.0, -1.0, near), vertex(1.0, -3.0, far), vertex(-1.0, -3.0, far)];
+        renderer.draw(&corners, &[[0, 1, 2], [0, 2, 3]]);
+        // Halfway in depth is not halfway on screen, but should still be halfway in color.
+        let middle = projection.transform_point(Vec3::new(0.0, -0.5, -2.0)).project();
+        let x = ((middle.x + 1.0) * 0.5 * size as f32) as u32;
+        let y = ((1.0 - middle.y) * 0.5 * size as f32) as u32;
+        let red = renderer.image().get(x, y).unwrap().r;
+        assert!(red.abs_diff(128) <= 6, "red is {}", red);
+    }
+}
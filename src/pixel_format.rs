@@ -0,0 +1,269 @@
+//! Choosing an OpenGL pixel format and describing the context we want.
+//!
+//! `ChoosePixelFormat` and `wglChoosePixelFormatARB` both pick a format by rules the driver makes
+//! up. Instead we list every format the device context offers and rank them ourselves, which
+//! keeps the choice the same across drivers and lets the rules be tested without a GPU. The
+//! ranking follows the one GLFW uses: hard requirements first, then the fewest missing buffers,
+//! then the closest color depth, then the least waste.
+
+/// What a pixel format provides, read from `DescribePixelFormat` or
+/// `wglGetPixelFormatAttribivARB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PixelFormatInfo {
+    /// The 1-based index to pass to `SetPixelFormat`.
+    pub index: i32,
+    pub draw_to_window: bool,
+    pub support_opengl: bool,
+    /// Hardware accelerated, rather than Microsoft's generic GDI implementation.
+    pub accelerated: bool,
+    pub double_buffer: bool,
+    /// Red, green and blue bits together.
+    pub color_bits: u8,
+    pub alpha_bits: u8,
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    /// Multisample anti-aliasing samples, 0 without MSAA.
+    pub samples: u8,
+    pub srgb: bool,
+}
+
+/// What we would like the pixel format to have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormatRequest {
+    pub color_bits: u8,
+    pub alpha_bits: u8,
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    pub samples: u8,
+    pub double_buffer: bool,
+    pub srgb: bool,
+}
+
+impl Default for PixelFormatRequest {
+    /// 8 bits per color channel and alpha, 24 bit depth, 8 bit stencil, double buffered.
+    fn default() -> Self {
+        Self {
+            color_bits: 24,
+            alpha_bits: 8,
+            depth_bits: 24,
+            stencil_bits: 8,
+            samples: 0,
+            double_buffer: true,
+            srgb: false,
+        }
+    }
+}
+
+/// How well a format fits a request, smaller is better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Score {
+    /// Software formats are a last resort.
+    pub unaccelerated: bool,
+    /// How many requested buffers (alpha, depth, stencil, samples, sRGB) are missing or smaller.
+    pub missing: u32,
+    /// The squared difference in color bits.
+    pub color_difference: u32,
+    /// The squared differences of everything else, counting bits we did not ask for.
+    pub extra: u32,
+}
+
+fn squared_difference(have: u8, want: u8) -> u32 {
+    let d = have as i32 - want as i32;
+    (d * d) as u32
+}
+
+/// Scores `format` for `request`, `None` if it cannot be used at all.
+///
+/// Formats must draw to a window, support OpenGL and match the requested buffering.
+pub fn score(format: &PixelFormatInfo, request: &PixelFormatRequest) -> Option<Score> {
+    if !format.draw_to_window || !format.support_opengl || format.double_buffer != request.double_buffer {
+        return None;
+    }
+    let pairs = [
+        (format.alpha_bits, request.alpha_bits),
+        (format.depth_bits, request.depth_bits),
+        (format.stencil_bits, request.stencil_bits),
+        (format.samples, request.samples),
+    ];
+    let mut missing = pairs.iter().filter(|(have, want)| have < want).count() as u32;
+    if request.srgb && !format.srgb {
+        missing += 1;
+    }
+    Some(Score {
+        unaccelerated: !format.accelerated,
+        missing,
+        color_difference: squared_difference(format.color_bits, request.color_bits),
+        extra: pairs.iter().map(|&(have, want)| squared_difference(have, want)).sum(),
+    })
+}
+
+/// The best format of `formats` for `request`, ties go to the lower index like the driver's
+/// own preference order.
+pub fn choose<'a>(formats: &'a [PixelFormatInfo], request: &PixelFormatRequest) -> Option<&'a PixelFormatInfo> {
+    formats
+        .iter()
+        .filter_map(|format| Some(((score(format, request)?, format.index), format)))
+        .min_by_key(|(key, _)| *key)
+        .map(|(_, format)| format)
+}
+
+/// Whether a space separated extension string such as `wglGetExtensionsStringARB` returns names
+/// `extension`. Substring matches like `WGL_EXT_swap_control_tear` do not count.
+pub fn has_extension(extensions: &str, extension: &str) -> bool {
+    extensions.split_ascii_whitespace().any(|name| name == extension)
+}
+
+/// The OpenGL context we want.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextRequest {
+    pub pixel_format: PixelFormatRequest,
+    /// The OpenGL version as (major, minor).
+    pub version: (u8, u8),
+    /// A core profile, without the deprecated fixed function API. Needs 3.2 or later.
+    pub core_profile: bool,
+    pub debug: bool,
+    /// Wait for vertical blank when swapping buffers.
+    pub vsync: bool,
+}
+
+impl Default for ContextRequest {
+    /// A 3.3 core profile context with vsync.
+    fn default() -> Self {
+        Self {
+            pixel_format: PixelFormatRequest::default(),
+            version: (3, 3),
+            core_profile: true,
+            debug: false,
+            vsync: true,
+        }
+    }
+}
+
+pub const WGL_CONTEXT_MAJOR_VERSION_ARB: i32 = 0x2091;
+pub const WGL_CONTEXT_MINOR_VERSION_ARB: i32 = 0x2092;
+pub const WGL_CONTEXT_FLAGS_ARB: i32 = 0x2094;
+pub const WGL_CONTEXT_PROFILE_MASK_ARB: i32 = 0x9126;
+pub const WGL_CONTEXT_DEBUG_BIT_ARB: i32 = 0x0001;
+pub const WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB: i32 = 0x0002;
+pub const WGL_CONTEXT_CORE_PROFILE_BIT_ARB: i32 = 0x0001;
+pub const WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB: i32 = 0x0002;
+
+impl ContextRequest {
+    /// The zero terminated attribute list for `wglCreateContextAttribsARB`.
+    ///
+    /// Core profiles are also marked forward compatible, some older drivers hand out a
+    /// compatibility context otherwise.
+    pub fn attributes(&self) -> Vec<i32> {
+        let (major, minor) = self.version;
+        let mut attributes =
+            vec![WGL_CONTEXT_MAJOR_VERSION_ARB, major as i32, WGL_CONTEXT_MINOR_VERSION_ARB, minor as i32];
+        let mut flags = 0;
+        if self.debug {
+            flags |= WGL_CONTEXT_DEBUG_BIT_ARB;
+        }
+        // Profiles only exist from 3.2 on.
+        if (major, minor) >= (3, 2) {
+            let profile = if self.core_profile {
+                flags |= WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB;
+                WGL_CONTEXT_CORE_PROFILE_BIT_ARB
+            } else {
+                WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB
+            };
+            attributes.extend([WGL_CONTEXT_PROFILE_MASK_ARB, profile]);
+        }
+        if flags != 0 {
+            attributes.extend([WGL_CONTEXT_FLAGS_ARB, flags]);
+        }
+        attributes.push(0);
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(index: i32, color: u8, alpha: u8, depth: u8, stencil: u8, samples: u8) -> PixelFormatInfo {
+        PixelFormatInfo {
+            index,
+            draw_to_window: true,
+            support_opengl: true,
+            accelerated: true,
+            double_buffer: true,
+            color_bits: color,
+            alpha_bits: alpha,
+            depth_bits: depth,
+            stencil_bits: stencil,
+            samples,
+            srgb: false,
+        }
+    }
+
+    #[test]
+    fn unusable_formats_are_skipped() {
+        let request = PixelFormatRequest::default();
+        let good = format(1, 24, 8, 24, 8, 0);
+        assert!(score(&PixelFormatInfo { draw_to_window: false, ..good }, &request).is_none());
+        assert!(score(&PixelFormatInfo { support_opengl: false, ..good }, &request).is_none());
+        assert!(score(&PixelFormatInfo { double_buffer: false, ..good }, &request).is_none());
+        assert_eq!(choose(&[PixelFormatInfo { double_buffer: false, ..good }], &request), None);
+    }
+
+    #[test]
+    fn exact_match_wins() {
+        let formats = [
+            format(1, 32, 8, 32, 8, 0),
+            format(2, 24, 8, 24, 8, 0),
+            format(3, 24, 8, 24, 8, 4),
+            format(4, 24, 0, 24, 8, 0),
+        ];
+        assert_eq!(choose(&formats, &PixelFormatRequest::default()).unwrap().index, 2);
+        let msaa = PixelFormatRequest { samples: 4, ..Default::default() };
+        assert_eq!(choose(&formats, &msaa).unwrap().index, 3);
+    }
+
+    #[test]
+    fn missing_buffers_count_before_closeness() {
+        // Format 1 is closer in color depth but has no stencil buffer.
+        let formats = [format(1, 24, 8, 24, 0, 0), format(2, 32, 8, 24, 8, 0)];
+        assert_eq!(choose(&formats, &PixelFormatRequest::default()).unwrap().index, 2);
+        // Without a stencil request the closer one wins.
+        let no_stencil = PixelFormatRequest { stencil_bits: 0, ..Default::default() };
+        assert_eq!(choose(&formats, &no_stencil).unwrap().index, 1);
+    }
+
+    #[test]
+    fn acceleration_beats_everything() {
+        let software = PixelFormatInfo { accelerated: false, ..format(1, 24, 8, 24, 8, 0) };
+        let hardware = format(2, 16, 0, 16, 0, 0);
+        assert_eq!(choose(&[software, hardware], &PixelFormatRequest::default()).unwrap().index, 2);
+        assert_eq!(choose(&[software], &PixelFormatRequest::default()).unwrap().index, 1);
+    }
+
+    #[test]
+    fn srgb_and_ties() {
+        let srgb = PixelFormatRequest { srgb: true, ..Default::default() };
+        let plain = format(1, 24, 8, 24, 8, 0);
+        let with_srgb = PixelFormatInfo { index: 2, srgb: true, ..plain };
+        assert_eq!(choose(&[plain, with_srgb], &srgb).unwrap().index, 2);
+        assert_eq!(choose(&[with_srgb, plain], &PixelFormatRequest::default()).unwrap().index, 1);
+    }
+
+    #[test]
+    fn extension_names_match_whole_words() {
+        let list = "WGL_ARB_pixel_format WGL_EXT_swap_control_tear  WGL_ARB_create_context";
+        assert!(has_extension(list, "WGL_ARB_create_context"));
+        assert!(!has_extension(list, "WGL_EXT_swap_control"));
+    }
+
+    #[test]
+    fn context_attributes() {
+        let core = ContextRequest::default().attributes();
+        assert_eq!(
+            core,
+            [0x2091, 3, 0x2092, 3, 0x9126, WGL_CONTEXT_CORE_PROFILE_BIT_ARB, 0x2094, WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB, 0]
+        );
+        let legacy = ContextRequest { version: (2, 1), debug: true, ..Default::default() }.attributes();
+        assert_eq!(legacy, [0x2091, 2, 0x2092, 1, 0x2094, WGL_CONTEXT_DEBUG_BIT_ARB, 0]);
+    }
+}
@@ -0,0 +1,152 @@
+//! A window that shows a CPU side RGBA framebuffer, for drawing pixel by pixel.
+//!
+//! The frame is converted with [`crate::dib`] and handed to
+//! [`StretchDIBits`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-stretchdibits)
+//! as a top-down 32 bit DIB, so no GDI bitmap objects need to be managed.
+
+use std::cell::{Cell, RefCell, RefMut};
+use std::rc::Rc;
+
+use crate::dib::{border_rects, destination_rect, image_to_bgra, Orientation, Scaling};
+use crate::geometry::Size;
+use crate::image::Image;
+use crate::win32::*;
+use crate::window::{pump_messages, Window, WindowBuilder};
+
+struct Shared {
+    frame: RefCell<Image>,
+    scaling: Cell<Scaling>,
+}
+
+/// A window owning a framebuffer which is presented after every frame and on `WM_PAINT`.
+pub struct PixelWindow {
+    window: Window,
+    shared: Rc<Shared>,
+}
+
+impl PixelWindow {
+    /// Opens a window with a `width` by `height` client area and a framebuffer of the same size.
+    pub fn new(title: &str, width: u32, height: u32) -> Result<Self, Win32Error> {
+        let shared = Rc::new(Shared {
+            frame: RefCell::new(Image::new(width, height)),
+            scaling: Cell::new(Scaling::default()),
+        });
+        let handler_shared = Rc::clone(&shared);
+        let window = WindowBuilder::new("triangle_from_scratch PixelWindow")
+            .title(title)
+            .client_size(width as CInt, height as CInt)
+            .build(move |hwnd, msg, _w_param, _l_param| match msg {
+                // Every pixel of the client area is drawn when presenting.
+                WM_ERASEBKGND => Some(1),
+                WM_PAINT => {
+                    let mut ps = PAINTSTRUCT::default();
+                    let hdc = unsafe { BeginPaint(hwnd, &mut ps) };
+                    // The app may be holding the frame while pumping messages, in which case the
+                    // next present will catch up.
+                    if let Ok(frame) = handler_shared.frame.try_borrow() {
+                        let _ = present_to(hdc, hwnd, &frame, handler_shared.scaling.get());
+                    }
+                    unsafe { EndPaint(hwnd, &ps) };
+                    Some(0)
+                }
+                _ => None,
+            })?;
+        window.show();
+        Ok(Self { window, shared })
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// The framebuffer, write pixels here and call [`PixelWindow::present`].
+    ///
+    /// # Panics
+    /// If the frame is already borrowed.
+    pub fn frame(&self) -> RefMut<'_, Image> {
+        self.shared.frame.borrow_mut()
+    }
+
+    /// Changes the framebuffer size, keeping what fits of the current contents.
+    pub fn resize_frame(&self, width: u32, height: u32) {
+        self.frame().resize(width, height, Default::default());
+    }
+
+    pub fn scaling(&self) -> Scaling {
+        self.shared.scaling.get()
+    }
+
+    pub fn set_scaling(&self, scaling: Scaling) {
+        self.shared.scaling.set(scaling);
+    }
+
+    /// Draws the framebuffer into the window right away.
+    pub fn present(&self) -> Result<(), Win32Error> {
+        let hwnd = self.window.hwnd();
+        let hdc = unsafe { GetDC(hwnd) };
+        if hdc.is_null() {
+            return Err(get_last_error());
+        }
+        let result = present_to(hdc, hwnd, &self.shared.frame.borrow(), self.scaling());
+        unsafe { ReleaseDC(hwnd, hdc) };
+        result
+    }
+
+    /// Calls `update` to draw every frame and presents it, until the window is closed.
+    ///
+    /// Frames are drawn as fast as possible, returns the exit code of `WM_QUIT`.
+    pub fn run(&self, mut update: impl FnMut(&mut Image)) -> Result<CInt, Win32Error> {
+        loop {
+            if let Some(exit_code) = pump_messages() {
+                return Ok(exit_code);
+            }
+            update(&mut self.frame());
+            self.present()?;
+        }
+    }
+}
+
+fn present_to(hdc: HDC, hwnd: HWND, image: &Image, scaling: Scaling) -> Result<(), Win32Error> {
+    unsafe { present_image(hdc, image, get_client_rect(hwnd)?.size(), scaling) }
+}
+
+/// Draws `image` into a client area of size `client`, clearing any bars around it to black.
+///
+/// # Safety
+/// `hdc` must be a valid device context.
+pub unsafe fn present_image(hdc: HDC, image: &Image, client: Size, scaling: Scaling) -> Result<(), Win32Error> {
+    let destination = destination_rect(
+        Size::new(image.width() as CInt, image.height() as CInt),
+        client,
+        scaling,
+    );
+    let black = GetStockObject(BLACK_BRUSH);
+    for border in border_rects(client, destination) {
+        FillRect(hdc, &border.into(), black);
+    }
+    let bits = image_to_bgra(image, Orientation::TopDown);
+    let info = BITMAPINFO::new(image.width(), image.height(), 32, Orientation::TopDown);
+    // When shrinking, drop the rows and columns that don't fit. The default mode ANDs them into
+    // their neighbours instead, which smears dark pixels over color images.
+    SetStretchBltMode(hdc, COLORONCOLOR);
+    let lines = StretchDIBits(
+        hdc,
+        destination.left,
+        destination.top,
+        destination.width(),
+        destination.height(),
+        0,
+        0,
+        image.width() as CInt,
+        image.height() as CInt,
+        bits.as_ptr().cast(),
+        &info,
+        DIB_RGB_COLORS,
+        SRCCOPY,
+    );
+    if lines == 0 && image.height() != 0 {
+        Err(get_last_error())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,372 @@
+//! A small PNG encoder, with its own deflate so there are no dependencies.
+//!
+//! Rows are filtered with whichever of the five PNG filters gives the smallest sum of absolute
+//! values (the heuristic libpng uses) and compressed with LZ77 and the fixed deflate Huffman
+//! codes. That is a fair bit bigger than a tuned zlib would produce, but far smaller than raw
+//! pixels for the flat colors and gradients we draw.
+
+use crate::image::Image;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const COLOR_TYPE_RGB: u8 = 2;
+const COLOR_TYPE_RGBA: u8 = 6;
+
+/// Encodes `image` as an 8 bit per channel PNG, without an alpha channel if every pixel is opaque.
+pub fn encode(image: &Image) -> Vec<u8> {
+    let opaque = image.pixels().iter().all(|p| p.a == 255);
+    let channels = if opaque { 3 } else { 4 };
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width().to_be_bytes());
+    header.extend_from_slice(&image.height().to_be_bytes());
+    // Bit depth, color type, compression, filter and interlace method.
+    header.extend_from_slice(&[8, if opaque { COLOR_TYPE_RGB } else { COLOR_TYPE_RGBA }, 0, 0, 0]);
+
+    let mut raw = Vec::with_capacity((image.width() as usize * channels + 1) * image.height() as usize);
+    let mut previous = vec![0; image.width() as usize * channels];
+    for y in 0..image.height() {
+        let row: Vec<u8> = image
+            .row(y)
+            .iter()
+            .flat_map(|p| [p.r, p.g, p.b, p.a].into_iter().take(channels))
+            .collect();
+        let (filter, filtered) = filter_row(&row, &previous, channels);
+        raw.push(filter);
+        raw.extend_from_slice(&filtered);
+        previous = row;
+    }
+
+    let mut bytes = SIGNATURE.to_vec();
+    write_chunk(&mut bytes, b"IHDR", &header);
+    write_chunk(&mut bytes, b"IDAT", &zlib_compress(&raw));
+    write_chunk(&mut bytes, b"IEND", &[]);
+    bytes
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// The CRC-32 used by PNG chunks (and zip, and gzip).
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// The checksum at the end of a zlib stream.
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    // 5552 bytes is the most that can be summed before `b` could overflow.
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Applies every filter to `row` and keeps the one with the smallest sum of absolute values.
+fn filter_row(row: &[u8], previous: &[u8], channels: usize) -> (u8, Vec<u8>) {
+    let mut best: Option<(u64, u8, Vec<u8>)> = None;
+    for filter in 0..5_u8 {
+        let filtered: Vec<u8> = (0..row.len())
+            .map(|i| {
+                let left = if i >= channels { row[i - channels] } else { 0 };
+                let up = previous[i];
+                let up_left = if i >= channels { previous[i - channels] } else { 0 };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    _ => paeth(left, up, up_left),
+                };
+                row[i].wrapping_sub(predicted)
+            })
+            .collect();
+        let cost = filtered.iter().map(|&b| (b as i8).unsigned_abs() as u64).sum();
+        let better = match &best {
+            Some((best_cost, _, _)) => cost < *best_cost,
+            None => true,
+        };
+        if better {
+            best = Some((cost, filter, filtered));
+        }
+    }
+    let (_, filter, filtered) = best.expect("there are five filters");
+    (filter, filtered)
+}
+
+/// Writes bits least significant first, as deflate wants.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.bits |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first.
+    fn write_code(&mut self, code: u32, count: u32) {
+        self.write(code.reverse_bits() >> (32 - count), count);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049,
+    3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// How many earlier positions with the same hash are tried, trading speed for size.
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+/// Writes a literal or end of block symbol with the fixed literal/length code.
+fn write_literal(out: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => out.write_code(0x30 + symbol, 8),
+        144..=255 => out.write_code(0x190 + symbol - 144, 9),
+        256..=279 => out.write_code(symbol - 256, 7),
+        _ => out.write_code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let code = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).unwrap();
+    write_literal(out, 257 + code as u32);
+    out.write((length - LENGTH_BASE[code] as usize) as u32, LENGTH_EXTRA[code] as u32);
+    let code = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).unwrap();
+    out.write_code(code as u32, 5);
+    out.write((distance - DISTANCE_BASE[code] as usize) as u32, DISTANCE_EXTRA[code] as u32);
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]);
+    (value.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Compresses `data` as a single fixed Huffman deflate block.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter { bytes: Vec::new(), bits: 0, count: 0 };
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman codes).
+    out.write(1, 1);
+    out.write(1, 2);
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let insert = |position: usize, head: &mut [usize], previous: &mut [usize]| {
+        if position + MIN_MATCH <= data.len() {
+            let h = hash(&data[position..]);
+            previous[position] = head[h];
+            head[h] = position;
+        }
+    };
+    let mut position = 0;
+    while position < data.len() {
+        let mut best = (0, 0);
+        if position + MIN_MATCH <= data.len() {
+            let max = MAX_MATCH.min(data.len() - position);
+            let mut candidate = head[hash(&data[position..])];
+            let mut chain = 0;
+            while candidate != usize::MAX && position - candidate <= WINDOW && chain < MAX_CHAIN {
+                let length = data[candidate..]
+                    .iter()
+                    .zip(&data[position..position + max])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length > best.0 {
+                    best = (length, position - candidate);
+                    if length == max {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+                chain += 1;
+            }
+        }
+        if best.0 >= MIN_MATCH {
+            write_match(&mut out, best.0, best.1);
+            for p in position..position + best.0 {
+                insert(p, &mut head, &mut previous);
+            }
+            position += best.0;
+        } else {
+            write_literal(&mut out, data[position] as u32);
+            insert(position, &mut head, &mut previous);
+            position += 1;
+        }
+    }
+    write_literal(&mut out, 256);
+    out.finish()
+}
+
+/// Wraps [`deflate`] in the zlib header and checksum that PNG requires.
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window, and a check value making the header a multiple of 31.
+    let mut bytes = vec![0x78, 0x01];
+    bytes.extend_from_slice(&deflate(data));
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Rgba;
+
+    /// Just enough of an inflater to read back what [`deflate`] writes.
+    fn inflate_fixed(bytes: &[u8]) -> Vec<u8> {
+        let mut bit = 0;
+        let mut read = |count: u32| {
+            let mut value = 0;
+            for i in 0..count {
+                value |= ((bytes[bit / 8] >> (bit % 8)) as u32 & 1) << i;
+                bit += 1;
+            }
+            value
+        };
+        assert_eq!(read(3), 0b011, "expected a single fixed Huffman block");
+        let mut out = Vec::new();
+        loop {
+            // Fixed literal/length codes are 7 to 9 bits, read them most significant bit first.
+            let mut code = 0;
+            let mut length = 0;
+            let symbol = loop {
+                code = (code << 1) | read(1);
+                length += 1;
+                match (length, code) {
+                    (7, 0..=0x17) => break code + 256,
+                    (8, 0x30..=0xbf) => break code - 0x30,
+                    (8, 0xc0..=0xc7) => break code - 0xc0 + 280,
+                    (9, 0x190..=0x1ff) => break code - 0x190 + 144,
+                    _ => assert!(length < 9),
+                }
+            };
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => return out,
+                _ => {
+                    let code = symbol as usize - 257;
+                    let length = LENGTH_BASE[code] as usize + read(LENGTH_EXTRA[code] as u32) as usize;
+                    let code = (read(5).reverse_bits() >> 27) as usize;
+                    let distance = DISTANCE_BASE[code] as usize + read(DISTANCE_EXTRA[code] as u32) as usize;
+                    for _ in 0..length {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!((0x78_u16 << 8 | 0x01) % 31, 0);
+    }
+
+    #[test]
+    fn deflate_round_trips() {
+        let mut state = 0x1234_5678_u32;
+        let noise: Vec<u8> = (0..5000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let repetitive: Vec<u8> = b"abcabcabd".iter().cycle().take(70_000).copied().collect();
+        for data in [&b""[..], b"a", b"aaaaaaaaaaaaaaaaaaaaaaa", &noise, &repetitive] {
+            assert_eq!(inflate_fixed(&deflate(data)), data);
+        }
+        assert!(deflate(&repetitive).len() < 1000);
+    }
+
+    #[test]
+    fn filters_invert() {
+        let previous = [10, 20, 30, 40, 50, 60];
+        let row = [12, 200, 31, 45, 49, 7];
+        for channels in [3, 4] {
+            let (filter, filtered) = filter_row(&row, &previous, channels);
+            let mut decoded = vec![0_u8; row.len()];
+            for i in 0..row.len() {
+                let left = if i >= channels { decoded[i - channels] } else { 0 };
+                let up_left = if i >= channels { previous[i - channels] } else { 0 };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => previous[i],
+                    3 => ((left as u16 + previous[i] as u16) / 2) as u8,
+                    _ => paeth(left, previous[i], up_left),
+                };
+                decoded[i] = filtered[i].wrapping_add(predicted);
+            }
+            assert_eq!(decoded, row);
+        }
+    }
+
+    #[test]
+    fn file_structure() {
+        let opaque = encode(&Image::filled(3, 2, Rgba::WHITE));
+        assert_eq!(&opaque[..8], &SIGNATURE);
+        assert_eq!(&opaque[12..16], b"IHDR");
+        assert_eq!(&opaque[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(opaque[25], COLOR_TYPE_RGB);
+        assert_eq!(&opaque[opaque.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+        let translucent = encode(&Image::new(3, 2));
+        assert_eq!(translucent[25], COLOR_TYPE_RGBA);
+    }
+}
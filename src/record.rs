@@ -0,0 +1,865 @@
+//! Recording the messages a window receives and playing them back.
+//!
+//! A [`Recording`] keeps every message together with whatever its `LPARAM` pointed at (window
+//! text, rectangles, `CREATESTRUCTW` and friends), copied by value since the pointers are
+//! meaningless outside the original call. Messages pointing at things we can't copy, like the
+//! `NMHDR` of `WM_NOTIFY`, are recorded but not replayed into window procedures, see
+//! [`replayable`]. Recordings are saved as plain text, one message per
+//! line, so they can be attached to bug reports and edited by hand:
+//!
+//! ```text
+//! # time hwnd message w_param l_param point [payload]
+//! 1000 0x1a0b 0x0081 0x0 0x0 0,0 create 100 100 640 480 0xcf0000 0x0 "Title" "Class"  # Raw { w_param: 0, l_param: 0 }
+//! 1016 0x1a0b 0x0201 0x1 0x140014 20,20  # Mouse { position: Point { x: 20, y: 20 }, keys: 1 }
+//! ```
+//!
+//! Playback calls a handler with each message in order and nothing in between, so a bug that
+//! depends on message order happens the same way every time. On Windows the messages can be fed
+//! to a real window procedure, and anywhere a plain Rust function can take them instead.
+
+use core::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::geometry::{Point, Rect, Size};
+use crate::message::*;
+
+/// The `CREATESTRUCTW` of `WM_NCCREATE` and `WM_CREATE`, minus its pointers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateParams {
+    pub position: Point,
+    pub size: Size,
+    pub style: u32,
+    pub ex_style: u32,
+    pub name: String,
+    /// The class name, or `#` and the atom for classes given by atom.
+    pub class: String,
+}
+
+/// The `WINDOWPOS` of `WM_WINDOWPOSCHANGING` and `WM_WINDOWPOSCHANGED`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowPos {
+    pub insert_after: usize,
+    pub position: Point,
+    pub size: Size,
+    pub flags: u32,
+}
+
+/// The `MINMAXINFO` of `WM_GETMINMAXINFO`, as passed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MinMaxInfo {
+    pub reserved: Point,
+    pub max_size: Point,
+    pub max_position: Point,
+    pub min_track_size: Point,
+    pub max_track_size: Point,
+}
+
+/// The `NCCALCSIZE_PARAMS` of `WM_NCCALCSIZE` when its `WPARAM` is `TRUE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NcCalcSize {
+    /// The new window rectangle, then the old window and client rectangles.
+    pub rects: [Rect; 3],
+    pub window_pos: WindowPos,
+}
+
+/// The `STYLESTRUCT` of `WM_STYLECHANGING` and `WM_STYLECHANGED`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyleChange {
+    pub old: u32,
+    pub new: u32,
+}
+
+/// The `COPYDATASTRUCT` of `WM_COPYDATA`, with the data it points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyData {
+    pub data: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// What a message's `LPARAM` pointed at.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Payload {
+    /// The `LPARAM` is a plain value (or a pointer we do not capture).
+    #[default]
+    None,
+    /// `WM_SETTEXT` and `WM_SETTINGCHANGE`.
+    Text(String),
+    /// `WM_SIZING`, `WM_MOVING`, `WM_DPICHANGED` and `WM_NCCALCSIZE` with a `WPARAM` of `FALSE`.
+    Rect(Rect),
+    Create(CreateParams),
+    WindowPos(WindowPos),
+    MinMaxInfo(MinMaxInfo),
+    NcCalcSize(NcCalcSize),
+    Style(StyleChange),
+    CopyData(CopyData),
+}
+
+/// Whether the `LPARAM` of `message` is a pointer (when it isn't 0).
+pub fn points_to_memory(message: u32) -> bool {
+    matches!(
+        message,
+        WM_SETTEXT
+            | WM_GETTEXT
+            | WM_SETTINGCHANGE
+            | WM_GETMINMAXINFO
+            | WM_DRAWITEM
+            | WM_MEASUREITEM
+            | WM_DELETEITEM
+            | WM_COMPAREITEM
+            | WM_WINDOWPOSCHANGING
+            | WM_WINDOWPOSCHANGED
+            | WM_COPYDATA
+            | WM_NOTIFY
+            | WM_HELP
+            | WM_STYLECHANGING
+            | WM_STYLECHANGED
+            | WM_NCCREATE
+            | WM_CREATE
+            | WM_NCCALCSIZE
+            | WM_SIZING
+            | WM_MOVING
+            | WM_DPICHANGED
+    )
+}
+
+/// Whether `message` can be handed to a window procedure again: its `LPARAM` is a plain value,
+/// or what it points at was recorded. `WM_GETTEXT` only needs an empty buffer.
+///
+/// Replaying anything else would pass on an address from the recording, which now points
+/// nowhere (or somewhere worse).
+pub fn replayable(message: &RecordedMessage) -> bool {
+    message.l_param == 0
+        || message.payload != Payload::None
+        || message.message == WM_GETTEXT
+        || !points_to_memory(message.message)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some((char::from(*high).to_digit(16)? * 16 + char::from(*low).to_digit(16)?) as u8),
+            _ => None,
+        })
+        .collect()
+}
+
+/// One message, like a `MSG` with the `LPARAM` target copied out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMessage {
+    /// Milliseconds, from `GetMessageTime`.
+    pub time: u32,
+    /// The window it was sent to, only useful to tell windows apart.
+    pub hwnd: usize,
+    pub message: u32,
+    pub w_param: usize,
+    pub l_param: isize,
+    /// The cursor position in screen coordinates when the message was sent.
+    pub point: Point,
+    pub payload: Payload,
+}
+
+impl RecordedMessage {
+    /// A message without a time, position or payload, handy for writing recordings by hand.
+    pub fn new(message: u32, w_param: usize, l_param: isize) -> Self {
+        Self { time: 0, hwnd: 0, message, w_param, l_param, point: Point::default(), payload: Payload::None }
+    }
+
+    pub fn with_payload(mut self, payload: Payload) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub fn params(&self) -> Params {
+        Params::decode(self.message, self.w_param, self.l_param)
+    }
+}
+
+/// A syntax error in a saved recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A list of messages in the order they arrived.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recording {
+    messages: Vec<RecordedMessage>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, message: RecordedMessage) {
+        self.messages.push(message);
+    }
+
+    pub fn messages(&self) -> &[RecordedMessage] {
+        &self.messages
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Calls `handler` with every message in order, returning what it returned for each.
+    pub fn replay(&self, handler: impl FnMut(&RecordedMessage) -> isize) -> Vec<isize> {
+        self.messages.iter().map(handler).collect()
+    }
+
+    /// The text form described in the module documentation.
+    pub fn to_text(&self) -> String {
+        use fmt::Write;
+        let mut text = String::from("# time hwnd message w_param l_param point [payload]\n");
+        for m in &self.messages {
+            let _ = write!(
+                text,
+                "{} {:#x} {:#06x} {:#x} {:#x} {},{}",
+                m.time, m.hwnd, m.message, m.w_param, m.l_param as usize, m.point.x, m.point.y
+            );
+            let _ = match &m.payload {
+                Payload::None => Ok(()),
+                Payload::Text(s) => write!(text, " text {:?}", s),
+                Payload::Rect(r) => write!(text, " rect {} {} {} {}", r.left, r.top, r.right, r.bottom),
+                Payload::Create(c) => write!(
+                    text,
+                    " create {} {} {} {} {:#x} {:#x} {:?} {:?}",
+                    c.position.x, c.position.y, c.size.width, c.size.height, c.style, c.ex_style, c.name, c.class
+                ),
+                Payload::WindowPos(p) => write!(
+                    text,
+                    " windowpos {:#x} {} {} {} {} {:#x}",
+                    p.insert_after, p.position.x, p.position.y, p.size.width, p.size.height, p.flags
+                ),
+                Payload::MinMaxInfo(i) => {
+                    text.push_str(" minmaxinfo");
+                    for p in [i.reserved, i.max_size, i.max_position, i.min_track_size, i.max_track_size] {
+                        let _ = write!(text, " {},{}", p.x, p.y);
+                    }
+                    Ok(())
+                }
+                Payload::NcCalcSize(n) => {
+                    text.push_str(" nccalcsize");
+                    for r in n.rects {
+                        let _ = write!(text, " {} {} {} {}", r.left, r.top, r.right, r.bottom);
+                    }
+                    let p = n.window_pos;
+                    write!(
+                        text,
+                        " {:#x} {} {} {} {} {:#x}",
+                        p.insert_after, p.position.x, p.position.y, p.size.width, p.size.height, p.flags
+                    )
+                }
+                Payload::Style(s) => write!(text, " style {:#x} {:#x}", s.old, s.new),
+                Payload::CopyData(c) => write!(text, " copydata {:#x} {:?}", c.data, hex(&c.bytes)),
+            };
+            let _ = writeln!(text, "  # {:?}", m.params());
+        }
+        text
+    }
+
+    /// Reads the text form back, blank lines and anything after a `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut recording = Recording::new();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| ParseError { line: index + 1, message };
+            let tokens = tokenize(line).map_err(error)?;
+            if tokens.is_empty() {
+                continue;
+            }
+            let mut tokens = Tokens { tokens: tokens.into_iter() };
+            recording.push(parse_message(&mut tokens).map_err(error)?);
+            if let Some(extra) = tokens.tokens.next() {
+                return Err(error(format!("unexpected {:?} at the end of the line", extra)));
+            }
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+}
+
+/// Splits a line into words and quoted strings, stopping at a `#` outside quotes.
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '#' {
+            break;
+        } else if c == '"' {
+            chars.next();
+            tokens.push(Token::Quoted(unescape(&mut chars)?));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reads the rest of a string written with `{:?}`, up to and including the closing quote.
+fn unescape(chars: &mut impl Iterator<Item = char>) -> Result<String, String> {
+    let mut s = String::new();
+    loop {
+        match chars.next().ok_or("unterminated string")? {
+            '"' => return Ok(s),
+            '\\' => s.push(match chars.next().ok_or("unterminated string")? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                c @ ('\\' | '"' | '\'') => c,
+                'u' => {
+                    let digits: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                    u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| format!("bad escape \\u{{{}}}", digits))?
+                }
+                c => return Err(format!("unknown escape \\{}", c)),
+            }),
+            c => s.push(c),
+        }
+    }
+}
+
+struct Tokens {
+    tokens: std::vec::IntoIter<Token>,
+}
+
+impl Tokens {
+    fn word(&mut self, what: &str) -> Result<String, String> {
+        match self.tokens.next() {
+            Some(Token::Word(word)) => Ok(word),
+            Some(Token::Quoted(s)) => Err(format!("expected {}, found the string {:?}", what, s)),
+            None => Err(format!("expected {}", what)),
+        }
+    }
+
+    fn string(&mut self, what: &str) -> Result<String, String> {
+        match self.tokens.next() {
+            Some(Token::Quoted(s)) => Ok(s),
+            Some(Token::Word(word)) => Err(format!("expected {} in quotes, found {:?}", what, word)),
+            None => Err(format!("expected {}", what)),
+        }
+    }
+
+    /// A decimal or `0x` hexadecimal integer, possibly negative, that fits into `T`.
+    fn int<T: TryFrom<i128>>(&mut self, what: &str) -> Result<T, String> {
+        let word = self.word(what)?;
+        let value = parse_int(&word).ok_or_else(|| format!("expected {}, found {:?}", what, word))?;
+        T::try_from(value).map_err(|_| format!("{} {} is out of range", what, word))
+    }
+
+    /// Written as `usize`, so both signed and unsigned values are fine.
+    fn l_param(&mut self) -> Result<isize, String> {
+        let word = self.word("an LPARAM")?;
+        let value = parse_int(&word).ok_or_else(|| format!("expected an LPARAM, found {:?}", word))?;
+        isize::try_from(value)
+            .or_else(|_| usize::try_from(value).map(|value| value as isize))
+            .map_err(|_| format!("an LPARAM {} is out of range", word))
+    }
+
+    fn point(&mut self, what: &str) -> Result<Point, String> {
+        let word = self.word(what)?;
+        let coordinate = |s: &str| parse_int(s).and_then(|value| i32::try_from(value).ok());
+        word.split_once(',')
+            .and_then(|(x, y)| Some(Point::new(coordinate(x)?, coordinate(y)?)))
+            .ok_or_else(|| format!("expected {} as x,y, found {:?}", what, word))
+    }
+
+    fn rect(&mut self) -> Result<Rect, String> {
+        Ok(Rect::new(
+            self.int("left")?,
+            self.int("top")?,
+            self.int("right")?,
+            self.int("bottom")?,
+        ))
+    }
+
+    fn window_pos(&mut self) -> Result<WindowPos, String> {
+        Ok(WindowPos {
+            insert_after: self.int("the insert after window")?,
+            position: Point::new(self.int("x")?, self.int("y")?),
+            size: Size::new(self.int("width")?, self.int("height")?),
+            flags: self.int("the flags")?,
+        })
+    }
+}
+
+fn parse_int(s: &str) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let value: i128 = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn parse_message(tokens: &mut Tokens) -> Result<RecordedMessage, String> {
+    let mut message = RecordedMessage {
+        time: tokens.int("a time")?,
+        hwnd: tokens.int("a window handle")?,
+        message: tokens.int("a message id")?,
+        w_param: tokens.int("a WPARAM")?,
+        l_param: tokens.l_param()?,
+        point: tokens.point("the cursor position")?,
+        payload: Payload::None,
+    };
+    let Some(kind) = tokens.tokens.next() else {
+        return Ok(message);
+    };
+    message.payload = match kind {
+        Token::Word(kind) if kind == "text" => Payload::Text(tokens.string("the text")?),
+        Token::Word(kind) if kind == "rect" => Payload::Rect(tokens.rect()?),
+        Token::Word(kind) if kind == "create" => Payload::Create(CreateParams {
+            position: Point::new(tokens.int("x")?, tokens.int("y")?),
+            size: Size::new(tokens.int("width")?, tokens.int("height")?),
+            style: tokens.int("the style")?,
+            ex_style: tokens.int("the extended style")?,
+            name: tokens.string("the window name")?,
+            class: tokens.string("the class name")?,
+        }),
+        Token::Word(kind) if kind == "windowpos" => Payload::WindowPos(tokens.window_pos()?),
+        Token::Word(kind) if kind == "minmaxinfo" => Payload::MinMaxInfo(MinMaxInfo {
+            reserved: tokens.point("the reserved point")?,
+            max_size: tokens.point("the maximized size")?,
+            max_position: tokens.point("the maximized position")?,
+            min_track_size: tokens.point("the minimum tracking size")?,
+            max_track_size: tokens.point("the maximum tracking size")?,
+        }),
+        Token::Word(kind) if kind == "nccalcsize" => Payload::NcCalcSize(NcCalcSize {
+            rects: [tokens.rect()?, tokens.rect()?, tokens.rect()?],
+            window_pos: tokens.window_pos()?,
+        }),
+        Token::Word(kind) if kind == "style" => Payload::Style(StyleChange {
+            old: tokens.int("the old style")?,
+            new: tokens.int("the new style")?,
+        }),
+        Token::Word(kind) if kind == "copydata" => {
+            let data = tokens.int("the copy data value")?;
+            let bytes = tokens.string("the copied bytes")?;
+            let bytes = unhex(&bytes).ok_or_else(|| format!("expected hexadecimal bytes, found {:?}", bytes))?;
+            Payload::CopyData(CopyData { data, bytes })
+        }
+        other => return Err(format!("unknown payload {:?}", other)),
+    };
+    Ok(message)
+}
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(windows)]
+mod windows {
+    use core::cell::RefCell;
+    use core::ptr::null_mut;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::panic_guard::resume_panic;
+    use crate::win32::*;
+    use crate::window::WindowHandler;
+
+    unsafe fn read_wide(mut ptr: LPCWSTR) -> String {
+        let mut units = Vec::new();
+        while !ptr.is_null() && *ptr != 0 {
+            units.push(*ptr);
+            ptr = ptr.add(1);
+        }
+        String::from_utf16_lossy(&units)
+    }
+
+    fn window_pos(wp: &WINDOWPOS) -> WindowPos {
+        WindowPos {
+            insert_after: wp.hwnd_insert_after as usize,
+            position: Point::new(wp.x, wp.y),
+            size: Size::new(wp.cx, wp.cy),
+            flags: wp.flags,
+        }
+    }
+
+    fn to_windowpos(p: &WindowPos) -> WINDOWPOS {
+        WINDOWPOS {
+            hwnd: null_mut(),
+            hwnd_insert_after: p.insert_after as HWND,
+            x: p.position.x,
+            y: p.position.y,
+            cx: p.size.width,
+            cy: p.size.height,
+            flags: p.flags,
+        }
+    }
+
+    /// Copies out what `l_param` points at, for the messages where we know it is a pointer.
+    unsafe fn read_payload(message: UINT, w_param: WPARAM, l_param: LPARAM) -> Payload {
+        if l_param == 0 {
+            return Payload::None;
+        }
+        match message {
+            WM_SETTEXT | WM_SETTINGCHANGE => Payload::Text(read_wide(l_param as LPCWSTR)),
+            WM_SIZING | WM_MOVING | WM_DPICHANGED => Payload::Rect((*(l_param as *const RECT)).into()),
+            WM_NCCALCSIZE if w_param == 0 => Payload::Rect((*(l_param as *const RECT)).into()),
+            WM_NCCALCSIZE => {
+                let params = &*(l_param as *const NCCALCSIZE_PARAMS);
+                Payload::NcCalcSize(NcCalcSize {
+                    rects: params.rgrc.map(Rect::from),
+                    window_pos: window_pos(&*params.lppos),
+                })
+            }
+            WM_STYLECHANGING | WM_STYLECHANGED => {
+                let style = &*(l_param as *const STYLESTRUCT);
+                Payload::Style(StyleChange { old: style.style_old, new: style.style_new })
+            }
+            WM_COPYDATA => {
+                let copy = &*(l_param as *const COPYDATASTRUCT);
+                let bytes = if copy.lp_data.is_null() {
+                    Vec::new()
+                } else {
+                    core::slice::from_raw_parts(copy.lp_data as *const u8, copy.cb_data as usize).to_vec()
+                };
+                Payload::CopyData(CopyData { data: copy.dw_data, bytes })
+            }
+            WM_NCCREATE | WM_CREATE => {
+                let cs = &*(l_param as *const CREATESTRUCTW);
+                let class = if (cs.lpsz_class as usize) < 0x10000 {
+                    format!("#{}", cs.lpsz_class as usize)
+                } else {
+                    read_wide(cs.lpsz_class)
+                };
+                Payload::Create(CreateParams {
+                    position: Point::new(cs.x, cs.y),
+                    size: Size::new(cs.cx, cs.cy),
+                    style: cs.style as u32,
+                    ex_style: cs.dw_ex_style,
+                    name: read_wide(cs.lpsz_name),
+                    class,
+                })
+            }
+            WM_WINDOWPOSCHANGING | WM_WINDOWPOSCHANGED => Payload::WindowPos(window_pos(&*(l_param as *const WINDOWPOS))),
+            WM_GETMINMAXINFO => {
+                let info = &*(l_param as *const MINMAXINFO);
+                Payload::MinMaxInfo(MinMaxInfo {
+                    reserved: info.pt_reserved.into(),
+                    max_size: info.pt_max_size.into(),
+                    max_position: info.pt_max_position.into(),
+                    min_track_size: info.pt_min_track_size.into(),
+                    max_track_size: info.pt_max_track_size.into(),
+                })
+            }
+            _ => Payload::None,
+        }
+    }
+
+    /// Captures a message from inside a window procedure.
+    ///
+    /// The time and cursor position are those of the last message taken from the queue, which
+    /// for sent messages is the message being handled when they were sent.
+    ///
+    /// # Safety
+    /// The parameters must be exactly those the window procedure received.
+    pub unsafe fn capture(hwnd: HWND, message: UINT, w_param: WPARAM, l_param: LPARAM) -> RecordedMessage {
+        RecordedMessage {
+            time: GetMessageTime() as u32,
+            hwnd: hwnd as usize,
+            message,
+            w_param,
+            l_param,
+            point: point_from_lparam(GetMessagePos() as i32 as isize),
+            payload: read_payload(message, w_param, l_param),
+        }
+    }
+
+    /// Captures a message taken from the queue with `GetMessageW` or `PeekMessageW`.
+    ///
+    /// # Safety
+    /// `msg` must be as the queue returned it.
+    pub unsafe fn capture_msg(msg: &MSG) -> RecordedMessage {
+        RecordedMessage {
+            time: msg.time,
+            hwnd: msg.hwnd as usize,
+            message: msg.message,
+            w_param: msg.w_param,
+            l_param: msg.l_param,
+            point: msg.pt.into(),
+            payload: read_payload(msg.message, msg.w_param, msg.l_param),
+        }
+    }
+
+    /// A [`WindowHandler`] that records every message before passing it on.
+    pub struct Recorder<H> {
+        handler: H,
+        recording: Rc<RefCell<Recording>>,
+    }
+
+    impl<H: WindowHandler> Recorder<H> {
+        /// Records into `recording`, keep a clone of it to save the messages later.
+        pub fn new(handler: H, recording: Rc<RefCell<Recording>>) -> Self {
+            Self { handler, recording }
+        }
+    }
+
+    impl<H: WindowHandler> WindowHandler for Recorder<H> {
+        unsafe fn handle(&self, hwnd: HWND, msg: UINT, w_param: WPARAM, l_param: LPARAM) -> Option<LRESULT> {
+            self.recording.borrow_mut().push(capture(hwnd, msg, w_param, l_param));
+            self.handler.handle(hwnd, msg, w_param, l_param)
+        }
+    }
+
+    /// The signature of a window procedure like `main.rs`'s `window_procedure`.
+    pub type WindowProcedure = unsafe extern "system" fn(HWND, UINT, WPARAM, LPARAM) -> LRESULT;
+
+    /// Calls `f` with an `LPARAM` pointing at a fresh copy of `payload`.
+    unsafe fn with_l_param(
+        message: &RecordedMessage,
+        create_params: LPVOID,
+        f: impl FnOnce(LPARAM) -> LRESULT,
+    ) -> LRESULT {
+        match &message.payload {
+            Payload::None if message.message == WM_GETTEXT && message.l_param != 0 => {
+                let mut buffer = vec![0 as WCHAR; message.w_param];
+                f(buffer.as_mut_ptr() as LPARAM)
+            }
+            Payload::None => f(message.l_param),
+            Payload::Text(text) => f(wide_null(text).as_ptr() as LPARAM),
+            Payload::Rect(rect) => {
+                let mut rect: RECT = (*rect).into();
+                f(&mut rect as *mut RECT as LPARAM)
+            }
+            Payload::Create(c) => {
+                let name = wide_null(&c.name);
+                let class = wide_null(&c.class);
+                let mut cs = CREATESTRUCTW {
+                    lp_create_params: create_params,
+                    h_instance: get_process_handle(),
+                    x: c.position.x,
+                    y: c.position.y,
+                    cx: c.size.width,
+                    cy: c.size.height,
+                    style: c.style as LONG,
+                    dw_ex_style: c.ex_style,
+                    lpsz_name: name.as_ptr(),
+                    lpsz_class: class.as_ptr(),
+                    ..Default::default()
+                };
+                f(&mut cs as *mut CREATESTRUCTW as LPARAM)
+            }
+            Payload::WindowPos(p) => {
+                let mut wp = to_windowpos(p);
+                f(&mut wp as *mut WINDOWPOS as LPARAM)
+            }
+            Payload::NcCalcSize(n) => {
+                let mut wp = to_windowpos(&n.window_pos);
+                let mut params = NCCALCSIZE_PARAMS { rgrc: n.rects.map(RECT::from), lppos: &mut wp };
+                f(&mut params as *mut NCCALCSIZE_PARAMS as LPARAM)
+            }
+            Payload::Style(s) => {
+                let mut style = STYLESTRUCT { style_old: s.old, style_new: s.new };
+                f(&mut style as *mut STYLESTRUCT as LPARAM)
+            }
+            Payload::CopyData(c) => {
+                let mut bytes = c.bytes.clone();
+                let mut copy = COPYDATASTRUCT {
+                    dw_data: c.data,
+                    cb_data: bytes.len() as DWORD,
+                    lp_data: if bytes.is_empty() { null_mut() } else { bytes.as_mut_ptr().cast() },
+                };
+                f(&mut copy as *mut COPYDATASTRUCT as LPARAM)
+            }
+            Payload::MinMaxInfo(i) => {
+                let mut info = MINMAXINFO {
+                    pt_reserved: i.reserved.into(),
+                    pt_max_size: i.max_size.into(),
+                    pt_max_position: i.max_position.into(),
+                    pt_min_track_size: i.min_track_size.into(),
+                    pt_max_track_size: i.max_track_size.into(),
+                };
+                f(&mut info as *mut MINMAXINFO as LPARAM)
+            }
+        }
+    }
+
+    /// Feeds every message straight into `window_procedure` as if sent to `hwnd`, returning the
+    /// results. No other messages get in between, the queue is not involved.
+    ///
+    /// Pointers are rebuilt from the recorded payloads. The `lpCreateParams` of the original
+    /// `CREATESTRUCTW` cannot be recorded, `create_params` is passed in its place. Messages that
+    /// aren't [`replayable`] are skipped, their result is 0.
+    ///
+    /// # Safety
+    /// `window_procedure` must be safe to call with `hwnd` and these messages.
+    ///
+    /// # Panics
+    /// When the procedure stashed a panic, see [`panic_guard`](crate::panic_guard).
+    pub unsafe fn replay_into(
+        recording: &Recording,
+        hwnd: HWND,
+        window_procedure: WindowProcedure,
+        create_params: LPVOID,
+    ) -> Vec<LRESULT> {
+        recording
+            .messages()
+            .iter()
+            .map(|message| {
+                if !replayable(message) {
+                    return 0;
+                }
+                let result = with_l_param(message, create_params, |l_param| {
+                    window_procedure(hwnd, message.message, message.w_param, l_param)
+                });
+                // A procedure using `catch_panic` panics here, like it would in the message loop.
+                resume_panic();
+                result
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Recording {
+        let mut recording = Recording::new();
+        recording.push(RecordedMessage::new(WM_CREATE, 0, 0).with_payload(Payload::Create(CreateParams {
+            position: Point::new(-32000, 10),
+            size: Size::new(640, 480),
+            style: 0x00cf_0000,
+            ex_style: 0,
+            name: "Sample \"Window\"\n\u{1F53A}".to_owned(),
+            class: "#32770".to_owned(),
+        })));
+        recording.push(RecordedMessage { time: 1016, hwnd: 0x1a0b, point: Point::new(-5, 7), ..RecordedMessage::new(WM_LBUTTONDOWN, 1, 0x0014_0014) });
+        recording.push(RecordedMessage::new(WM_KEYDOWN, 0x1b, 0xc001_0001_u32 as i32 as isize));
+        recording.push(RecordedMessage::new(WM_SIZING, 8, 0).with_payload(Payload::Rect(Rect::new(1, 2, 3, 4))));
+        recording.push(RecordedMessage::new(0x24, 0, 0).with_payload(Payload::MinMaxInfo(MinMaxInfo {
+            max_size: Point::new(1920, 1080),
+            ..Default::default()
+        })));
+        recording.push(RecordedMessage::new(0x46, 0, 0).with_payload(Payload::WindowPos(WindowPos {
+            insert_after: usize::MAX,
+            position: Point::new(1, 2),
+            size: Size::new(3, 4),
+            flags: 0x10,
+        })));
+        recording.push(RecordedMessage::new(WM_SETTEXT, 0, 0).with_payload(Payload::Text("# not a comment".to_owned())));
+        recording.push(RecordedMessage::new(WM_NCCALCSIZE, 1, 0).with_payload(Payload::NcCalcSize(NcCalcSize {
+            rects: [Rect::new(0, 0, 8, 8), Rect::new(-1, -1, 7, 7), Rect::new(1, 1, 6, 6)],
+            window_pos: WindowPos { insert_after: 0, position: Point::new(0, 0), size: Size::new(8, 8), flags: 0x20 },
+        })));
+        recording.push(RecordedMessage::new(WM_STYLECHANGED, 0xffff_fff0, 0).with_payload(Payload::Style(StyleChange {
+            old: 0x00cf_0000,
+            new: 0x9000_0000,
+        })));
+        recording.push(RecordedMessage::new(WM_COPYDATA, 0x1a0b, 0).with_payload(Payload::CopyData(CopyData {
+            data: 7,
+            bytes: vec![0, 0x7f, 0xff],
+        })));
+        recording
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let recording = sample();
+        let text = recording.to_text();
+        assert_eq!(Recording::parse(&text), Ok(recording));
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let recording = Recording::parse("# header\n\n  5 0x1 0x000f 0x0 0x0 0,0 # WM_PAINT\n").unwrap();
+        assert_eq!(recording.messages(), &[RecordedMessage { time: 5, hwnd: 1, ..RecordedMessage::new(WM_PAINT, 0, 0) }]);
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = Recording::parse("1 0x1 0x000f 0x0 0x0 0,0\n2 0x1 0x000f 0x0\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "expected an LPARAM");
+        let error = Recording::parse("1 0x1 0xc 0 0 0,0 text \"open").unwrap_err();
+        assert_eq!(error.message, "unterminated string");
+        let error = Recording::parse("1 0x1 0xc 0 0 0,0 text \"a\" extra").unwrap_err();
+        assert!(error.message.starts_with("unexpected"), "{}", error);
+        // Too big for the field, rather than silently cut down.
+        let error = Recording::parse("\n1 0x1 0x10000000f 0x0 0x0 0,0").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: a message id 0x10000000f is out of range");
+        let error = Recording::parse("1 0x1 0x214 0 0 0,0 rect 0 0 4294967296 1").unwrap_err();
+        assert_eq!(error.message, "right 4294967296 is out of range");
+        assert!(Recording::parse("1 0x1 0xf 0 -1 0,0").is_ok());
+    }
+
+    #[test]
+    fn replay_calls_a_plain_handler_in_order() {
+        let mut seen = Vec::new();
+        let results = sample().replay(|message| {
+            seen.push(message.message);
+            match (message.params(), &message.payload) {
+                (crate::message::Params::Mouse { position, .. }, _) => position.x as isize,
+                (_, Payload::Text(text)) => text.len() as isize,
+                _ => 0,
+            }
+        });
+        assert_eq!(seen, sample().messages().iter().map(|m| m.message).collect::<Vec<_>>());
+        assert_eq!(results, vec![0, 20, 0, 0, 0, 0, 15, 0, 0, 0]);
+    }
+
+    #[test]
+    fn stale_pointers_are_not_replayable() {
+        assert!(sample().messages().iter().all(replayable));
+        // A WM_NOTIFY header can't be copied, so the recorded address is all there is.
+        assert!(points_to_memory(WM_NOTIFY));
+        assert!(!replayable(&RecordedMessage::new(WM_NOTIFY, 1, 0x00a0_1000)));
+        assert!(replayable(&RecordedMessage::new(WM_SETTINGCHANGE, 0x2f, 0)));
+        // The buffer of WM_GETTEXT only receives text, replay brings a fresh one.
+        assert!(replayable(&RecordedMessage::new(WM_GETTEXT, 16, 0x00a0_1000)));
+        assert!(replayable(&RecordedMessage::new(WM_KEYDOWN, 0x1b, 0x0001_0001)));
+        let error = Recording::parse("1 0x1 0x4a 0 0 0,0 copydata 0x0 \"abc\"").unwrap_err();
+        assert_eq!(error.message, "expected hexadecimal bytes, found \"abc\"");
+    }
+}
@@ -0,0 +1,356 @@
+//! Pictures described in a small text format instead of code.
+//!
+//! ```text
+//! // Comments run to the end of the line.
+//! background Window
+//! vertex top    150   0 #ff8000
+//! vertex left     0 150 #900090
+//! vertex right  300 150 #098090
+//! triangle top left right
+//! rect top right vertical
+//! ```
+//!
+//! `vertex` names a point with a color, `#rrggbb` or `#rrggbbaa`. `triangle` shades between three
+//! vertices like `GradientFill` does. `rect` fills the rectangle between a top left and a bottom
+//! right vertex, blending from the first color to the second left to right, or top to bottom
+//! when marked `vertical`. `background` is the system color behind everything, `Window` unless
+//! given.
+
+use core::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::canvas::{Canvas, Vertex};
+use crate::color::Color;
+use crate::error::Win32Error;
+use crate::geometry::{Point, Rect};
+use crate::image::Rgba;
+use crate::palette::SysColor;
+
+/// The scene the binary shows without arguments.
+pub const DEFAULT_SCENE: &str = include_str!("../scenes/triangle.scene");
+
+/// Which way a `rect` blends its two colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientDirection {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+/// Something drawn from the scene's vertices, by index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mesh {
+    Triangle([usize; 3]),
+    Rect { top_left: usize, bottom_right: usize, direction: GradientDirection },
+}
+
+/// A parsed scene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scene {
+    pub background: SysColor,
+    pub vertices: Vec<Vertex>,
+    /// Drawn in order, later meshes cover earlier ones.
+    pub meshes: Vec<Mesh>,
+}
+
+impl Default for Scene {
+    /// An empty scene on the window background.
+    fn default() -> Self {
+        Self { background: SysColor::Window, vertices: Vec::new(), meshes: Vec::new() }
+    }
+}
+
+/// A mistake in a scene file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneError {
+    /// Counting from 1.
+    pub line: usize,
+    /// Counting characters from 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// A word and the column it starts at.
+#[derive(Debug, Clone, Copy)]
+struct Word<'a> {
+    text: &'a str,
+    column: usize,
+}
+
+/// Splits a line into words, stopping at `//`.
+fn words(line: &str) -> Vec<Word<'_>> {
+    let line = line.find("//").map_or(line, |comment| &line[..comment]);
+    let mut words = Vec::new();
+    let mut start = None;
+    for (column, (offset, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((offset, column + 1)),
+            (true, Some((from, column))) => {
+                words.push(Word { text: &line[from..offset], column });
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some((from, column)) = start {
+        words.push(Word { text: &line[from..], column });
+    }
+    words
+}
+
+/// `#rrggbb` or `#rrggbbaa`, unlike [`Color::from_hex`] the `#` is required.
+fn parse_color(text: &str) -> Option<Rgba> {
+    text.strip_prefix('#')?;
+    Color::from_hex(text).ok().map(Rgba::from)
+}
+
+/// How far from the origin a coordinate may be, plenty for any screen while leaving room for the
+/// arithmetic of painting.
+const MAX_COORDINATE: i32 = 1 << 24;
+
+/// Parses one scene, remembering vertex names along the way.
+struct Parser<'a> {
+    scene: Scene,
+    names: Vec<&'a str>,
+    line: usize,
+    /// Where the line ends, for complaints about missing words.
+    end_column: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, column: usize, message: String) -> SceneError {
+        SceneError { line: self.line, column, message }
+    }
+
+    fn next(&self, words: &mut impl Iterator<Item = Word<'a>>, what: &str) -> Result<Word<'a>, SceneError> {
+        words.next().ok_or_else(|| self.error(self.end_column, format!("expected {}", what)))
+    }
+
+    fn coordinate(&self, word: Word<'a>) -> Result<i32, SceneError> {
+        let value: i32 =
+            word.text.parse().map_err(|_| self.error(word.column, format!("expected a coordinate, found {:?}", word.text)))?;
+        if !(-MAX_COORDINATE..=MAX_COORDINATE).contains(&value) {
+            return Err(self.error(
+                word.column,
+                format!("coordinate {} is out of range, keep it within ±{}", value, MAX_COORDINATE),
+            ));
+        }
+        Ok(value)
+    }
+
+    fn vertex(&self, word: Word<'a>) -> Result<usize, SceneError> {
+        self.names
+            .iter()
+            .position(|&name| name == word.text)
+            .ok_or_else(|| self.error(word.column, format!("no vertex named {:?}", word.text)))
+    }
+
+    fn statement(&mut self, keyword: Word<'a>, words: &mut impl Iterator<Item = Word<'a>>) -> Result<(), SceneError> {
+        match keyword.text {
+            "background" => {
+                let word = self.next(words, "a system color")?;
+                self.scene.background = SysColor::from_name(word.text)
+                    .ok_or_else(|| self.error(word.column, format!("unknown system color {:?}", word.text)))?;
+            }
+            "vertex" => {
+                let name = self.next(words, "a vertex name")?;
+                if self.names.contains(&name.text) {
+                    return Err(self.error(name.column, format!("vertex {:?} is already defined", name.text)));
+                }
+                let x = self.coordinate(self.next(words, "an x coordinate")?)?;
+                let y = self.coordinate(self.next(words, "a y coordinate")?)?;
+                let color = self.next(words, "a color")?;
+                let color = parse_color(color.text).ok_or_else(|| {
+                    self.error(color.column, format!("expected a color like #ff8000, found {:?}", color.text))
+                })?;
+                self.names.push(name.text);
+                self.scene.vertices.push(Vertex::new(x, y, color));
+            }
+            "triangle" => {
+                let mut corners = [0; 3];
+                for corner in &mut corners {
+                    *corner = self.vertex(self.next(words, "a vertex name")?)?;
+                }
+                self.scene.meshes.push(Mesh::Triangle(corners));
+            }
+            "rect" => {
+                let top_left = self.vertex(self.next(words, "the top left vertex")?)?;
+                let bottom_right = self.vertex(self.next(words, "the bottom right vertex")?)?;
+                let direction = match words.next() {
+                    None => GradientDirection::Horizontal,
+                    Some(Word { text: "horizontal", .. }) => GradientDirection::Horizontal,
+                    Some(Word { text: "vertical", .. }) => GradientDirection::Vertical,
+                    Some(word) => {
+                        return Err(self.error(
+                            word.column,
+                            format!("expected horizontal or vertical, found {:?}", word.text),
+                        ))
+                    }
+                };
+                self.scene.meshes.push(Mesh::Rect { top_left, bottom_right, direction });
+            }
+            other => return Err(self.error(keyword.column, format!("unknown statement {:?}", other))),
+        }
+        Ok(())
+    }
+}
+
+impl Scene {
+    /// The built in [`DEFAULT_SCENE`].
+    pub fn default_scene() -> Self {
+        Self::parse(DEFAULT_SCENE).expect("the default scene parses")
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SceneError> {
+        let mut parser = Parser { scene: Scene::default(), names: Vec::new(), line: 0, end_column: 0 };
+        for (index, line) in text.lines().enumerate() {
+            parser.line = index + 1;
+            parser.end_column = line.chars().count() + 1;
+            let mut words = words(line).into_iter();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            parser.statement(keyword, &mut words)?;
+            if let Some(extra) = words.next() {
+                return Err(parser.error(extra.column, format!("unexpected {:?} at the end of the line", extra.text)));
+            }
+        }
+        Ok(parser.scene)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Fills `dirty` with the background and draws every mesh.
+    ///
+    /// Rectangles are drawn as two triangles whose corners repeat the colors along the gradient,
+    /// which shades exactly like `GRADIENT_FILL_RECT_H` and `_V`.
+    pub fn paint(&self, canvas: &mut impl Canvas, dirty: Rect) -> Result<(), Win32Error> {
+        canvas.fill_sys_color(dirty, self.background)?;
+        for mesh in &self.meshes {
+            match *mesh {
+                Mesh::Triangle(corners) => canvas.gradient_triangle(corners.map(|i| self.vertices[i]))?,
+                Mesh::Rect { top_left, bottom_right, direction } => {
+                    let (first, last) = (self.vertices[top_left], self.vertices[bottom_right]);
+                    let (Point { x: left, y: top }, Point { x: right, y: bottom }) = (first.position, last.position);
+                    let (top_right, bottom_left) = match direction {
+                        GradientDirection::Horizontal => (last.color, first.color),
+                        GradientDirection::Vertical => (first.color, last.color),
+                    };
+                    let top_right = Vertex::new(right, top, top_right);
+                    let bottom_left = Vertex::new(left, bottom, bottom_left);
+                    canvas.gradient_triangle([first, top_right, last])?;
+                    canvas.gradient_triangle([first, last, bottom_left])?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::render;
+    use crate::demo::paint_triangle;
+    use crate::geometry::Size;
+    use crate::palette::Palette;
+
+    fn error(text: &str) -> (usize, usize, String) {
+        let error = Scene::parse(text).unwrap_err();
+        (error.line, error.column, error.message)
+    }
+
+    #[test]
+    fn parses_every_statement() {
+        let scene = Scene::parse(
+            "background ButtonFace // behind everything\n\
+             \n\
+             vertex a 0 0 #ff000080\n\
+             vertex b 10 -5 #00FF00\n\
+             vertex c 3 4 #0000ff\n\
+             triangle a b c\n\
+             rect a b vertical\n\
+             rect a c\n",
+        )
+        .unwrap();
+        assert_eq!(scene.background, SysColor::ButtonFace);
+        assert_eq!(scene.vertices[0], Vertex::new(0, 0, Rgba::new(255, 0, 0, 0x80)));
+        assert_eq!(scene.vertices[1], Vertex::new(10, -5, Rgba::opaque(0, 255, 0)));
+        assert_eq!(
+            scene.meshes,
+            [
+                Mesh::Triangle([0, 1, 2]),
+                Mesh::Rect { top_left: 0, bottom_right: 1, direction: GradientDirection::Vertical },
+                Mesh::Rect { top_left: 0, bottom_right: 2, direction: GradientDirection::Horizontal },
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_word() {
+        assert_eq!(error("vertex a 0 0 #fff\n"), (1, 14, "expected a color like #ff8000, found \"#fff\"".into()));
+        assert_eq!(error("\n  triangle a b c"), (2, 12, "no vertex named \"a\"".into()));
+        assert_eq!(error("vertex a x 0 #ffffff"), (1, 10, "expected a coordinate, found \"x\"".into()));
+        assert_eq!(error("vertex a 0"), (1, 11, "expected a y coordinate".into()));
+        assert_eq!(error("background Blue"), (1, 12, "unknown system color \"Blue\"".into()));
+        assert_eq!(error("circle"), (1, 1, "unknown statement \"circle\"".into()));
+        assert_eq!(
+            error("vertex a 0 0 #ffffff\nvertex a 1 1 #000000"),
+            (2, 8, "vertex \"a\" is already defined".into())
+        );
+        assert_eq!(
+            error("vertex a 0 0 #ffffff\nrect a a diagonal"),
+            (2, 10, "expected horizontal or vertical, found \"diagonal\"".into())
+        );
+        // Columns count characters, not bytes.
+        assert_eq!(error("vertex é 0 0 #ffffff extra").1, 22);
+    }
+
+    #[test]
+    fn coordinates_stay_in_range() {
+        assert!(Scene::parse("vertex a -16777216 16777216 #ffffff").is_ok());
+        assert_eq!(
+            error("vertex a 0 16777217 #ffffff"),
+            (1, 12, "coordinate 16777217 is out of range, keep it within ±16777216".into())
+        );
+        assert_eq!(error("vertex a -2147483648 0 #ffffff").1, 10);
+    }
+
+    #[test]
+    fn default_scene_matches_the_demo() {
+        let size = Size::new(320, 160);
+        let dirty = Rect::from_origin_size(Point::default(), size);
+        let scene = Scene::default_scene();
+        let from_scene = render(size, Palette::default(), |canvas| scene.paint(canvas, dirty)).unwrap();
+        let from_code = render(size, Palette::default(), |canvas| paint_triangle(canvas, dirty)).unwrap();
+        assert_eq!(from_scene, from_code);
+    }
+
+    #[test]
+    fn rects_blend_along_their_direction() {
+        let scene = Scene::parse("vertex a 0 0 #000000\nvertex b 4 4 #ff0000\nrect a b\nrect a b vertical").unwrap();
+        let mut horizontal = scene.clone();
+        horizontal.meshes.pop();
+        let size = Size::new(4, 4);
+        let dirty = Rect::from_origin_size(Point::default(), size);
+        let image = render(size, Palette::default(), |canvas| horizontal.paint(canvas, dirty)).unwrap();
+        // Every row is the same, every column differs.
+        assert!((1..4).all(|y| image.row(y) == image.row(0)));
+        assert!(image.get(0, 0).unwrap().r < image.get(3, 0).unwrap().r);
+        let image = render(size, Palette::default(), |canvas| scene.paint(canvas, dirty)).unwrap();
+        assert!((0..4).all(|y| image.row(y).iter().all(|&p| p == image.row(y)[0])));
+        assert!(image.get(0, 0).unwrap().r < image.get(0, 3).unwrap().r);
+    }
+}
@@ -0,0 +1,106 @@
+//! Reading back what a window shows.
+//!
+//! The client area is copied with `BitBlt` into a bitmap we own, then read out as a top-down
+//! 32 bit DIB with `GetDIBits`. Save the result with [`Image::save`].
+
+use core::ptr::null_mut;
+
+use crate::dib::{dib_to_image, stride, Orientation};
+use crate::geometry::Rect;
+use crate::image::Image;
+use crate::win32::*;
+
+/// Releases whatever GDI resources were acquired so far, in reverse order.
+struct Resources {
+    hwnd: HWND,
+    screen: HDC,
+    memory: HDC,
+    bitmap: HBITMAP,
+}
+
+impl Drop for Resources {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.bitmap.is_null() {
+                DeleteObject(self.bitmap);
+            }
+            if !self.memory.is_null() {
+                DeleteDC(self.memory);
+            }
+            if !self.screen.is_null() {
+                ReleaseDC(self.hwnd, self.screen);
+            }
+        }
+    }
+}
+
+/// Copies the client area of `hwnd` into an opaque image.
+///
+/// This reads what is on screen right now, so parts covered by other windows come out as
+/// whatever covers them.
+///
+/// # Safety
+/// `hwnd` must be a window handle, [`Window::capture_client`](crate::window::Window::capture_client)
+/// is the safe way.
+pub unsafe fn capture_client(hwnd: HWND) -> Result<Image, Win32Error> {
+    let client = get_client_rect(hwnd)?;
+    let mut resources = Resources { hwnd, screen: null_mut(), memory: null_mut(), bitmap: null_mut() };
+    resources.screen = GetDC(hwnd);
+    if resources.screen.is_null() {
+        return Err(get_last_error());
+    }
+    capture_dc(resources.screen, client)
+}
+
+/// Copies `rect` of a device context into an opaque image.
+///
+/// # Safety
+/// `hdc` must be a valid device context.
+pub unsafe fn capture_dc(hdc: HDC, rect: Rect) -> Result<Image, Win32Error> {
+    let (width, height) = (rect.width().max(0) as u32, rect.height().max(0) as u32);
+    if width == 0 || height == 0 {
+        return Ok(Image::new(width, height));
+    }
+    let mut resources = Resources { hwnd: null_mut(), screen: null_mut(), memory: null_mut(), bitmap: null_mut() };
+    resources.memory = CreateCompatibleDC(hdc);
+    if resources.memory.is_null() {
+        return Err(get_last_error());
+    }
+    resources.bitmap = CreateCompatibleBitmap(hdc, width as CInt, height as CInt);
+    if resources.bitmap.is_null() {
+        return Err(get_last_error());
+    }
+    let previous = SelectObject(resources.memory, resources.bitmap);
+    let copied = BitBlt(
+        resources.memory,
+        0,
+        0,
+        width as CInt,
+        height as CInt,
+        hdc,
+        rect.left,
+        rect.top,
+        SRCCOPY,
+    );
+    // `GetDIBits` wants the bitmap not to be selected into any device context.
+    SelectObject(resources.memory, previous);
+    if copied == 0 {
+        return Err(get_last_error());
+    }
+    let mut info = BITMAPINFO::new(width, height, 32, Orientation::TopDown);
+    let mut bits = vec![0_u8; stride(width, 32) * height as usize];
+    let lines = GetDIBits(
+        resources.memory,
+        resources.bitmap,
+        0,
+        height,
+        bits.as_mut_ptr().cast(),
+        &mut info,
+        DIB_RGB_COLORS,
+    );
+    if lines == 0 {
+        return Err(get_last_error());
+    }
+    // The fourth byte of a screen bitmap is not alpha, so the image comes out opaque.
+    Ok(dib_to_image(&bits, width, height, 32, Orientation::TopDown, false).expect("the buffer fits the bitmap"))
+}
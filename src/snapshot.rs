@@ -0,0 +1,379 @@
+//! Golden image tests for painting code.
+//!
+//! A paint routine is rendered offscreen with [`crate::canvas::render`] and compared to a
+//! reference `.bmp` stored next to the tests. Small differences can be allowed per channel and
+//! per number of pixels, so harmless rounding changes do not fail the build. On a mismatch the
+//! actual image and a diff image (differing pixels in red over a faded copy of the reference) are
+//! written beside the reference.
+//!
+//! Set `SNAPSHOT_BLESS=1` to write the current output as the new reference instead of comparing.
+//!
+//! ```no_run
+//! use triangle_from_scratch::demo::paint_triangle;
+//! use triangle_from_scratch::geometry::{Rect, Size};
+//! use triangle_from_scratch::snapshot::Snapshots;
+//!
+//! Snapshots::new("tests/snapshots").assert_paint("triangle", Size::new(300, 150), |canvas| {
+//!     paint_triangle(canvas, Rect::new(0, 0, 300, 150))
+//! });
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::bmp;
+use crate::canvas::{render, ImageCanvas};
+use crate::error::Win32Error;
+use crate::geometry::Size;
+use crate::image::{Image, Rgba};
+use crate::palette::Palette;
+
+/// The environment variable that turns on bless mode.
+pub const BLESS_VAR: &str = "SNAPSHOT_BLESS";
+
+/// How far an image may stray from its reference and still pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tolerance {
+    /// Channels (alpha included) differing by at most this much count as equal.
+    pub per_channel: u8,
+    /// How many pixels may differ by more than `per_channel`.
+    pub max_differing_pixels: usize,
+}
+
+impl Tolerance {
+    /// Every pixel must match exactly.
+    pub const EXACT: Tolerance = Tolerance { per_channel: 0, max_differing_pixels: 0 };
+
+    pub const fn new(per_channel: u8, max_differing_pixels: usize) -> Self {
+        Self { per_channel, max_differing_pixels }
+    }
+}
+
+/// The result of comparing two images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comparison {
+    pub actual_size: Size,
+    pub expected_size: Size,
+    /// Pixels differing by more than the tolerance, zero if the sizes differ.
+    pub differing_pixels: usize,
+    /// The largest difference of any channel of any pixel.
+    pub max_channel_difference: u8,
+    pub tolerance: Tolerance,
+}
+
+impl Comparison {
+    pub fn sizes_match(&self) -> bool {
+        self.actual_size == self.expected_size
+    }
+
+    pub fn passed(&self) -> bool {
+        self.sizes_match() && self.differing_pixels <= self.tolerance.max_differing_pixels
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.sizes_match() {
+            return write!(
+                f,
+                "size is {}x{}, expected {}x{}",
+                self.actual_size.width, self.actual_size.height, self.expected_size.width, self.expected_size.height
+            );
+        }
+        write!(
+            f,
+            "{} pixels differ by more than {} (at most {} allowed), largest channel difference {}",
+            self.differing_pixels,
+            self.tolerance.per_channel,
+            self.tolerance.max_differing_pixels,
+            self.max_channel_difference
+        )
+    }
+}
+
+fn image_size(image: &Image) -> Size {
+    Size::new(image.width() as i32, image.height() as i32)
+}
+
+fn channel_difference(a: Rgba, b: Rgba) -> u8 {
+    [a.r.abs_diff(b.r), a.g.abs_diff(b.g), a.b.abs_diff(b.b), a.a.abs_diff(b.a)].into_iter().max().unwrap()
+}
+
+pub fn compare(actual: &Image, expected: &Image, tolerance: Tolerance) -> Comparison {
+    let mut comparison = Comparison {
+        actual_size: image_size(actual),
+        expected_size: image_size(expected),
+        differing_pixels: 0,
+        max_channel_difference: 0,
+        tolerance,
+    };
+    if comparison.sizes_match() {
+        for (&a, &b) in actual.pixels().iter().zip(expected.pixels()) {
+            let difference = channel_difference(a, b);
+            comparison.max_channel_difference = comparison.max_channel_difference.max(difference);
+            if difference > tolerance.per_channel {
+                comparison.differing_pixels += 1;
+            }
+        }
+    }
+    comparison
+}
+
+/// Pixels outside the tolerance in red, the rest a faded gray version of `expected`.
+///
+/// The diff covers both images, where only one of them has a pixel it counts as differing.
+pub fn diff_image(actual: &Image, expected: &Image, tolerance: Tolerance) -> Image {
+    let width = actual.width().max(expected.width());
+    let height = actual.height().max(expected.height());
+    Image::from_fn(width, height, |x, y| match (actual.get(x, y), expected.get(x, y)) {
+        (Some(a), Some(b)) if channel_difference(a, b) <= tolerance.per_channel => {
+            let gray = ((b.r as u32 * 3 + b.g as u32 * 6 + b.b as u32) / 10) as u8;
+            let faded = 192 + gray / 4;
+            Rgba::opaque(faded, faded, faded)
+        }
+        _ => Rgba::opaque(255, 0, 0),
+    })
+}
+
+/// Why a snapshot did not match.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// There is no reference yet, run again in bless mode to create it.
+    MissingReference(PathBuf),
+    /// The reference exists but is not a bitmap we can read.
+    UnreadableReference(PathBuf),
+    Mismatch {
+        comparison: Comparison,
+        actual: PathBuf,
+        diff: PathBuf,
+    },
+    Paint(Win32Error),
+    Io(io::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::MissingReference(path) => write!(
+                f,
+                "no reference image at {}, run with {}=1 to create it",
+                path.display(),
+                BLESS_VAR
+            ),
+            SnapshotError::UnreadableReference(path) => {
+                write!(f, "{} is not an uncompressed 24 or 32 bit bitmap", path.display())
+            }
+            SnapshotError::Mismatch { comparison, actual, diff } => write!(
+                f,
+                "{}, see {} and {}, or run with {}=1 to accept the new output",
+                comparison,
+                actual.display(),
+                diff.display(),
+                BLESS_VAR
+            ),
+            SnapshotError::Paint(e) => write!(f, "painting failed: {}", e),
+            SnapshotError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+/// Whether a check compared against the reference or replaced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Matched(Comparison),
+    Blessed,
+}
+
+/// A directory of reference images and the settings used to compare against them.
+#[derive(Debug, Clone)]
+pub struct Snapshots {
+    directory: PathBuf,
+    tolerance: Tolerance,
+    palette: Palette,
+    bless: bool,
+}
+
+impl Snapshots {
+    /// Exact comparisons with the light palette, bless mode comes from [`BLESS_VAR`].
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        let bless = std::env::var_os(BLESS_VAR).is_some_and(|value| !value.is_empty() && value != "0");
+        Self {
+            directory: directory.into(),
+            tolerance: Tolerance::EXACT,
+            palette: Palette::light(),
+            bless,
+        }
+    }
+
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// The palette system colors are painted with.
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Overrides the environment variable.
+    pub fn bless(mut self, bless: bool) -> Self {
+        self.bless = bless;
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn path(&self, name: &str, suffix: &str) -> PathBuf {
+        self.directory.join(format!("{}{}", name, suffix))
+    }
+
+    /// Compares `image` to the reference called `name`, or stores it as the reference when
+    /// blessing. Leftover actual and diff images from an earlier failure are removed on success.
+    pub fn check(&self, name: &str, image: &Image) -> Result<Outcome, SnapshotError> {
+        let reference = self.path(name, ".bmp");
+        let actual = self.path(name, ".actual.png");
+        let diff = self.path(name, ".diff.png");
+        let outcome = if self.bless {
+            std::fs::create_dir_all(&self.directory)?;
+            image.save(&reference)?;
+            Outcome::Blessed
+        } else {
+            let bytes = match std::fs::read(&reference) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    return Err(SnapshotError::MissingReference(reference))
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let expected = bmp::decode(&bytes).ok_or(SnapshotError::UnreadableReference(reference))?;
+            let comparison = compare(image, &expected, self.tolerance);
+            if !comparison.passed() {
+                image.save(&actual)?;
+                diff_image(image, &expected, self.tolerance).save(&diff)?;
+                return Err(SnapshotError::Mismatch { comparison, actual, diff });
+            }
+            Outcome::Matched(comparison)
+        };
+        for leftover in [actual, diff] {
+            match std::fs::remove_file(leftover) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => (),
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Renders `paint` into a `size` image and checks it against the reference called `name`.
+    pub fn check_paint(
+        &self,
+        name: &str,
+        size: Size,
+        paint: impl FnOnce(&mut ImageCanvas<'_>) -> Result<(), Win32Error>,
+    ) -> Result<Outcome, SnapshotError> {
+        let image = render(size, self.palette.clone(), paint).map_err(SnapshotError::Paint)?;
+        self.check(name, &image)
+    }
+
+    /// Like [`Snapshots::check`], for use in tests.
+    ///
+    /// # Panics
+    /// If the image does not match.
+    #[track_caller]
+    pub fn assert_matches(&self, name: &str, image: &Image) {
+        if let Err(e) = self.check(name, image) {
+            panic!("snapshot `{}` failed: {}", name, e);
+        }
+    }
+
+    /// Like [`Snapshots::check_paint`], for use in tests.
+    ///
+    /// # Panics
+    /// If the rendered image does not match.
+    #[track_caller]
+    pub fn assert_paint(
+        &self,
+        name: &str,
+        size: Size,
+        paint: impl FnOnce(&mut ImageCanvas<'_>) -> Result<(), Win32Error>,
+    ) {
+        if let Err(e) = self.check_paint(name, size, paint) {
+            panic!("snapshot `{}` failed: {}", name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("triangle_snapshot_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn gradient() -> Image {
+        Image::from_fn(8, 4, |x, y| Rgba::opaque(x as u8 * 30, y as u8 * 60, 100))
+    }
+
+    #[test]
+    fn tolerance_allows_small_differences() {
+        let expected = gradient();
+        let mut actual = expected.clone();
+        actual.set(0, 0, Rgba::opaque(2, 0, 100));
+        actual.set(1, 0, Rgba::opaque(30, 0, 140));
+        let exact = compare(&actual, &expected, Tolerance::EXACT);
+        assert_eq!((exact.differing_pixels, exact.max_channel_difference), (2, 40));
+        assert!(!exact.passed());
+        assert_eq!(compare(&actual, &expected, Tolerance::new(2, 0)).differing_pixels, 1);
+        assert!(compare(&actual, &expected, Tolerance::new(2, 1)).passed());
+        assert!(compare(&actual, &expected, Tolerance::new(40, 0)).passed());
+        assert!(!compare(&Image::new(8, 5), &expected, Tolerance::new(255, 100)).passed());
+    }
+
+    #[test]
+    fn diff_marks_differing_pixels() {
+        let expected = gradient();
+        let mut actual = expected.clone();
+        actual.set(3, 2, Rgba::BLACK);
+        let diff = diff_image(&actual, &expected, Tolerance::EXACT);
+        let red = Rgba::opaque(255, 0, 0);
+        assert_eq!(diff.pixels().iter().filter(|&&p| p == red).count(), 1);
+        assert_eq!(diff.get(3, 2), Some(red));
+        assert_eq!(diff_image(&Image::new(9, 4), &expected, Tolerance::new(255, 0)).get(8, 0), Some(red));
+    }
+
+    #[test]
+    fn bless_then_check() {
+        let dir = temp_dir("bless");
+        let snapshots = Snapshots::new(&dir).bless(false);
+        assert!(matches!(snapshots.check("gradient", &gradient()), Err(SnapshotError::MissingReference(_))));
+        assert_eq!(snapshots.clone().bless(true).check("gradient", &gradient()).unwrap(), Outcome::Blessed);
+        assert!(matches!(snapshots.check("gradient", &gradient()), Ok(Outcome::Matched(_))));
+
+        let mut changed = gradient();
+        changed.set(0, 0, Rgba::WHITE);
+        match snapshots.check("gradient", &changed) {
+            Err(SnapshotError::Mismatch { comparison, actual, diff }) => {
+                assert_eq!(comparison.differing_pixels, 1);
+                assert!(actual.exists() && diff.exists());
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+        snapshots.clone().tolerance(Tolerance::new(0, 1)).assert_matches("gradient", &changed);
+        assert!(!dir.join("gradient.diff.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,438 @@
+//! Typed `WS_*`, `WS_EX_*` and `CS_*` flags.
+//!
+//! The names drop the prefix, so `WS_OVERLAPPEDWINDOW` is [`WindowStyle::OVERLAPPEDWINDOW`]. The
+//! Win32 headers give some bits two names (`WS_MINIMIZEBOX` is `WS_GROUP` on a control), those
+//! are there as well but `Debug` only prints the first one. `Debug` also prefers the combined
+//! names, so the default window style prints as `WindowStyle(OVERLAPPEDWINDOW)`.
+//!
+//! Windows accepts any bits and quietly does something odd with nonsense like a child popup, so
+//! [`validate`] checks for the combinations the documentation rules out.
+
+use core::fmt;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Sub, SubAssign};
+
+macro_rules! flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $( $(#[$flag_meta:meta])* const $flag:ident = $value:expr; )*
+        }
+        aliases {
+            $( $(#[$alias_meta:meta])* const $alias:ident = $alias_value:expr; )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name(u32);
+
+        impl $name {
+            $( $(#[$flag_meta])* pub const $flag: Self = Self($value); )*
+            $( $(#[$alias_meta])* pub const $alias: Self = Self($alias_value); )*
+
+            /// What `Debug` prints, in order. The combined flags come first.
+            const NAMES: &'static [(&'static str, u32)] = &[$( (stringify!($flag), $value), )*];
+
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Keeps every bit, including ones without a name.
+            pub const fn from_bits(bits: u32) -> Self {
+                Self(bits)
+            }
+
+            pub const fn bits(self) -> u32 {
+                self.0
+            }
+
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether all bits of `other` are set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Whether any bit of `other` is set.
+            pub const fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            pub const fn union(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+
+            pub const fn difference(self, other: Self) -> Self {
+                Self(self.0 & !other.0)
+            }
+
+            pub fn insert(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+
+            pub fn remove(&mut self, other: Self) {
+                self.0 &= !other.0;
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = Self;
+            fn bitor(self, other: Self) -> Self {
+                self.union(other)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: Self) {
+                self.insert(other);
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = Self;
+            fn bitand(self, other: Self) -> Self {
+                Self(self.0 & other.0)
+            }
+        }
+
+        impl BitAndAssign for $name {
+            fn bitand_assign(&mut self, other: Self) {
+                self.0 &= other.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, other: Self) -> Self {
+                self.difference(other)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: Self) {
+                self.remove(other);
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}(", stringify!($name))?;
+                write_names(f, Self::NAMES, self.0)?;
+                write!(f, ")")
+            }
+        }
+    };
+}
+
+/// Writes the names in `bits` separated by `|`, taking each bit only once and ending with the
+/// bits without a name in hex.
+fn write_names(f: &mut fmt::Formatter, names: &[(&str, u32)], bits: u32) -> fmt::Result {
+    if bits == 0 {
+        return write!(f, "0x0");
+    }
+    let mut rest = bits;
+    let mut first = true;
+    let mut separate = |f: &mut fmt::Formatter| {
+        let separator = if first { "" } else { " | " };
+        first = false;
+        write!(f, "{}", separator)
+    };
+    for &(name, value) in names {
+        if value != 0 && rest & value == value {
+            separate(f)?;
+            write!(f, "{}", name)?;
+            rest &= !value;
+        }
+    }
+    if rest != 0 {
+        separate(f)?;
+        write!(f, "{:#x}", rest)?;
+    }
+    Ok(())
+}
+
+flags! {
+    /// [Window styles](https://docs.microsoft.com/en-us/windows/win32/winmsg/window-styles), the
+    /// `dwStyle` of `CreateWindowExW`.
+    pub struct WindowStyle {
+        /// A caption, sizing border, window menu and both boxes, what most windows use.
+        const OVERLAPPEDWINDOW = 0x00C0_0000 | 0x0008_0000 | 0x0004_0000 | 0x0002_0000 | 0x0001_0000;
+        /// A title bar, which includes a border.
+        const CAPTION = 0x00C0_0000;
+        /// A popup with a border and window menu, which only shows along with `CAPTION`.
+        const POPUPWINDOW = 0x8000_0000 | 0x0080_0000 | 0x0008_0000;
+        /// A top-level window without the frame of an overlapped one.
+        const POPUP = 0x8000_0000;
+        /// Lives inside its parent, can't have a menu bar.
+        const CHILD = 0x4000_0000;
+        /// Starts out minimized.
+        const MINIMIZE = 0x2000_0000;
+        const VISIBLE = 0x1000_0000;
+        /// Gets no mouse or keyboard input.
+        const DISABLED = 0x0800_0000;
+        /// Doesn't draw over the sibling windows it overlaps.
+        const CLIPSIBLINGS = 0x0400_0000;
+        /// Doesn't draw over its children, OpenGL windows need this.
+        const CLIPCHILDREN = 0x0200_0000;
+        /// Starts out maximized.
+        const MAXIMIZE = 0x0100_0000;
+        /// A thin border.
+        const BORDER = 0x0080_0000;
+        /// The border of a dialog box, a window with it can't have a title bar.
+        const DLGFRAME = 0x0040_0000;
+        const VSCROLL = 0x0020_0000;
+        const HSCROLL = 0x0010_0000;
+        /// The window menu in the title bar, needed for the boxes.
+        const SYSMENU = 0x0008_0000;
+        /// A sizing border.
+        const THICKFRAME = 0x0004_0000;
+        const MINIMIZEBOX = 0x0002_0000;
+        const MAXIMIZEBOX = 0x0001_0000;
+    }
+    aliases {
+        /// Nothing set, a window with a title bar and border.
+        const OVERLAPPED = 0;
+        const TILED = 0;
+        const TILEDWINDOW = Self::OVERLAPPEDWINDOW.0;
+        const CHILDWINDOW = Self::CHILD.0;
+        const ICONIC = Self::MINIMIZE.0;
+        const SIZEBOX = Self::THICKFRAME.0;
+        /// The first control of a group, the same bit as `MINIMIZEBOX`.
+        const GROUP = Self::MINIMIZEBOX.0;
+        /// A control Tab stops at, the same bit as `MAXIMIZEBOX`.
+        const TABSTOP = Self::MAXIMIZEBOX.0;
+    }
+}
+
+flags! {
+    /// [Extended window styles](https://docs.microsoft.com/en-us/windows/win32/winmsg/extended-window-styles),
+    /// the `dwExStyle` of `CreateWindowExW`.
+    pub struct WindowExStyle {
+        /// `WINDOWEDGE` and `CLIENTEDGE`.
+        const OVERLAPPEDWINDOW = 0x0000_0100 | 0x0000_0200;
+        /// A topmost tool window with a raised edge.
+        const PALETTEWINDOW = 0x0000_0100 | 0x0000_0080 | 0x0000_0008;
+        /// A double border, as dialog boxes have.
+        const DLGMODALFRAME = 0x0000_0001;
+        /// A child that doesn't send `WM_PARENTNOTIFY`.
+        const NOPARENTNOTIFY = 0x0000_0004;
+        /// Stays above all windows that aren't topmost.
+        const TOPMOST = 0x0000_0008;
+        /// Gets `WM_DROPFILES`, see [`FilesDropped`](crate::drag_drop::FilesDropped).
+        const ACCEPTFILES = 0x0000_0010;
+        /// Painted after its siblings, so they show through.
+        const TRANSPARENT = 0x0000_0020;
+        const MDICHILD = 0x0000_0040;
+        /// A floating toolbar, not in the taskbar or Alt+Tab.
+        const TOOLWINDOW = 0x0000_0080;
+        /// A raised border.
+        const WINDOWEDGE = 0x0000_0100;
+        /// A sunken border.
+        const CLIENTEDGE = 0x0000_0200;
+        /// A question mark in the title bar, which doesn't go with the boxes.
+        const CONTEXTHELP = 0x0000_0400;
+        /// Right aligned, for right to left languages.
+        const RIGHT = 0x0000_1000;
+        /// Text read right to left.
+        const RTLREADING = 0x0000_2000;
+        /// The vertical scroll bar on the left.
+        const LEFTSCROLLBAR = 0x0000_4000;
+        /// Tab moves into its children, as in a dialog box.
+        const CONTROLPARENT = 0x0001_0000;
+        /// The three dimensional border of controls that take no input.
+        const STATICEDGE = 0x0002_0000;
+        /// Always in the taskbar.
+        const APPWINDOW = 0x0004_0000;
+        /// Gets its transparency from `SetLayeredWindowAttributes` or `UpdateLayeredWindow`.
+        const LAYERED = 0x0008_0000;
+        /// Children don't inherit the layout.
+        const NOINHERITLAYOUT = 0x0010_0000;
+        /// No redirection surface, for windows drawn entirely with DirectComposition.
+        const NOREDIRECTIONBITMAP = 0x0020_0000;
+        /// The origin is on the right.
+        const LAYOUTRTL = 0x0040_0000;
+        /// Children are painted bottom to top, double buffered.
+        const COMPOSITED = 0x0200_0000;
+        /// Clicking it doesn't make it the foreground window.
+        const NOACTIVATE = 0x0800_0000;
+    }
+    aliases {
+        const LEFT = 0;
+        const LTRREADING = 0;
+        const RIGHTSCROLLBAR = 0;
+    }
+}
+
+flags! {
+    /// [Class styles](https://docs.microsoft.com/en-us/windows/win32/winmsg/window-class-styles),
+    /// the `style` of `WNDCLASSEXW`.
+    pub struct ClassStyle {
+        /// Redraws everything when the height changes.
+        const VREDRAW = 0x0001;
+        /// Redraws everything when the width changes.
+        const HREDRAW = 0x0002;
+        /// Gets `WM_LBUTTONDBLCLK` and friends.
+        const DBLCLKS = 0x0008;
+        /// Every window gets its own device context, which OpenGL needs.
+        const OWNDC = 0x0020;
+        /// All windows of the class share one device context.
+        const CLASSDC = 0x0040;
+        /// Draws with the device context of the parent.
+        const PARENTDC = 0x0080;
+        /// No Close in the window menu.
+        const NOCLOSE = 0x0200;
+        /// Keeps what it covers as a bitmap, for short lived windows like menus.
+        const SAVEBITS = 0x0800;
+        const BYTEALIGNCLIENT = 0x1000;
+        const BYTEALIGNWINDOW = 0x2000;
+        /// Usable from any module of the process.
+        const GLOBALCLASS = 0x4000;
+        const IME = 0x0001_0000;
+        /// A shadow, for short lived windows like menus.
+        const DROPSHADOW = 0x0002_0000;
+    }
+    aliases {}
+}
+
+/// A combination of styles that Windows doesn't support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleConflict {
+    /// `WS_CHILD` with `WS_POPUP`.
+    ChildPopup,
+    /// `WS_MINIMIZE` with `WS_MAXIMIZE`.
+    MinimizedAndMaximized,
+    /// A top-level window with a minimize or maximize box but no `WS_SYSMENU`.
+    BoxWithoutSysMenu,
+    /// `WS_EX_CONTEXTHELP` with a minimize or maximize box.
+    ContextHelpWithBox,
+    /// More than one of `CS_OWNDC`, `CS_CLASSDC` and `CS_PARENTDC`.
+    DeviceContexts(ClassStyle),
+    /// `WS_EX_COMPOSITED` with `CS_OWNDC` or `CS_CLASSDC`.
+    CompositedSharedDc,
+}
+
+impl fmt::Display for StyleConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ChildPopup => write!(f, "a window can't be both a child and a popup"),
+            Self::MinimizedAndMaximized => write!(f, "a window can't start out minimized and maximized"),
+            Self::BoxWithoutSysMenu => write!(f, "the minimize and maximize boxes need the window menu"),
+            Self::ContextHelpWithBox => write!(f, "the help button can't go with the minimize and maximize boxes"),
+            Self::DeviceContexts(dcs) => write!(f, "a class can only have one kind of device context, not {:?}", dcs),
+            Self::CompositedSharedDc => write!(f, "a composited window can't have its own or a class device context"),
+        }
+    }
+}
+
+impl std::error::Error for StyleConflict {}
+
+const BOXES: WindowStyle = WindowStyle::MINIMIZEBOX.union(WindowStyle::MAXIMIZEBOX);
+
+impl WindowStyle {
+    /// Checks the combinations that only involve the window style.
+    pub fn validate(self) -> Result<Self, StyleConflict> {
+        if self.contains(Self::CHILD | Self::POPUP) {
+            return Err(StyleConflict::ChildPopup);
+        }
+        if self.contains(Self::MINIMIZE | Self::MAXIMIZE) {
+            return Err(StyleConflict::MinimizedAndMaximized);
+        }
+        // On a child these bits are `WS_GROUP` and `WS_TABSTOP`.
+        if !self.contains(Self::CHILD) && self.intersects(BOXES) && !self.contains(Self::SYSMENU) {
+            return Err(StyleConflict::BoxWithoutSysMenu);
+        }
+        Ok(self)
+    }
+}
+
+impl ClassStyle {
+    /// Checks the combinations that only involve the class style.
+    pub fn validate(self) -> Result<Self, StyleConflict> {
+        let dcs = self & (Self::OWNDC | Self::CLASSDC | Self::PARENTDC);
+        if dcs.bits().count_ones() > 1 {
+            return Err(StyleConflict::DeviceContexts(dcs));
+        }
+        Ok(self)
+    }
+}
+
+/// Checks everything a window is created with, the first conflict found is returned.
+pub fn validate(style: WindowStyle, ex_style: WindowExStyle, class_style: ClassStyle) -> Result<(), StyleConflict> {
+    style.validate()?;
+    class_style.validate()?;
+    if ex_style.contains(WindowExStyle::CONTEXTHELP) && !style.contains(WindowStyle::CHILD) && style.intersects(BOXES) {
+        return Err(StyleConflict::ContextHelpWithBox);
+    }
+    if ex_style.contains(WindowExStyle::COMPOSITED) && class_style.intersects(ClassStyle::OWNDC | ClassStyle::CLASSDC) {
+        return Err(StyleConflict::CompositedSharedDc);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators_and_constants() {
+        assert_eq!(WindowStyle::OVERLAPPEDWINDOW.bits(), 0x00CF_0000);
+        assert_eq!(WindowStyle::CAPTION, WindowStyle::BORDER | WindowStyle::DLGFRAME);
+        assert_eq!(WindowExStyle::PALETTEWINDOW.bits(), 0x188);
+        let mut style = WindowStyle::OVERLAPPEDWINDOW;
+        style -= WindowStyle::THICKFRAME | WindowStyle::MAXIMIZEBOX;
+        assert!(style.contains(WindowStyle::CAPTION | WindowStyle::SYSMENU));
+        assert!(!style.intersects(WindowStyle::SIZEBOX));
+        style |= WindowStyle::VISIBLE;
+        assert_eq!(style & WindowStyle::VISIBLE, WindowStyle::VISIBLE);
+        assert!(WindowStyle::empty().contains(WindowStyle::OVERLAPPED));
+    }
+
+    #[test]
+    fn debug_prints_names() {
+        assert_eq!(format!("{:?}", WindowStyle::OVERLAPPEDWINDOW), "WindowStyle(OVERLAPPEDWINDOW)");
+        assert_eq!(
+            format!("{:?}", WindowStyle::OVERLAPPEDWINDOW | WindowStyle::VISIBLE | WindowStyle::CLIPCHILDREN),
+            "WindowStyle(OVERLAPPEDWINDOW | VISIBLE | CLIPCHILDREN)"
+        );
+        assert_eq!(
+            format!("{:?}", WindowStyle::CHILD | WindowStyle::TABSTOP | WindowStyle::BORDER),
+            "WindowStyle(CHILD | BORDER | MAXIMIZEBOX)"
+        );
+        assert_eq!(format!("{:?}", ClassStyle::OWNDC | ClassStyle::from_bits(0x4)), "ClassStyle(OWNDC | 0x4)");
+        assert_eq!(format!("{:?}", WindowExStyle::empty()), "WindowExStyle(0x0)");
+    }
+
+    #[test]
+    fn conflicts() {
+        let none = WindowExStyle::empty();
+        let class = ClassStyle::OWNDC | ClassStyle::HREDRAW | ClassStyle::VREDRAW;
+        assert_eq!(validate(WindowStyle::OVERLAPPEDWINDOW | WindowStyle::VISIBLE, none, class), Ok(()));
+        assert_eq!(
+            (WindowStyle::CHILD | WindowStyle::POPUP).validate(),
+            Err(StyleConflict::ChildPopup)
+        );
+        assert_eq!(
+            (WindowStyle::OVERLAPPEDWINDOW | WindowStyle::MINIMIZE | WindowStyle::MAXIMIZE).validate(),
+            Err(StyleConflict::MinimizedAndMaximized)
+        );
+        assert_eq!(
+            (WindowStyle::CAPTION | WindowStyle::MINIMIZEBOX).validate(),
+            Err(StyleConflict::BoxWithoutSysMenu)
+        );
+        // The same bits on a control are fine.
+        assert!((WindowStyle::CHILD | WindowStyle::GROUP | WindowStyle::TABSTOP).validate().is_ok());
+        assert_eq!(
+            (ClassStyle::OWNDC | ClassStyle::CLASSDC).validate(),
+            Err(StyleConflict::DeviceContexts(ClassStyle::OWNDC | ClassStyle::CLASSDC))
+        );
+        assert_eq!(
+            validate(WindowStyle::OVERLAPPEDWINDOW, WindowExStyle::CONTEXTHELP, ClassStyle::empty()),
+            Err(StyleConflict::ContextHelpWithBox)
+        );
+        assert_eq!(
+            validate(WindowStyle::POPUP, WindowExStyle::COMPOSITED, class),
+            Err(StyleConflict::CompositedSharedDc)
+        );
+    }
+}
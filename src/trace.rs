@@ -0,0 +1,265 @@
+//! Spy++ style logging of the messages a window receives.
+//!
+//! Every message is logged when it arrives (`S`) and again with its result when the handler
+//! returns (`R`). Messages sent while another is still being handled are indented one level
+//! deeper, which makes re-entrant sends like `DestroyWindow` inside `WM_CLOSE` easy to follow:
+//!
+//! ```text
+//! <00007> 0x001a0b S WM_CLOSE wParam: 0x0 lParam: 0x0
+//! <00008> 0x001a0b   S WM_DESTROY wParam: 0x0 lParam: 0x0
+//! <00008> 0x001a0b   R WM_DESTROY lResult: 0
+//! <00007> 0x001a0b R WM_CLOSE lResult: 0
+//! ```
+
+use core::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+use crate::message::{MessageClass, MessageName, Params};
+use crate::record::{Payload, RecordedMessage};
+
+/// Which messages get logged.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Filter {
+    hidden_classes: HashSet<MessageClass>,
+    hidden_messages: HashSet<u32>,
+}
+
+impl Filter {
+    /// Logs every message.
+    pub fn everything() -> Self {
+        Self::default()
+    }
+
+    /// Logs only messages of the given classes.
+    pub fn only(classes: &[MessageClass]) -> Self {
+        let hidden_classes = MessageClass::ALL.into_iter().filter(|class| !classes.contains(class)).collect();
+        Self { hidden_classes, hidden_messages: HashSet::new() }
+    }
+
+    pub fn hide(mut self, class: MessageClass) -> Self {
+        self.hidden_classes.insert(class);
+        self
+    }
+
+    pub fn show(mut self, class: MessageClass) -> Self {
+        self.hidden_classes.remove(&class);
+        self
+    }
+
+    /// Hides a single noisy message, like `WM_NCHITTEST` or `WM_SETCURSOR`.
+    pub fn hide_message(mut self, message: u32) -> Self {
+        self.hidden_messages.insert(message);
+        self
+    }
+
+    pub fn allows(&self, message: u32) -> bool {
+        !self.hidden_messages.contains(&message) && !self.hidden_classes.contains(&MessageClass::of(message))
+    }
+}
+
+/// The name shown for a message, registered messages are looked up on Windows.
+pub fn display_name(message: u32) -> String {
+    match MessageName::of(message) {
+        #[cfg(windows)]
+        MessageName::Registered(id) => match registered_name(id) {
+            Some(name) => format!("Registered({:#06x} {:?})", id, name),
+            None => MessageName::Registered(id).to_string(),
+        },
+        name => name.to_string(),
+    }
+}
+
+#[cfg(windows)]
+fn registered_name(id: u32) -> Option<String> {
+    use crate::win32::{CInt, GetClipboardFormatNameW};
+    let mut buffer = [0_u16; 256];
+    let len = unsafe { GetClipboardFormatNameW(id, buffer.as_mut_ptr(), buffer.len() as CInt) };
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+type Output = Box<dyn FnMut(&str)>;
+
+/// Logs messages through an output function, see the module documentation for the format.
+pub struct Tracer {
+    filter: Filter,
+    output: RefCell<Output>,
+    depth: Cell<usize>,
+    sequence: Cell<u64>,
+}
+
+impl Tracer {
+    /// Hands every line to `output`.
+    pub fn new(output: impl FnMut(&str) + 'static) -> Self {
+        Self {
+            filter: Filter::everything(),
+            output: RefCell::new(Box::new(output)),
+            depth: Cell::new(0),
+            sequence: Cell::new(0),
+        }
+    }
+
+    /// Prints every line to standard error.
+    pub fn to_stderr() -> Self {
+        Self::new(|line| eprintln!("{}", line))
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// How many messages are being handled right now.
+    pub fn depth(&self) -> usize {
+        self.depth.get()
+    }
+
+    fn write(&self, line: &str) {
+        // A message sent from inside the output function itself is not logged.
+        if let Ok(mut output) = self.output.try_borrow_mut() {
+            output(line);
+        }
+    }
+
+    /// Logs `message`, calls `handle` to handle it and logs what that returned.
+    ///
+    /// Hidden messages still count towards the nesting depth of the messages they send.
+    pub fn trace(&self, message: &RecordedMessage, handle: impl FnOnce() -> isize) -> isize {
+        let sequence = self.sequence.get() + 1;
+        self.sequence.set(sequence);
+        let depth = self.depth.get();
+        let shown = self.filter.allows(message.message);
+        let prefix = format!("<{:05}> {:#08x} {}", sequence, message.hwnd, "  ".repeat(depth));
+        let name = display_name(message.message);
+        if shown {
+            self.write(&format!("{}S {} {}", prefix, name, describe(message)));
+        }
+        self.depth.set(depth + 1);
+        let result = handle();
+        self.depth.set(depth);
+        if shown {
+            self.write(&format!("{}R {} lResult: {}", prefix, name, result));
+        }
+        result
+    }
+}
+
+/// The decoded parameters and payload of a message.
+pub fn describe(message: &RecordedMessage) -> String {
+    let mut text = match message.params() {
+        Params::Raw { w_param, l_param } => format!("wParam: {:#x} lParam: {:#x}", w_param, l_param as usize),
+        params => format!("{:?}", params),
+    };
+    if message.payload != Payload::None {
+        text.push_str(&format!(" {:?}", message.payload));
+    }
+    text
+}
+
+#[cfg(windows)]
+pub use self::windows::Traced;
+
+#[cfg(windows)]
+mod windows {
+    use std::rc::Rc;
+
+    use super::Tracer;
+    use crate::record::capture;
+    use crate::win32::*;
+    use crate::window::WindowHandler;
+
+    /// A [`WindowHandler`] that logs every message and its result.
+    ///
+    /// Messages the inner handler leaves alone are passed to `DefWindowProcW` here, so their
+    /// result can be logged too. Share one tracer between the windows of a thread to see sends
+    /// between them nest.
+    pub struct Traced<H> {
+        handler: H,
+        tracer: Rc<Tracer>,
+    }
+
+    impl<H: WindowHandler> Traced<H> {
+        pub fn new(handler: H, tracer: Rc<Tracer>) -> Self {
+            Self { handler, tracer }
+        }
+    }
+
+    impl<H: WindowHandler> WindowHandler for Traced<H> {
+        unsafe fn handle(&self, hwnd: HWND, msg: UINT, w_param: WPARAM, l_param: LPARAM) -> Option<LRESULT> {
+            let message = capture(hwnd, msg, w_param, l_param);
+            Some(self.tracer.trace(&message, || {
+                self.handler
+                    .handle(hwnd, msg, w_param, l_param)
+                    .unwrap_or_else(|| DefWindowProcW(hwnd, msg, w_param, l_param))
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::message::{WM_CLOSE, WM_DESTROY, WM_MOUSEMOVE, WM_SETTEXT, WM_SIZE, WM_USER};
+
+    fn collecting() -> (Tracer, Rc<RefCell<Vec<String>>>) {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&lines);
+        (Tracer::new(move |line| sink.borrow_mut().push(line.to_owned())), lines)
+    }
+
+    fn message(id: u32, w_param: usize, l_param: isize) -> RecordedMessage {
+        RecordedMessage { hwnd: 0x1a0b, ..RecordedMessage::new(id, w_param, l_param) }
+    }
+
+    #[test]
+    fn nested_sends_are_indented() {
+        let (tracer, lines) = collecting();
+        let result = tracer.trace(&message(WM_CLOSE, 0, 0), || {
+            assert_eq!(tracer.depth(), 1);
+            tracer.trace(&message(WM_DESTROY, 0, 0), || 7);
+            0
+        });
+        assert_eq!(result, 0);
+        assert_eq!(tracer.depth(), 0);
+        assert_eq!(
+            *lines.borrow(),
+            [
+                "<00001> 0x001a0b S WM_CLOSE wParam: 0x0 lParam: 0x0",
+                "<00002> 0x001a0b   S WM_DESTROY wParam: 0x0 lParam: 0x0",
+                "<00002> 0x001a0b   R WM_DESTROY lResult: 7",
+                "<00001> 0x001a0b R WM_CLOSE lResult: 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn parameters_are_decoded() {
+        let (tracer, lines) = collecting();
+        tracer.trace(&message(WM_SIZE, 0, 0x0096_012c), || 0);
+        let text = message(WM_SETTEXT, 0, 0).with_payload(Payload::Text("hi".to_owned()));
+        tracer.trace(&RecordedMessage { hwnd: 0x1a0b, ..text }, || 1);
+        tracer.trace(&message(WM_USER + 2, 1, -1), || 0);
+        let lines = lines.borrow();
+        assert_eq!(lines[0], "<00001> 0x001a0b S WM_SIZE Size { kind: 0, width: 300, height: 150 }");
+        assert_eq!(lines[2], "<00002> 0x001a0b S WM_SETTEXT wParam: 0x0 lParam: 0x0 Text(\"hi\")");
+        assert_eq!(lines[4], "<00003> 0x001a0b S WM_USER+2 wParam: 0x1 lParam: 0xffffffffffffffff");
+    }
+
+    #[test]
+    fn filters_hide_classes_but_keep_depth() {
+        let (tracer, lines) = collecting();
+        let tracer = tracer.filter(Filter::everything().hide(MessageClass::Mouse).hide_message(WM_SIZE));
+        tracer.trace(&message(WM_MOUSEMOVE, 0, 0), || {
+            tracer.trace(&message(WM_SIZE, 0, 0), || 0);
+            tracer.trace(&message(WM_CLOSE, 0, 0), || 0)
+        });
+        assert_eq!(lines.borrow().len(), 2);
+        assert!(lines.borrow()[0].starts_with("<00003> 0x001a0b   S WM_CLOSE"));
+
+        let only = Filter::only(&[MessageClass::Keyboard, MessageClass::User]);
+        assert!(only.allows(0x0100) && only.allows(WM_USER + 9));
+        assert!(!only.allows(WM_CLOSE));
+        assert!(only.show(MessageClass::Window).allows(WM_CLOSE));
+    }
+}
@@ -0,0 +1,412 @@
+//! OpenGL contexts through WGL.
+//!
+//! The functions that pick a pixel format by attributes and create core profile contexts are
+//! extensions, and extensions can only be loaded while some context is current. So a hidden
+//! dummy window gets a plain `ChoosePixelFormat` format and a legacy `wglCreateContext` context
+//! just long enough to look them up. The real window then gets the format
+//! [`pixel_format::choose`](crate::pixel_format::choose) likes best and, when the driver has
+//! `WGL_ARB_create_context`, exactly the context version and profile that was asked for.
+//!
+//! A window can only ever have one pixel format, so give GL windows their own class with
+//! `CS_OWNDC` (see [`WindowBuilder::class_style`]).
+
+use core::ffi::{c_char, CStr};
+use core::fmt;
+use core::ptr::null_mut;
+
+use crate::pixel_format::{choose, has_extension, ContextRequest, PixelFormatInfo, PixelFormatRequest};
+use crate::style::ClassStyle;
+use crate::win32::*;
+use crate::window::WindowBuilder;
+
+pub const WGL_NUMBER_PIXEL_FORMATS_ARB: CInt = 0x2000;
+pub const WGL_DRAW_TO_WINDOW_ARB: CInt = 0x2001;
+pub const WGL_ACCELERATION_ARB: CInt = 0x2003;
+pub const WGL_SUPPORT_OPENGL_ARB: CInt = 0x2010;
+pub const WGL_DOUBLE_BUFFER_ARB: CInt = 0x2011;
+pub const WGL_PIXEL_TYPE_ARB: CInt = 0x2013;
+pub const WGL_RED_BITS_ARB: CInt = 0x2015;
+pub const WGL_GREEN_BITS_ARB: CInt = 0x2017;
+pub const WGL_BLUE_BITS_ARB: CInt = 0x2019;
+pub const WGL_ALPHA_BITS_ARB: CInt = 0x201B;
+pub const WGL_DEPTH_BITS_ARB: CInt = 0x2022;
+pub const WGL_STENCIL_BITS_ARB: CInt = 0x2023;
+pub const WGL_FULL_ACCELERATION_ARB: CInt = 0x2027;
+pub const WGL_TYPE_RGBA_ARB: CInt = 0x202B;
+pub const WGL_SAMPLES_ARB: CInt = 0x2042;
+pub const WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB: CInt = 0x20A9;
+
+/// [`wglGetExtensionsStringARB`](https://registry.khronos.org/OpenGL/extensions/ARB/WGL_ARB_extensions_string.txt)
+pub type WglGetExtensionsStringARB = unsafe extern "system" fn(hdc: HDC) -> *const c_char;
+/// [`wglGetPixelFormatAttribivARB`](https://registry.khronos.org/OpenGL/extensions/ARB/WGL_ARB_pixel_format.txt)
+pub type WglGetPixelFormatAttribivARB = unsafe extern "system" fn(
+    hdc: HDC,
+    pixel_format: CInt,
+    layer_plane: CInt,
+    attribute_count: UINT,
+    attributes: *const CInt,
+    values: *mut CInt,
+) -> BOOL;
+/// [`wglCreateContextAttribsARB`](https://registry.khronos.org/OpenGL/extensions/ARB/WGL_ARB_create_context.txt)
+pub type WglCreateContextAttribsARB =
+    unsafe extern "system" fn(hdc: HDC, share_context: HGLRC, attributes: *const CInt) -> HGLRC;
+/// [`wglSwapIntervalEXT`](https://registry.khronos.org/OpenGL/extensions/EXT/WGL_EXT_swap_control.txt)
+pub type WglSwapIntervalEXT = unsafe extern "system" fn(interval: CInt) -> BOOL;
+
+/// Why no context could be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlError {
+    Win32(Win32Error),
+    /// None of the window's pixel formats can do OpenGL with the requested buffering.
+    NoPixelFormat,
+    /// The driver lacks an extension the request needs.
+    MissingExtension(&'static str),
+}
+
+impl From<Win32Error> for GlError {
+    fn from(error: Win32Error) -> Self {
+        GlError::Win32(error)
+    }
+}
+
+impl fmt::Display for GlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlError::Win32(error) => write!(f, "{}", error),
+            GlError::NoPixelFormat => write!(f, "no pixel format supports OpenGL as requested"),
+            GlError::MissingExtension(name) => write!(f, "the OpenGL driver does not support {}", name),
+        }
+    }
+}
+
+impl std::error::Error for GlError {}
+
+/// Looks up an OpenGL or WGL extension function of the current context.
+///
+/// Some drivers return small integers instead of null for unknown names, those count as missing.
+pub fn get_proc_address(name: &CStr) -> Option<PROC> {
+    let proc = unsafe { wglGetProcAddress(name.as_ptr()) };
+    match proc as isize {
+        -1..=3 => None,
+        _ => Some(proc),
+    }
+}
+
+/// The WGL extension functions the driver offers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extensions {
+    pub get_pixel_format_attribiv: Option<WglGetPixelFormatAttribivARB>,
+    pub create_context_attribs: Option<WglCreateContextAttribsARB>,
+    pub swap_interval: Option<WglSwapIntervalEXT>,
+}
+
+/// The dummy window and whatever it got so far, destroyed in reverse order.
+struct Dummy {
+    hwnd: HWND,
+    hdc: HDC,
+    hglrc: HGLRC,
+}
+
+impl Drop for Dummy {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.hglrc.is_null() {
+                wglMakeCurrent(null_mut(), null_mut());
+                wglDeleteContext(self.hglrc);
+            }
+            if !self.hdc.is_null() {
+                ReleaseDC(self.hwnd, self.hdc);
+            }
+            DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+/// A plain double buffered 32 bit format through `ChoosePixelFormat`.
+unsafe fn set_legacy_pixel_format(hdc: HDC) -> Result<(), Win32Error> {
+    let mut pfd = PIXELFORMATDESCRIPTOR::new();
+    pfd.dw_flags = PFD_DRAW_TO_WINDOW | PFD_SUPPORT_OPENGL | PFD_DOUBLEBUFFER;
+    pfd.i_pixel_type = PFD_TYPE_RGBA;
+    pfd.c_color_bits = 32;
+    pfd.c_depth_bits = 24;
+    pfd.c_stencil_bits = 8;
+    pfd.i_layer_type = PFD_MAIN_PLANE;
+    let format = ChoosePixelFormat(hdc, &pfd);
+    if format == 0 {
+        return Err(get_last_error());
+    }
+    if SetPixelFormat(hdc, format, &pfd) == 0 {
+        return Err(get_last_error());
+    }
+    Ok(())
+}
+
+/// Loads the WGL extensions with a throwaway window and context.
+pub fn load_extensions() -> Result<Extensions, GlError> {
+    let window = WindowBuilder::new("triangle_from_scratch WGL dummy")
+        .class_style(ClassStyle::OWNDC)
+        .quit_on_destroy(false)
+        .build(|_, _, _, _| None)?;
+    let mut dummy = Dummy { hwnd: window.hwnd(), hdc: null_mut(), hglrc: null_mut() };
+    unsafe {
+        dummy.hdc = GetDC(dummy.hwnd);
+        if dummy.hdc.is_null() {
+            return Err(get_last_error().into());
+        }
+        set_legacy_pixel_format(dummy.hdc)?;
+        dummy.hglrc = wglCreateContext(dummy.hdc);
+        if dummy.hglrc.is_null() {
+            return Err(get_last_error().into());
+        }
+        if wglMakeCurrent(dummy.hdc, dummy.hglrc) == 0 {
+            return Err(get_last_error().into());
+        }
+        let Some(get_extensions_string) = get_proc_address(c"wglGetExtensionsStringARB") else {
+            return Ok(Extensions::default());
+        };
+        let get_extensions_string: WglGetExtensionsStringARB = core::mem::transmute(get_extensions_string);
+        let extensions = CStr::from_ptr(get_extensions_string(dummy.hdc)).to_string_lossy();
+        let load = |extension: &str, name: &CStr| {
+            if has_extension(&extensions, extension) {
+                get_proc_address(name)
+            } else {
+                None
+            }
+        };
+        Ok(Extensions {
+            get_pixel_format_attribiv: load("WGL_ARB_pixel_format", c"wglGetPixelFormatAttribivARB")
+                .map(|proc| core::mem::transmute::<PROC, WglGetPixelFormatAttribivARB>(proc)),
+            create_context_attribs: load("WGL_ARB_create_context", c"wglCreateContextAttribsARB")
+                .map(|proc| core::mem::transmute::<PROC, WglCreateContextAttribsARB>(proc)),
+            swap_interval: load("WGL_EXT_swap_control", c"wglSwapIntervalEXT")
+                .map(|proc| core::mem::transmute::<PROC, WglSwapIntervalEXT>(proc)),
+        })
+    }
+}
+
+/// Every pixel format of `hdc` according to `DescribePixelFormat`, which knows nothing about
+/// multisampling or sRGB.
+unsafe fn describe_pixel_formats(hdc: HDC) -> Result<Vec<PixelFormatInfo>, Win32Error> {
+    let size = core::mem::size_of::<PIXELFORMATDESCRIPTOR>() as UINT;
+    let count = DescribePixelFormat(hdc, 1, size, null_mut());
+    if count == 0 {
+        return Err(get_last_error());
+    }
+    let mut formats = Vec::with_capacity(count as usize);
+    for index in 1..=count {
+        let mut pfd = PIXELFORMATDESCRIPTOR::default();
+        if DescribePixelFormat(hdc, index, size, &mut pfd) == 0 {
+            return Err(get_last_error());
+        }
+        if pfd.i_pixel_type != PFD_TYPE_RGBA {
+            continue;
+        }
+        let generic = pfd.dw_flags & PFD_GENERIC_FORMAT != 0;
+        let generic_accelerated = pfd.dw_flags & PFD_GENERIC_ACCELERATED != 0;
+        formats.push(PixelFormatInfo {
+            index,
+            draw_to_window: pfd.dw_flags & PFD_DRAW_TO_WINDOW != 0,
+            support_opengl: pfd.dw_flags & PFD_SUPPORT_OPENGL != 0,
+            accelerated: !generic || generic_accelerated,
+            double_buffer: pfd.dw_flags & PFD_DOUBLEBUFFER != 0,
+            color_bits: pfd.c_red_bits + pfd.c_green_bits + pfd.c_blue_bits,
+            alpha_bits: pfd.c_alpha_bits,
+            depth_bits: pfd.c_depth_bits,
+            stencil_bits: pfd.c_stencil_bits,
+            samples: 0,
+            srgb: false,
+        });
+    }
+    Ok(formats)
+}
+
+/// Every pixel format of `hdc` through `wglGetPixelFormatAttribivARB`.
+unsafe fn query_pixel_formats(
+    hdc: HDC,
+    get_attribiv: WglGetPixelFormatAttribivARB,
+) -> Result<Vec<PixelFormatInfo>, Win32Error> {
+    let mut count = 0;
+    if get_attribiv(hdc, 0, 0, 1, &WGL_NUMBER_PIXEL_FORMATS_ARB, &mut count) == 0 {
+        return Err(get_last_error());
+    }
+    const ATTRIBUTES: [CInt; 12] = [
+        WGL_DRAW_TO_WINDOW_ARB,
+        WGL_SUPPORT_OPENGL_ARB,
+        WGL_ACCELERATION_ARB,
+        WGL_DOUBLE_BUFFER_ARB,
+        WGL_PIXEL_TYPE_ARB,
+        WGL_RED_BITS_ARB,
+        WGL_GREEN_BITS_ARB,
+        WGL_BLUE_BITS_ARB,
+        WGL_ALPHA_BITS_ARB,
+        WGL_DEPTH_BITS_ARB,
+        WGL_STENCIL_BITS_ARB,
+        WGL_SAMPLES_ARB,
+    ];
+    let mut formats = Vec::with_capacity(count.max(0) as usize);
+    for index in 1..=count {
+        let mut values = [0; ATTRIBUTES.len()];
+        let attributes = ATTRIBUTES.as_ptr();
+        if get_attribiv(hdc, index, 0, ATTRIBUTES.len() as UINT, attributes, values.as_mut_ptr()) == 0 {
+            return Err(get_last_error());
+        }
+        let [window, opengl, acceleration, double_buffer, pixel_type, red, green, blue, alpha, depth, stencil, samples] =
+            values;
+        if pixel_type != WGL_TYPE_RGBA_ARB {
+            continue;
+        }
+        // Drivers without `WGL_ARB_framebuffer_sRGB` fail the whole query if it is included.
+        let mut srgb = 0;
+        get_attribiv(hdc, index, 0, 1, &WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB, &mut srgb);
+        formats.push(PixelFormatInfo {
+            index,
+            draw_to_window: window != 0,
+            support_opengl: opengl != 0,
+            accelerated: acceleration == WGL_FULL_ACCELERATION_ARB,
+            double_buffer: double_buffer != 0,
+            color_bits: (red + green + blue) as u8,
+            alpha_bits: alpha as u8,
+            depth_bits: depth as u8,
+            stencil_bits: stencil as u8,
+            samples: samples as u8,
+            srgb: srgb != 0,
+        });
+    }
+    Ok(formats)
+}
+
+/// Picks and sets the best pixel format of `hdc` for `request`.
+///
+/// # Safety
+/// `hdc` must be a valid device context of a window that has no pixel format yet.
+pub unsafe fn set_pixel_format(
+    hdc: HDC,
+    request: &PixelFormatRequest,
+    extensions: &Extensions,
+) -> Result<PixelFormatInfo, GlError> {
+    let formats = match extensions.get_pixel_format_attribiv {
+        Some(get_attribiv) => query_pixel_formats(hdc, get_attribiv)?,
+        None => describe_pixel_formats(hdc)?,
+    };
+    let format = *choose(&formats, request).ok_or(GlError::NoPixelFormat)?;
+    let mut pfd = PIXELFORMATDESCRIPTOR::default();
+    if DescribePixelFormat(hdc, format.index, core::mem::size_of_val(&pfd) as UINT, &mut pfd) == 0 {
+        return Err(get_last_error().into());
+    }
+    if SetPixelFormat(hdc, format.index, &pfd) == 0 {
+        return Err(get_last_error().into());
+    }
+    Ok(format)
+}
+
+/// An OpenGL context drawing into a window.
+///
+/// The context is current on the thread that made it. Dropping it deletes the context but
+/// leaves the window alone, the window's pixel format cannot be undone anyway.
+#[derive(Debug)]
+pub struct GlContext {
+    hwnd: HWND,
+    hdc: HDC,
+    hglrc: HGLRC,
+    pixel_format: PixelFormatInfo,
+    extensions: Extensions,
+}
+
+impl GlContext {
+    /// Sets up `hwnd` for OpenGL and makes the new context current.
+    ///
+    /// Core profiles and versions above 2.1 need `WGL_ARB_create_context`, anything else falls
+    /// back to `wglCreateContext` on drivers without it. Without `WGL_EXT_swap_control` vsync is
+    /// left to the driver.
+    ///
+    /// # Safety
+    /// `hwnd` must be a window handle that stays valid for as long as the context lives.
+    pub unsafe fn new(hwnd: HWND, request: &ContextRequest) -> Result<Self, GlError> {
+        let extensions = load_extensions()?;
+        let hdc = unsafe { GetDC(hwnd) };
+        if hdc.is_null() {
+            return Err(get_last_error().into());
+        }
+        let mut context = GlContext { hwnd, hdc, hglrc: null_mut(), pixel_format: Default::default(), extensions };
+        unsafe {
+            context.pixel_format = set_pixel_format(hdc, &request.pixel_format, &extensions)?;
+            context.hglrc = match extensions.create_context_attribs {
+                Some(create_context_attribs) => {
+                    create_context_attribs(hdc, null_mut(), request.attributes().as_ptr())
+                }
+                None if request.version > (2, 1) => {
+                    return Err(GlError::MissingExtension("WGL_ARB_create_context"))
+                }
+                None => wglCreateContext(hdc),
+            };
+            if context.hglrc.is_null() {
+                return Err(get_last_error().into());
+            }
+        }
+        context.make_current()?;
+        if extensions.swap_interval.is_some() {
+            context.set_vsync(request.vsync)?;
+        }
+        Ok(context)
+    }
+
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    pub fn hdc(&self) -> HDC {
+        self.hdc
+    }
+
+    pub fn hglrc(&self) -> HGLRC {
+        self.hglrc
+    }
+
+    /// The pixel format the window ended up with.
+    pub fn pixel_format(&self) -> &PixelFormatInfo {
+        &self.pixel_format
+    }
+
+    /// See [`wglMakeCurrent`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wglmakecurrent)
+    pub fn make_current(&self) -> Result<(), Win32Error> {
+        if unsafe { wglMakeCurrent(self.hdc, self.hglrc) } != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+
+    /// Turns waiting for vertical blank on or off, the context must be current.
+    pub fn set_vsync(&self, vsync: bool) -> Result<(), GlError> {
+        let swap_interval =
+            self.extensions.swap_interval.ok_or(GlError::MissingExtension("WGL_EXT_swap_control"))?;
+        if unsafe { swap_interval(vsync as CInt) } != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error().into())
+        }
+    }
+
+    /// See [`SwapBuffers`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-swapbuffers)
+    pub fn swap_buffers(&self) -> Result<(), Win32Error> {
+        if unsafe { SwapBuffers(self.hdc) } != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+}
+
+impl Drop for GlContext {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.hglrc.is_null() {
+                if wglGetCurrentContext() == self.hglrc {
+                    wglMakeCurrent(null_mut(), null_mut());
+                }
+                wglDeleteContext(self.hglrc);
+            }
+            ReleaseDC(self.hwnd, self.hdc);
+        }
+    }
+}
@@ -38,21 +38,10 @@ pub type WNDPROC = Option<
 >;
 
 // Values defined by Windows for interactions with the OS
-pub const WS_OVERLAPPED: u32 = 0x00000000;
-pub const WS_CAPTION: u32 = 0x00C00000;
-pub const WS_SYSMENU: u32 = 0x00080000;
-pub const WS_THICKFRAME: u32 = 0x00040000;
-pub const WS_MINIMIZEBOX: u32 = 0x00020000;
-pub const WS_MAXIMIZEBOX: u32 = 0x00010000;
+// The `WS_*`, `WS_EX_*` and `CS_*` flags are typed, see `crate::style`.
 pub const CW_USEDEFAULT: CInt = 0x80000000_u32 as CInt;
-pub const WS_OVERLAPPEDWINDOW: u32 =
-    WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_THICKFRAME | WS_MINIMIZEBOX | WS_MAXIMIZEBOX;
 pub const COLOR_WINDOW: u32 = 5;
 pub const SW_SHOW: CInt = 5;
-#[allow(dead_code)]
-pub const MB_OKCANCEL: u32 = 1;
-#[allow(dead_code)]
-pub const IDOK: CInt = 1;
 pub const GWLP_USERDATA: CInt = -21;
 pub const IDC_ARROW: LPCWSTR = makeintresourcew(32512);
 pub const FORMAT_MESSAGE_ALLOCATE_BUFFER:DWORD = 0x00000100;
@@ -61,16 +50,71 @@ pub const FORMAT_MESSAGE_IGNORE_INSERTS:DWORD = 0x00000200;
 pub const GRADIENT_FILL_RECT_H: CUlong= 0x00000000;
 pub const GRADIENT_FILL_RECT_V: CUlong= 0x00000001;
 pub const GRADIENT_FILL_TRIANGLE: CUlong= 0x00000002;
+pub const PM_REMOVE: UINT = 0x0001;
+pub const BI_RGB: DWORD = 0;
+pub const DIB_RGB_COLORS: UINT = 0;
+pub const SRCCOPY: DWORD = 0x00CC0020;
+pub const COLORONCOLOR: CInt = 3;
+pub const BLACK_BRUSH: CInt = 4;
+pub const ERROR_INVALID_PARAMETER: DWORD = 87;
+pub const ERROR_INVALID_WINDOW_HANDLE: DWORD = 1400;
+pub const ERROR_CLASS_ALREADY_EXISTS: DWORD = 1410;
+pub const PS_SOLID: CInt = 0;
+pub const NULL_BRUSH: CInt = 5;
+pub const NULL_PEN: CInt = 8;
+pub const TRANSPARENT: CInt = 1;
+pub const PFD_TYPE_RGBA: BYTE = 0;
+pub const PFD_MAIN_PLANE: BYTE = 0;
+pub const PFD_DOUBLEBUFFER: DWORD = 0x00000001;
+pub const PFD_DRAW_TO_WINDOW: DWORD = 0x00000004;
+pub const PFD_SUPPORT_OPENGL: DWORD = 0x00000020;
+pub const PFD_GENERIC_FORMAT: DWORD = 0x00000040;
+pub const PFD_GENERIC_ACCELERATED: DWORD = 0x00001000;
+pub const CF_DIB: UINT = 8;
+pub const CF_UNICODETEXT: UINT = 13;
+pub const GMEM_MOVEABLE: UINT = 0x0002;
+pub const MF_ENABLED: UINT = 0x0000;
+pub const MF_STRING: UINT = 0x0000;
+pub const MF_UNCHECKED: UINT = 0x0000;
+pub const MF_BYCOMMAND: UINT = 0x0000;
+pub const MF_GRAYED: UINT = 0x0001;
+pub const MF_CHECKED: UINT = 0x0008;
+pub const MF_POPUP: UINT = 0x0010;
+pub const MF_SEPARATOR: UINT = 0x0800;
+pub const TPM_LEFTALIGN: UINT = 0x0000;
+pub const TPM_RIGHTBUTTON: UINT = 0x0002;
+pub const TPM_NONOTIFY: UINT = 0x0080;
+pub const TPM_RETURNCMD: UINT = 0x0100;
+pub const ICON_SMALL: WPARAM = 0;
+pub const ICON_BIG: WPARAM = 1;
+pub const ICON_RESOURCE_VERSION: DWORD = 0x00030000;
+pub const LR_DEFAULTCOLOR: UINT = 0x0000;
+pub const GWL_STYLE: CInt = -16;
+pub const GWL_EXSTYLE: CInt = -20;
+pub const HWND_TOP: HWND = core::ptr::null_mut();
+pub const SWP_NOSIZE: UINT = 0x0001;
+pub const SWP_NOMOVE: UINT = 0x0002;
+pub const SWP_NOZORDER: UINT = 0x0004;
+pub const SWP_FRAMECHANGED: UINT = 0x0020;
+pub const SWP_NOOWNERZORDER: UINT = 0x0200;
+pub const MONITOR_DEFAULTTOPRIMARY: DWORD = 0x0001;
+pub const MONITOR_DEFAULTTONEAREST: DWORD = 0x0002;
+pub const MONITORINFOF_PRIMARY: DWORD = 0x0001;
+pub const MDT_EFFECTIVE_DPI: CInt = 0;
+pub const INFINITE: DWORD = 0xFFFFFFFF;
+pub const WAIT_FAILED: DWORD = 0xFFFFFFFF;
+pub const QS_ALLINPUT: DWORD = 0x04FF;
+pub const MWMO_ALERTABLE: DWORD = 0x0002;
+pub const MWMO_INPUTAVAILABLE: DWORD = 0x0004;
 
 // Values used by the Window_Procedure to check for events we care about
-pub const WM_CLOSE: u32 = 0x0010;
-pub const WM_DESTROY: u32 = 0x0002;
-pub const WM_MOUSEMOVE: u32 = 0x0200;
-pub const WM_PAINT: u32 = 0x000F;
-pub const WM_NCCREATE: u32 = 0x0081;
-pub const WM_CREATE: u32 = 0x0001;
+pub use crate::message::{
+    WM_CLOSE, WM_CREATE, WM_DESTROY, WM_DROPFILES, WM_ERASEBKGND, WM_MOUSEMOVE, WM_NCCREATE, WM_NCDESTROY,
+    WM_PAINT, WM_QUIT, WM_SETICON, WM_SIZE,
+};
 
 /// The predefined cursor styles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IDCursor {
     /// Standard arrow and small hourglass
     AppStarting = 32650,
@@ -102,43 +146,10 @@ pub enum IDCursor {
     Wait = 32514,
 }
 
-/// See [`GetSysColor`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsyscolor)
-pub enum SysColor {
-    _3dDarkShadow = 21,
-    _3dLight = 22,
-    ActiveBorder = 10,
-    ActiveCaption = 2,
-    AppWorkspace = 12,
-    /// Button face, also "3D face" color.
-    ButtonFace = 15,
-    /// Button highlight, also "3D highlight" color.
-    ButtonHighlight = 20,
-    /// Button shadow, also "3D shadow" color.
-    ButtonShadow = 16,
-    ButtonText = 18,
-    CaptionText = 9,
-    /// Desktop background color
-    Desktop = 1,
-    GradientActiveCaption = 27,
-    GradientInactiveCaption = 28,
-    GrayText = 17,
-    Highlight = 13,
-    HighlightText = 14,
-    HotLight = 26,
-    InactiveBorder = 11,
-    InactiveCaption = 3,
-    InactiveCaptionText = 19,
-    InfoBackground = 24,
-    InfoText = 23,
-    Menu = 4,
-    MenuHighlight = 29,
-    MenuBar = 30,
-    MenuText = 7,
-    ScrollBar = 0,
-    Window = 5,
-    WindowFrame = 6,
-    WindowText = 8,
-  }
+pub use crate::palette::{Palette, Rgb, SysColor};
+use crate::dib::Orientation;
+use crate::geometry::{Point, Rect};
+pub use crate::message_box::{MessageBox, MessageBoxResult, MB_OKCANCEL, IDOK};
 
 // C structs converted into Rust Structs
 #[repr(C)]
@@ -162,6 +173,71 @@ impl Default for WNDCLASSW {
     }
 }
 
+/// [`WNDCLASSEXW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-wndclassexw)
+///
+/// `WNDCLASSW` plus a size and a separate small icon.
+#[repr(C)]
+pub struct WNDCLASSEXW {
+    pub cb_size: UINT,
+    pub style: UINT,
+    pub lpfn_wnd_proc: WNDPROC,
+    pub cb_cls_extra: CInt,
+    pub cb_wnd_extra: CInt,
+    pub h_instance: HINSTANCE,
+    pub h_icon: HICON,
+    pub h_cursor: HCURSOR,
+    pub hbr_background: HBRUSH,
+    pub lpsz_menu_name: LPCWSTR,
+    pub lpsz_class_name: LPCWSTR,
+    pub h_icon_sm: HICON,
+}
+
+impl Default for WNDCLASSEXW {
+    fn default() -> Self {
+        let mut wc: Self = unsafe { core::mem::zeroed() };
+        wc.cb_size = core::mem::size_of::<Self>() as UINT;
+        wc
+    }
+}
+
+/// [`WINDOWPLACEMENT`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-windowplacement)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct WINDOWPLACEMENT {
+    pub length: UINT,
+    pub flags: UINT,
+    pub show_cmd: UINT,
+    pub pt_min_position: POINT,
+    pub pt_max_position: POINT,
+    pub rc_normal_position: RECT,
+}
+
+impl Default for WINDOWPLACEMENT {
+    fn default() -> Self {
+        let mut placement: Self = unsafe { core::mem::zeroed() };
+        placement.length = core::mem::size_of::<Self>() as UINT;
+        placement
+    }
+}
+
+/// [`MONITORINFO`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-monitorinfo)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MONITORINFO {
+    pub cb_size: DWORD,
+    pub rc_monitor: RECT,
+    pub rc_work: RECT,
+    pub dw_flags: DWORD,
+}
+
+impl Default for MONITORINFO {
+    fn default() -> Self {
+        let mut info: Self = unsafe { core::mem::zeroed() };
+        info.cb_size = core::mem::size_of::<Self>() as DWORD;
+        info
+    }
+}
+
 #[repr(C)]
 pub struct MSG {
     pub hwnd: HWND,
@@ -183,13 +259,24 @@ pub struct PAINTSTRUCT {
     pub rgb_reserved: [BYTE; 32],
 }
 
+/// [`ACCEL`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-accel)
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ACCEL {
+    pub f_virt: BYTE,
+    pub key: WORD,
+    pub cmd: WORD,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct POINT {
     pub x: LONG,
     pub y: LONG,
 }
 
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RECT {
     pub left: LONG,
     pub top: LONG,
@@ -224,6 +311,57 @@ pub struct TRIVERTIX {
     pub alpha: COLOR16,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct WINDOWPOS {
+    pub hwnd: HWND,
+    pub hwnd_insert_after: HWND,
+    pub x: CInt,
+    pub y: CInt,
+    pub cx: CInt,
+    pub cy: CInt,
+    pub flags: UINT,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MINMAXINFO {
+    pub pt_reserved: POINT,
+    pub pt_max_size: POINT,
+    pub pt_max_position: POINT,
+    pub pt_min_track_size: POINT,
+    pub pt_max_track_size: POINT,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NCCALCSIZE_PARAMS {
+    pub rgrc: [RECT; 3],
+    pub lppos: *mut WINDOWPOS,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct STYLESTRUCT {
+    pub style_old: DWORD,
+    pub style_new: DWORD,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct COPYDATASTRUCT {
+    pub dw_data: ULongPtr,
+    pub cb_data: DWORD,
+    pub lp_data: PVOID,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SIZE {
+    pub cx: LONG,
+    pub cy: LONG,
+}
+
 #[repr(C)]
 pub struct GradientTriangle {
     pub vertex1: CUlong,
@@ -231,14 +369,120 @@ pub struct GradientTriangle {
     pub vertex3: CUlong,
 }
 
-#[derive(Debug)]
-#[repr(transparent)]
-pub struct Win32Error(pub DWORD);
-impl std::error::Error for Win32Error {}
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BITMAPINFOHEADER {
+    pub bi_size: DWORD,
+    pub bi_width: LONG,
+    pub bi_height: LONG,
+    pub bi_planes: WORD,
+    pub bi_bit_count: WORD,
+    pub bi_compression: DWORD,
+    pub bi_size_image: DWORD,
+    pub bi_x_pels_per_meter: LONG,
+    pub bi_y_pels_per_meter: LONG,
+    pub bi_clr_used: DWORD,
+    pub bi_clr_important: DWORD,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RGBQUAD {
+    pub rgb_blue: BYTE,
+    pub rgb_green: BYTE,
+    pub rgb_red: BYTE,
+    pub rgb_reserved: BYTE,
+}
+
+/// [`PIXELFORMATDESCRIPTOR`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/ns-wingdi-pixelformatdescriptor)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PIXELFORMATDESCRIPTOR {
+    pub n_size: WORD,
+    pub n_version: WORD,
+    pub dw_flags: DWORD,
+    pub i_pixel_type: BYTE,
+    pub c_color_bits: BYTE,
+    pub c_red_bits: BYTE,
+    pub c_red_shift: BYTE,
+    pub c_green_bits: BYTE,
+    pub c_green_shift: BYTE,
+    pub c_blue_bits: BYTE,
+    pub c_blue_shift: BYTE,
+    pub c_alpha_bits: BYTE,
+    pub c_alpha_shift: BYTE,
+    pub c_accum_bits: BYTE,
+    pub c_accum_red_bits: BYTE,
+    pub c_accum_green_bits: BYTE,
+    pub c_accum_blue_bits: BYTE,
+    pub c_accum_alpha_bits: BYTE,
+    pub c_depth_bits: BYTE,
+    pub c_stencil_bits: BYTE,
+    pub c_aux_buffers: BYTE,
+    pub i_layer_type: BYTE,
+    pub b_reserved: BYTE,
+    pub dw_layer_mask: DWORD,
+    pub dw_visible_mask: DWORD,
+    pub dw_damage_mask: DWORD,
+}
+
+impl PIXELFORMATDESCRIPTOR {
+    /// A zeroed descriptor with the size and version filled in.
+    pub fn new() -> Self {
+        Self { n_size: core::mem::size_of::<Self>() as WORD, n_version: 1, ..Self::default() }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BITMAPINFO {
+    pub bmi_header: BITMAPINFOHEADER,
+    pub bmi_colors: [RGBQUAD; 1],
+}
+
+impl BITMAPINFO {
+    /// Describes an uncompressed bitmap of 24 or 32 bits per pixel.
+    pub fn new(width: u32, height: u32, bits_per_pixel: u16, orientation: Orientation) -> Self {
+        let mut info = BITMAPINFO::default();
+        info.bmi_header.bi_size = core::mem::size_of::<BITMAPINFOHEADER>() as DWORD;
+        info.bmi_header.bi_width = width as LONG;
+        info.bmi_header.bi_height = orientation.header_height(height);
+        info.bmi_header.bi_planes = 1;
+        info.bmi_header.bi_bit_count = bits_per_pixel;
+        info.bmi_header.bi_compression = BI_RGB;
+        info
+    }
+}
+
+impl From<RECT> for Rect {
+    fn from(rect: RECT) -> Self {
+        Rect::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+impl From<Rect> for RECT {
+    fn from(rect: Rect) -> Self {
+        RECT { left: rect.left, top: rect.top, right: rect.right, bottom: rect.bottom }
+    }
+}
+
+impl From<POINT> for Point {
+    fn from(point: POINT) -> Self {
+        Point::new(point.x, point.y)
+    }
+}
+
+impl From<Point> for POINT {
+    fn from(point: Point) -> Self {
+        POINT { x: point.x, y: point.y }
+    }
+}
+
+pub use crate::error::Win32Error;
 
 impl core::fmt::Display for Win32Error {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        if self.0 & (1 << 29) > 0 {
+        if self.is_application_error() {
             return write!(f, "Win32ApplicationError({})", self.0);
         }
         let dw_flags = FORMAT_MESSAGE_ALLOCATE_BUFFER | FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS;
@@ -299,6 +543,9 @@ pub type LPCVOID = *const core::ffi::c_void;
 pub type VaList = *mut CChar;
 pub type CChar = i8;
 pub type HLOCAL = HANDLE;
+pub type COLORREF = DWORD;
+pub type HGLOBAL = HANDLE;
+pub type SizeT = ULongPtr;
 #[link(name = "Kernel32")]
 extern "system" {
     /// [`GetModuleHandleW`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulehandlew)
@@ -314,6 +561,33 @@ extern "system" {
 
     /// [`LocalFree`](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-localfree)
     pub fn LocalFree(hMem: HLOCAL) -> HLOCAL;
+
+    /// [`LoadLibraryW`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-loadlibraryw)
+    pub fn LoadLibraryW(lpLibFileName: LPCWSTR) -> HMODULE;
+
+    /// [`FreeLibrary`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-freelibrary)
+    pub fn FreeLibrary(hLibModule: HMODULE) -> BOOL;
+
+    /// [`GetProcAddress`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getprocaddress)
+    pub fn GetProcAddress(hModule: HMODULE, lpProcName: LPCSTR) -> PROC;
+
+    /// [`GlobalAlloc`](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalalloc)
+    pub fn GlobalAlloc(uFlags: UINT, dwBytes: SizeT) -> HGLOBAL;
+
+    /// [`GlobalFree`](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalfree)
+    pub fn GlobalFree(hMem: HGLOBAL) -> HGLOBAL;
+
+    /// [`GlobalLock`](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globallock)
+    pub fn GlobalLock(hMem: HGLOBAL) -> LPVOID;
+
+    /// [`GlobalUnlock`](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalunlock)
+    pub fn GlobalUnlock(hMem: HGLOBAL) -> BOOL;
+
+    /// [`GlobalSize`](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalsize)
+    pub fn GlobalSize(hMem: HGLOBAL) -> SizeT;
+
+    /// [`GetCurrentThreadId`](https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-getcurrentthreadid)
+    pub fn GetCurrentThreadId() -> DWORD;
 }
 
 /// See [`GetModuleHandleW`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulehandlew)
@@ -343,12 +617,17 @@ pub type ATOM = WORD;
 pub type WORD = CUshort;
 pub type CUshort = u16;
 pub type HMENU = HANDLE;
+pub type HMONITOR = HANDLE;
+pub type MONITORENUMPROC = Option<unsafe extern "system" fn(HMONITOR, HDC, *mut RECT, LPARAM) -> BOOL>;
 pub type LPVOID = *mut core::ffi::c_void;
 #[link(name = "User32")]
 extern "system" {
     /// [`RegisterClassW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerclassw)
     pub fn RegisterClassW(lpWndClass: *const WNDCLASSW) -> ATOM;
 
+    /// [`RegisterClassExW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerclassexw)
+    pub fn RegisterClassExW(lpWndClass: *const WNDCLASSEXW) -> ATOM;
+
     /// [`CreateWindowExW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createwindowexw)
     pub fn CreateWindowExW(
         dwExStyle: DWORD,
@@ -373,14 +652,44 @@ extern "system" {
 
     /// [`EndPaint`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-endpaint)
     pub fn EndPaint(hWnd: HWND, lpPaint: *const PAINTSTRUCT) -> BOOL;
+
+    /// [`GetSysColor`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsyscolor)
+    pub fn GetSysColor(nIndex: CInt) -> COLORREF;
+
+    /// [`GetSysColorBrush`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsyscolorbrush)
+    pub fn GetSysColorBrush(nIndex: CInt) -> HBRUSH;
+
+    /// [`GetClipboardFormatNameW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getclipboardformatnamew)
+    ///
+    /// Registered window messages share the atom table with clipboard formats, so this also
+    /// returns the string a message was registered with.
+    pub fn GetClipboardFormatNameW(format: UINT, lpszFormatName: LPWSTR, cchMaxCount: CInt) -> CInt;
 }
 
 /// [`FillRect`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-fillrect)
-pub unsafe fn fill_rect_with_sys_color(hdc: HDC, rect: &RECT, color: SysColor) -> Result<(), ()> {
+pub unsafe fn fill_rect_with_sys_color(hdc: HDC, rect: &RECT, color: SysColor) -> Result<(), Win32Error> {
     if FillRect(hdc, rect, (color as u32 + 1) as HBRUSH) != 0 {
       Ok(())
     } else {
-      Err(())
+      Err(get_last_error())
+    }
+}
+
+/// See [`GetSysColor`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsyscolor)
+pub fn get_sys_color(color: SysColor) -> Rgb {
+    // Safety: The enum only allows valid color indexes.
+    Rgb::from_colorref(unsafe { GetSysColor(color as CInt) })
+}
+
+/// See [`GetSysColorBrush`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsyscolorbrush)
+///
+/// The brush is owned by the system and must not be deleted.
+pub fn get_sys_color_brush(color: SysColor) -> Option<HBRUSH> {
+    let hbrush = unsafe { GetSysColorBrush(color as CInt) };
+    if hbrush.is_null() {
+        None
+    } else {
+        Some(hbrush)
     }
 }
 
@@ -394,6 +703,16 @@ pub unsafe fn register_class(window_class: &WNDCLASSW) -> Result<ATOM, Win32Erro
     }
 }
 
+/// See [`RegisterClassExW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerclassexw)
+pub unsafe fn register_class_ex(window_class: &WNDCLASSEXW) -> Result<ATOM, Win32Error> {
+    let atom = RegisterClassExW(window_class);
+    if atom == 0 {
+        Err(get_last_error())
+    } else {
+        Ok(atom)
+    }
+}
+
 pub type BOOL = CInt;
 #[link(name = "User32")]
 extern "system" {
@@ -437,6 +756,196 @@ extern "system" {
 
     /// [`GetWindowLongPtrW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowlongptrw)
     pub fn GetWindowLongPtrW(hWnd: HWND, nIndex: CInt) -> LongPtr;
+
+    /// [`GetMessageTime`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagetime)
+    pub fn GetMessageTime() -> LONG;
+
+    /// [`GetMessagePos`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagepos)
+    pub fn GetMessagePos() -> DWORD;
+
+    /// [`PeekMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew)
+    pub fn PeekMessageW(
+        lpMsg: *mut MSG,
+        hWnd: HWND,
+        wMsgFilterMin: UINT,
+        wMsgFilterMax: UINT,
+        wRemoveMsg: UINT,
+    ) -> BOOL;
+
+    /// [`GetDC`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getdc)
+    pub fn GetDC(hWnd: HWND) -> HDC;
+
+    /// [`ReleaseDC`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-releasedc)
+    pub fn ReleaseDC(hWnd: HWND, hDC: HDC) -> CInt;
+
+    /// [`GetClientRect`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getclientrect)
+    pub fn GetClientRect(hWnd: HWND, lpRect: *mut RECT) -> BOOL;
+
+    /// [`AdjustWindowRectEx`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-adjustwindowrectex)
+    pub fn AdjustWindowRectEx(lpRect: *mut RECT, dwStyle: DWORD, bMenu: BOOL, dwExStyle: DWORD) -> BOOL;
+
+    /// [`InvalidateRect`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-invalidaterect)
+    pub fn InvalidateRect(hWnd: HWND, lpRect: *const RECT, bErase: BOOL) -> BOOL;
+
+    /// [`UpdateWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-updatewindow)
+    pub fn UpdateWindow(hWnd: HWND) -> BOOL;
+
+    /// [`OpenClipboard`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-openclipboard)
+    pub fn OpenClipboard(hWndNewOwner: HWND) -> BOOL;
+
+    /// [`CloseClipboard`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-closeclipboard)
+    pub fn CloseClipboard() -> BOOL;
+
+    /// [`EmptyClipboard`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-emptyclipboard)
+    pub fn EmptyClipboard() -> BOOL;
+
+    /// [`IsClipboardFormatAvailable`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-isclipboardformatavailable)
+    pub fn IsClipboardFormatAvailable(format: UINT) -> BOOL;
+
+    /// [`GetClipboardData`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getclipboarddata)
+    pub fn GetClipboardData(uFormat: UINT) -> HANDLE;
+
+    /// [`SetClipboardData`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setclipboarddata)
+    pub fn SetClipboardData(uFormat: UINT, hMem: HANDLE) -> HANDLE;
+
+    /// [`SendMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-sendmessagew)
+    pub fn SendMessageW(hWnd: HWND, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> LRESULT;
+
+    /// [`PostMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postmessagew)
+    pub fn PostMessageW(hWnd: HWND, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> BOOL;
+
+    /// [`RegisterWindowMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerwindowmessagew)
+    pub fn RegisterWindowMessageW(lpString: LPCWSTR) -> UINT;
+
+    /// [`PostThreadMessageW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postthreadmessagew)
+    pub fn PostThreadMessageW(idThread: DWORD, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> BOOL;
+
+    /// [`MsgWaitForMultipleObjectsEx`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-msgwaitformultipleobjectsex)
+    pub fn MsgWaitForMultipleObjectsEx(
+        nCount: DWORD,
+        pHandles: *const HANDLE,
+        dwMilliseconds: DWORD,
+        dwWakeMask: DWORD,
+        dwFlags: DWORD,
+    ) -> DWORD;
+
+    /// [`CreateMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createmenu)
+    pub fn CreateMenu() -> HMENU;
+
+    /// [`CreatePopupMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createpopupmenu)
+    pub fn CreatePopupMenu() -> HMENU;
+
+    /// [`DestroyMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroymenu)
+    pub fn DestroyMenu(hMenu: HMENU) -> BOOL;
+
+    /// [`AppendMenuW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-appendmenuw)
+    pub fn AppendMenuW(hMenu: HMENU, uFlags: UINT, uIDNewItem: UintPtr, lpNewItem: LPCWSTR) -> BOOL;
+
+    /// [`CheckMenuItem`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-checkmenuitem)
+    pub fn CheckMenuItem(hMenu: HMENU, uIDCheckItem: UINT, uCheck: UINT) -> DWORD;
+
+    /// [`CheckMenuRadioItem`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-checkmenuradioitem)
+    pub fn CheckMenuRadioItem(hmenu: HMENU, first: UINT, last: UINT, check: UINT, flags: UINT) -> BOOL;
+
+    /// [`GetMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmenu)
+    pub fn GetMenu(hWnd: HWND) -> HMENU;
+
+    /// [`SetMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setmenu)
+    pub fn SetMenu(hWnd: HWND, hMenu: HMENU) -> BOOL;
+
+    /// [`DrawMenuBar`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-drawmenubar)
+    pub fn DrawMenuBar(hWnd: HWND) -> BOOL;
+
+    /// [`TrackPopupMenu`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-trackpopupmenu)
+    pub fn TrackPopupMenu(
+        hMenu: HMENU,
+        uFlags: UINT,
+        x: CInt,
+        y: CInt,
+        nReserved: CInt,
+        hWnd: HWND,
+        prcRect: *const RECT,
+    ) -> BOOL;
+}
+
+#[link(name = "User32")]
+extern "system" {
+    /// [`CreateIconFromResourceEx`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createiconfromresourceex)
+    pub fn CreateIconFromResourceEx(
+        presbits: *const BYTE,
+        dwResSize: DWORD,
+        fIcon: BOOL,
+        dwVer: DWORD,
+        cxDesired: CInt,
+        cyDesired: CInt,
+        Flags: UINT,
+    ) -> HICON;
+
+    /// [`DestroyIcon`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroyicon)
+    pub fn DestroyIcon(hIcon: HICON) -> BOOL;
+
+    /// [`GetDpiForWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getdpiforwindow)
+    pub fn GetDpiForWindow(hwnd: HWND) -> UINT;
+
+    /// [`GetWindowRect`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowrect)
+    pub fn GetWindowRect(hWnd: HWND, lpRect: *mut RECT) -> BOOL;
+
+    /// [`GetWindowPlacement`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowplacement)
+    pub fn GetWindowPlacement(hWnd: HWND, lpwndpl: *mut WINDOWPLACEMENT) -> BOOL;
+
+    /// [`SetWindowPlacement`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowplacement)
+    pub fn SetWindowPlacement(hWnd: HWND, lpwndpl: *const WINDOWPLACEMENT) -> BOOL;
+
+    /// [`SetWindowPos`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowpos)
+    pub fn SetWindowPos(hWnd: HWND, hWndInsertAfter: HWND, X: CInt, Y: CInt, cx: CInt, cy: CInt, uFlags: UINT) -> BOOL;
+
+    /// [`EnumDisplayMonitors`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-enumdisplaymonitors)
+    pub fn EnumDisplayMonitors(hdc: HDC, lprcClip: *const RECT, lpfnEnum: MONITORENUMPROC, dwData: LPARAM) -> BOOL;
+
+    /// [`MonitorFromWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-monitorfromwindow)
+    pub fn MonitorFromWindow(hwnd: HWND, dwFlags: DWORD) -> HMONITOR;
+
+    /// [`MonitorFromPoint`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-monitorfrompoint)
+    pub fn MonitorFromPoint(pt: POINT, dwFlags: DWORD) -> HMONITOR;
+
+    /// [`GetMonitorInfoW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmonitorinfow)
+    pub fn GetMonitorInfoW(hMonitor: HMONITOR, lpmi: *mut MONITORINFO) -> BOOL;
+}
+
+pub type HRESULT = CLong;
+#[link(name = "Shcore")]
+extern "system" {
+    /// [`GetDpiForMonitor`](https://docs.microsoft.com/en-us/windows/win32/api/shellscalingapi/nf-shellscalingapi-getdpiformonitor)
+    pub fn GetDpiForMonitor(hmonitor: HMONITOR, dpiType: CInt, dpiX: *mut UINT, dpiY: *mut UINT) -> HRESULT;
+}
+
+pub type HACCEL = HANDLE;
+#[link(name = "User32")]
+extern "system" {
+    /// [`CreateAcceleratorTableW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createacceleratortablew)
+    pub fn CreateAcceleratorTableW(paccel: *const ACCEL, cAccel: CInt) -> HACCEL;
+
+    /// [`DestroyAcceleratorTable`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroyacceleratortable)
+    pub fn DestroyAcceleratorTable(hAccel: HACCEL) -> BOOL;
+
+    /// [`TranslateAcceleratorW`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-translateacceleratorw)
+    pub fn TranslateAcceleratorW(hWnd: HWND, hAccTable: HACCEL, lpMsg: *const MSG) -> CInt;
+}
+
+pub type HDROP = HANDLE;
+#[link(name = "Shell32")]
+extern "system" {
+    /// [`DragAcceptFiles`](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragacceptfiles)
+    pub fn DragAcceptFiles(hWnd: HWND, fAccept: BOOL);
+
+    /// [`DragQueryFileW`](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragqueryfilew)
+    pub fn DragQueryFileW(hDrop: HDROP, iFile: UINT, lpszFile: LPWSTR, cch: UINT) -> UINT;
+
+    /// [`DragQueryPoint`](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragquerypoint)
+    pub fn DragQueryPoint(hDrop: HDROP, ppt: *mut POINT) -> BOOL;
+
+    /// [`DragFinish`](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragfinish)
+    pub fn DragFinish(hDrop: HDROP);
 }
 
 type PGradientTriangle = *const c_void;
@@ -447,6 +956,157 @@ extern "system" {
     pub fn GradientFill(hdc: HDC, PVertex: PTRIVERTIX, NVertex: CUlong, PMesh: PGradientTriangle, NMesh: CUlong, UlMode: CUlong) -> BOOL;
 }
 
+pub type HGDIOBJ = HANDLE;
+pub type HPEN = HANDLE;
+pub type HBITMAP = HANDLE;
+#[link(name = "Gdi32")]
+extern "system" {
+    /// [`StretchDIBits`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-stretchdibits)
+    pub fn StretchDIBits(
+        hdc: HDC,
+        xDest: CInt,
+        yDest: CInt,
+        DestWidth: CInt,
+        DestHeight: CInt,
+        xSrc: CInt,
+        ySrc: CInt,
+        SrcWidth: CInt,
+        SrcHeight: CInt,
+        lpBits: LPCVOID,
+        lpbmi: *const BITMAPINFO,
+        iUsage: UINT,
+        rop: DWORD,
+    ) -> CInt;
+
+    /// [`SetStretchBltMode`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-setstretchbltmode)
+    pub fn SetStretchBltMode(hdc: HDC, mode: CInt) -> CInt;
+
+    /// [`GetStockObject`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-getstockobject)
+    pub fn GetStockObject(i: CInt) -> HGDIOBJ;
+
+    /// [`SelectObject`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-selectobject)
+    pub fn SelectObject(hdc: HDC, h: HGDIOBJ) -> HGDIOBJ;
+
+    /// [`DeleteObject`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-deleteobject)
+    pub fn DeleteObject(ho: HGDIOBJ) -> BOOL;
+
+    /// [`CreatePen`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createpen)
+    pub fn CreatePen(iStyle: CInt, cWidth: CInt, color: COLORREF) -> HPEN;
+
+    /// [`CreateSolidBrush`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createsolidbrush)
+    pub fn CreateSolidBrush(color: COLORREF) -> HBRUSH;
+
+    /// [`MoveToEx`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-movetoex)
+    pub fn MoveToEx(hdc: HDC, x: CInt, y: CInt, lppt: *mut POINT) -> BOOL;
+
+    /// [`LineTo`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-lineto)
+    pub fn LineTo(hdc: HDC, x: CInt, y: CInt) -> BOOL;
+
+    /// [`Rectangle`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-rectangle)
+    pub fn Rectangle(hdc: HDC, left: CInt, top: CInt, right: CInt, bottom: CInt) -> BOOL;
+
+    /// [`Ellipse`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-ellipse)
+    pub fn Ellipse(hdc: HDC, left: CInt, top: CInt, right: CInt, bottom: CInt) -> BOOL;
+
+    /// [`Polygon`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-polygon)
+    pub fn Polygon(hdc: HDC, apt: *const POINT, cpt: CInt) -> BOOL;
+
+    /// [`SetTextColor`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-settextcolor)
+    pub fn SetTextColor(hdc: HDC, color: COLORREF) -> COLORREF;
+
+    /// [`SetBkMode`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-setbkmode)
+    pub fn SetBkMode(hdc: HDC, mode: CInt) -> CInt;
+
+    /// [`GetTextExtentPoint32W`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-gettextextentpoint32w)
+    pub fn GetTextExtentPoint32W(hdc: HDC, lpString: LPCWSTR, c: CInt, psizl: *mut SIZE) -> BOOL;
+
+    /// [`CreateCompatibleDC`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createcompatibledc)
+    pub fn CreateCompatibleDC(hdc: HDC) -> HDC;
+
+    /// [`DeleteDC`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-deletedc)
+    pub fn DeleteDC(hdc: HDC) -> BOOL;
+
+    /// [`CreateCompatibleBitmap`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createcompatiblebitmap)
+    pub fn CreateCompatibleBitmap(hdc: HDC, cx: CInt, cy: CInt) -> HBITMAP;
+
+    /// [`BitBlt`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-bitblt)
+    pub fn BitBlt(hdc: HDC, x: CInt, y: CInt, cx: CInt, cy: CInt, hdcSrc: HDC, x1: CInt, y1: CInt, rop: DWORD) -> BOOL;
+
+    /// [`GetDIBits`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-getdibits)
+    pub fn GetDIBits(hdc: HDC, hbm: HBITMAP, start: UINT, cLines: UINT, lpvBits: PVOID, lpbmi: *mut BITMAPINFO, usage: UINT) -> CInt;
+
+    /// [`TextOutW`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-textoutw)
+    pub fn TextOutW(hdc: HDC, x: CInt, y: CInt, lpString: LPCWSTR, c: CInt) -> BOOL;
+
+    /// [`ChoosePixelFormat`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-choosepixelformat)
+    pub fn ChoosePixelFormat(hdc: HDC, ppfd: *const PIXELFORMATDESCRIPTOR) -> CInt;
+
+    /// [`DescribePixelFormat`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-describepixelformat)
+    pub fn DescribePixelFormat(hdc: HDC, iPixelFormat: CInt, nBytes: UINT, ppfd: *mut PIXELFORMATDESCRIPTOR) -> CInt;
+
+    /// [`SetPixelFormat`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-setpixelformat)
+    pub fn SetPixelFormat(hdc: HDC, format: CInt, ppfd: *const PIXELFORMATDESCRIPTOR) -> BOOL;
+
+    /// [`SwapBuffers`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-swapbuffers)
+    pub fn SwapBuffers(hdc: HDC) -> BOOL;
+}
+
+pub type HGLRC = HANDLE;
+pub type PROC = *const c_void;
+pub type LPCSTR = *const CChar;
+#[link(name = "Opengl32")]
+extern "system" {
+    /// [`wglCreateContext`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wglcreatecontext)
+    pub fn wglCreateContext(hdc: HDC) -> HGLRC;
+
+    /// [`wglDeleteContext`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wgldeletecontext)
+    pub fn wglDeleteContext(hglrc: HGLRC) -> BOOL;
+
+    /// [`wglMakeCurrent`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wglmakecurrent)
+    pub fn wglMakeCurrent(hdc: HDC, hglrc: HGLRC) -> BOOL;
+
+    /// [`wglGetCurrentContext`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wglgetcurrentcontext)
+    pub fn wglGetCurrentContext() -> HGLRC;
+
+    /// [`wglGetProcAddress`](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wglgetprocaddress)
+    pub fn wglGetProcAddress(name: LPCSTR) -> PROC;
+}
+
+/// See [`GetClientRect`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getclientrect)
+///
+/// # Safety
+/// `hwnd` must be a window handle, [`Window::client_rect`](crate::window::Window::client_rect)
+/// is the safe way.
+pub unsafe fn get_client_rect(hwnd: HWND) -> Result<Rect, Win32Error> {
+    let mut rect = RECT::default();
+    if GetClientRect(hwnd, &mut rect) != 0 {
+        Ok(rect.into())
+    } else {
+        Err(get_last_error())
+    }
+}
+
+/// Copies `bytes` into a new movable `GlobalAlloc` block, the kind the clipboard and `HDROP`s use.
+///
+/// The caller owns the block until it hands it over (e.g. to `SetClipboardData`).
+pub fn global_alloc_copy(bytes: &[u8]) -> Result<HGLOBAL, Win32Error> {
+    unsafe {
+        let memory = GlobalAlloc(GMEM_MOVEABLE, bytes.len().max(1));
+        if memory.is_null() {
+            return Err(get_last_error());
+        }
+        let data = GlobalLock(memory).cast::<u8>();
+        if data.is_null() {
+            let e = get_last_error();
+            GlobalFree(memory);
+            return Err(e);
+        }
+        data.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len());
+        GlobalUnlock(memory);
+        Ok(memory)
+    }
+}
+
 // Macro to automatically apply an impl, sets all values for the initialised type to 0
 macro_rules! unsafe_impl_default_zeroed {
     ($t:ty) => {
@@ -469,6 +1129,10 @@ unsafe_impl_default_zeroed!(MSG);
 unsafe_impl_default_zeroed!(PAINTSTRUCT);
 unsafe_impl_default_zeroed!(RECT);
 unsafe_impl_default_zeroed!(POINT);
+unsafe_impl_default_zeroed!(SIZE);
 unsafe_impl_default_zeroed!(CREATESTRUCTW);
 unsafe_impl_default_zeroed!(TRIVERTIX);
-unsafe_impl_default_zeroed!(GradientTriangle);
\ No newline at end of file
+unsafe_impl_default_zeroed!(GradientTriangle);
+unsafe_impl_default_zeroed!(BITMAPINFOHEADER);
+unsafe_impl_default_zeroed!(BITMAPINFO);
+unsafe_impl_default_zeroed!(PIXELFORMATDESCRIPTOR);
\ No newline at end of file
@@ -0,0 +1,565 @@
+//! Windows whose messages go to a Rust handler instead of a hand written `extern "system"`
+//! procedure, plus the message loops that drive them.
+//!
+//! The handler is boxed and passed through `CreateWindowExW`'s `lpParam`, moved into
+//! `GWLP_USERDATA` during `WM_NCCREATE` and freed again on `WM_NCDESTROY`, much like the boxed
+//! counter in `main.rs`.
+
+use core::cell::Cell;
+use core::ptr::{null, null_mut};
+
+use crate::accelerator::AcceleratorTable;
+use crate::executor::{Events, Executor, NextEvent};
+use crate::fullscreen::Fullscreen;
+use crate::geometry::{Rect, Size};
+use crate::icon::OwnedIcon;
+use crate::image::Image;
+use crate::monitor::{center, Monitor};
+use crate::panic_guard::{catch_panic, panic_pending, panic_result, resume_panic};
+use crate::style::{validate, ClassStyle, WindowExStyle, WindowStyle};
+use crate::win32::*;
+
+/// Receives the messages of a window.
+///
+/// Returning `None` passes the message on to `DefWindowProcW`. Handlers take `&self` because
+/// Windows happily sends a new message while an earlier one is still being handled (e.g. calling
+/// `DestroyWindow` from `WM_CLOSE` sends `WM_DESTROY`), so any state must use interior mutability.
+///
+/// A panicking handler doesn't unwind into Windows, the panic continues from the message loop
+/// (see [`panic_guard`](crate::panic_guard)).
+pub trait WindowHandler {
+    /// # Safety
+    /// The parameters must be exactly those the window procedure received, handlers may read
+    /// through `l_param` and call Windows with `hwnd`.
+    unsafe fn handle(&self, hwnd: HWND, msg: UINT, w_param: WPARAM, l_param: LPARAM) -> Option<LRESULT>;
+}
+
+impl<F> WindowHandler for F
+where
+    F: Fn(HWND, UINT, WPARAM, LPARAM) -> Option<LRESULT>,
+{
+    unsafe fn handle(&self, hwnd: HWND, msg: UINT, w_param: WPARAM, l_param: LPARAM) -> Option<LRESULT> {
+        self(hwnd, msg, w_param, l_param)
+    }
+}
+
+/// Everything the trampoline keeps for one window, owned by `GWLP_USERDATA`.
+struct WindowState {
+    handler: Box<dyn WindowHandler>,
+    quit_on_destroy: bool,
+    /// How many calls into the handler are on the stack right now.
+    depth: Cell<u32>,
+    /// Set by `WM_NCDESTROY`, the state is freed once the outermost call returns.
+    destroyed: Cell<bool>,
+    /// Taken out while switching, since that sends messages which may ask for it again.
+    fullscreen: Cell<Fullscreen>,
+    events: Events<WindowEvent>,
+}
+
+/// A message as seen by the window procedure, for tasks awaiting [`Window::next_message`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowEvent {
+    pub msg: UINT,
+    pub w_param: WPARAM,
+    pub l_param: LPARAM,
+}
+
+/// The window procedure of every window made by [`WindowBuilder`].
+unsafe extern "system" fn window_trampoline(
+    hwnd: HWND,
+    msg: UINT,
+    w_param: WPARAM,
+    l_param: LPARAM,
+) -> LRESULT {
+    if msg == WM_NCCREATE {
+        let createstruct: *mut CREATESTRUCTW = l_param as *mut _;
+        if !createstruct.is_null() {
+            let pending: *mut Option<Box<WindowState>> = (*createstruct).lp_create_params.cast();
+            if let Some(state) = (*pending).take() {
+                SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(state) as LongPtr);
+            }
+        }
+    }
+    // A few messages (like `WM_GETMINMAXINFO`) arrive before `WM_NCCREATE`.
+    let state = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowState;
+    if state.is_null() {
+        return DefWindowProcW(hwnd, msg, w_param, l_param);
+    }
+    let state_ref = &*state;
+    state_ref.depth.set(state_ref.depth.get() + 1);
+    // A panic can't unwind into Windows, it is resumed once the message loop gets control back.
+    // Until then the handler isn't trusted with more messages.
+    let handled = if panic_pending() {
+        None
+    } else {
+        catch_panic(Some(panic_result(msg)), || {
+            // This only wakes the tasks, they run once the message loop gets to them.
+            state_ref.events.emit(&WindowEvent { msg, w_param, l_param });
+            state_ref.handler.handle(hwnd, msg, w_param, l_param)
+        })
+    };
+    let result = handled.unwrap_or_else(|| DefWindowProcW(hwnd, msg, w_param, l_param));
+    match msg {
+        WM_DESTROY if state_ref.quit_on_destroy => PostQuitMessage(0),
+        WM_NCDESTROY => {
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0);
+            state_ref.destroyed.set(true);
+        }
+        _ => (),
+    }
+    leave(state);
+    result
+}
+
+/// Ends a call that counted itself in `depth`, freeing the state if the window was destroyed
+/// meanwhile and nobody else is using it.
+unsafe fn leave(state: *mut WindowState) {
+    let depth = (*state).depth.get() - 1;
+    (*state).depth.set(depth);
+    // `DestroyWindow` is often called from inside a handler, so an outer call may still be using
+    // the state when `WM_NCDESTROY` arrives.
+    if depth == 0 && (*state).destroyed.get() {
+        drop(Box::from_raw(state));
+    }
+}
+
+/// Registers a class (once) and creates a window of it.
+#[derive(Debug, Clone)]
+pub struct WindowBuilder {
+    class_name: String,
+    title: String,
+    style: WindowStyle,
+    ex_style: WindowExStyle,
+    class_style: ClassStyle,
+    position: Option<(CInt, CInt)>,
+    center_in: Option<Rect>,
+    client_size: Option<Size>,
+    cursor: IDCursor,
+    icon: HICON,
+    small_icon: HICON,
+    menu: HMENU,
+    quit_on_destroy: bool,
+}
+
+impl WindowBuilder {
+    pub fn new(class_name: &str) -> Self {
+        Self {
+            class_name: class_name.to_owned(),
+            title: String::new(),
+            style: WindowStyle::OVERLAPPEDWINDOW,
+            ex_style: WindowExStyle::empty(),
+            class_style: ClassStyle::empty(),
+            position: None,
+            center_in: None,
+            client_size: None,
+            cursor: IDCursor::Arrow,
+            icon: null_mut(),
+            small_icon: null_mut(),
+            menu: null_mut(),
+            quit_on_destroy: true,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_owned();
+        self
+    }
+
+    pub fn style(mut self, style: WindowStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn ex_style(mut self, ex_style: WindowExStyle) -> Self {
+        self.ex_style = ex_style;
+        self
+    }
+
+    /// The flags of the class, only used by whichever builder registers it first.
+    pub fn class_style(mut self, class_style: ClassStyle) -> Self {
+        self.class_style = class_style;
+        self
+    }
+
+    /// The outer top left corner, `CW_USEDEFAULT` if unset.
+    pub fn position(mut self, x: CInt, y: CInt) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Centers the window in the work area of `monitor`, instead of using `position`. Only
+    /// works along with a `client_size`.
+    pub fn centered_on(mut self, monitor: &Monitor) -> Self {
+        self.center_in = Some(monitor.work_area);
+        self
+    }
+
+    /// The size of the client area, the outer size is worked out from the style.
+    pub fn client_size(mut self, width: CInt, height: CInt) -> Self {
+        self.client_size = Some(Size::new(width, height));
+        self
+    }
+
+    pub fn cursor(mut self, cursor: IDCursor) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// The class icon, shown in Alt+Tab and by the taskbar. Like the class style it only counts
+    /// for the builder that registers the class, and must stay alive as long as the class.
+    pub fn icon(mut self, icon: HICON) -> Self {
+        self.icon = icon;
+        self
+    }
+
+    /// The class icon for the caption, Windows shrinks the big one if this is unset.
+    pub fn small_icon(mut self, small_icon: HICON) -> Self {
+        self.small_icon = small_icon;
+        self
+    }
+
+    /// The menu bar, see [`Commands::create_menu_bar`](crate::menu::Commands::create_menu_bar).
+    ///
+    /// The window owns the menu once it is created, so pass it with `OwnedMenu::into_raw`.
+    pub fn menu(mut self, menu: HMENU) -> Self {
+        self.menu = menu;
+        self
+    }
+
+    /// Whether destroying the window posts `WM_QUIT`, on by default.
+    pub fn quit_on_destroy(mut self, quit_on_destroy: bool) -> Self {
+        self.quit_on_destroy = quit_on_destroy;
+        self
+    }
+
+    fn register_class(&self, class_name: &[u16]) -> Result<(), Win32Error> {
+        let wc = WNDCLASSEXW {
+            style: self.class_style.bits(),
+            lpfn_wnd_proc: Some(window_trampoline),
+            h_instance: get_process_handle(),
+            lpsz_class_name: class_name.as_ptr(),
+            h_cursor: load_predefined_cursor(self.cursor)?,
+            h_icon: self.icon,
+            h_icon_sm: self.small_icon,
+            ..Default::default()
+        };
+        match unsafe { register_class_ex(&wc) } {
+            Ok(_) => Ok(()),
+            // Several windows may share a class.
+            Err(Win32Error(ERROR_CLASS_ALREADY_EXISTS)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn outer_size(&self) -> Result<(CInt, CInt), Win32Error> {
+        let Some(size) = self.client_size else {
+            return Ok((CW_USEDEFAULT, CW_USEDEFAULT));
+        };
+        let mut rect: RECT = Rect::from_origin_size(Default::default(), size).into();
+        let has_menu = !self.menu.is_null() as BOOL;
+        if unsafe { AdjustWindowRectEx(&mut rect, self.style.bits(), has_menu, self.ex_style.bits()) } == 0 {
+            return Err(get_last_error());
+        }
+        Ok((rect.right - rect.left, rect.bottom - rect.top))
+    }
+
+    /// Creates the window, it starts out hidden.
+    ///
+    /// Styles that don't go together (see [`validate`]) fail with `ERROR_INVALID_PARAMETER`. If
+    /// the handler panics on one of the creation messages, the panic continues from here.
+    pub fn build(self, handler: impl WindowHandler + 'static) -> Result<Window, Win32Error> {
+        validate(self.style, self.ex_style, self.class_style).map_err(|_| Win32Error(ERROR_INVALID_PARAMETER))?;
+        let class_name = wide_null(&self.class_name);
+        let title = wide_null(&self.title);
+        self.register_class(&class_name)?;
+        let (width, height) = self.outer_size()?;
+        let (x, y) = match (self.center_in, self.client_size) {
+            (Some(area), Some(_)) => {
+                let origin = center(Size::new(width, height), area).origin();
+                (origin.x, origin.y)
+            }
+            _ => self.position.unwrap_or((CW_USEDEFAULT, CW_USEDEFAULT)),
+        };
+        // The window takes the state out of here on `WM_NCCREATE`, if creation fails before that
+        // it is simply dropped with the rest of this function.
+        let mut pending = Some(Box::new(WindowState {
+            handler: Box::new(handler),
+            quit_on_destroy: self.quit_on_destroy,
+            depth: Cell::new(0),
+            destroyed: Cell::new(false),
+            fullscreen: Cell::default(),
+            events: Events::new(),
+        }));
+        let hwnd = unsafe {
+            CreateWindowExW(
+                self.ex_style.bits(),
+                class_name.as_ptr(),
+                title.as_ptr(),
+                self.style.bits(),
+                x,
+                y,
+                width,
+                height,
+                null_mut(),
+                self.menu,
+                get_process_handle(),
+                (&mut pending as *mut Option<Box<WindowState>>).cast(),
+            )
+        };
+        // The handler already got `WM_NCCREATE` and `WM_CREATE`.
+        resume_panic();
+        if hwnd.is_null() {
+            return Err(get_last_error());
+        }
+        Ok(Window { hwnd })
+    }
+}
+
+/// A window created by [`WindowBuilder`].
+///
+/// This is only a handle, the window lives until it is destroyed (closing it does that by
+/// default) and the handler is dropped along with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    hwnd: HWND,
+}
+
+impl Window {
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// See [`ShowWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showwindow)
+    pub fn show(&self) {
+        unsafe { ShowWindow(self.hwnd, SW_SHOW) };
+    }
+
+    pub fn client_rect(&self) -> Result<Rect, Win32Error> {
+        unsafe { get_client_rect(self.hwnd) }
+    }
+
+    /// What the client area shows right now, see [`capture_client`](crate::screenshot::capture_client).
+    pub fn capture_client(&self) -> Result<Image, Win32Error> {
+        unsafe { crate::screenshot::capture_client(self.hwnd) }
+    }
+
+    /// Marks the whole client area for repainting, see [`InvalidateRect`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-invalidaterect)
+    ///
+    /// The `WM_PAINT` comes once the message queue is otherwise empty, several invalidations
+    /// before that make a single paint.
+    pub fn invalidate(&self) -> Result<(), Win32Error> {
+        unsafe { invalidate(self.hwnd, None) }
+    }
+
+    /// Marks part of the client area for repainting.
+    pub fn invalidate_rect(&self, rect: Rect) -> Result<(), Win32Error> {
+        unsafe { invalidate(self.hwnd, Some(rect)) }
+    }
+
+    /// Paints right away if anything is invalid, see [`UpdateWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-updatewindow)
+    pub fn update(&self) -> Result<(), Win32Error> {
+        if unsafe { UpdateWindow(self.hwnd) } != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+
+    /// Whether files dropped from Explorer arrive as `WM_DROPFILES`, see
+    /// [`FilesDropped`](crate::drag_drop::FilesDropped).
+    pub fn accept_files(&self, accept: bool) {
+        unsafe { crate::drag_drop::accept_files(self.hwnd, accept) };
+    }
+
+    /// Changes the big or small icon of this window only, returning the previous one, see
+    /// [`WM_SETICON`](https://docs.microsoft.com/en-us/windows/win32/winmsg/wm-seticon)
+    ///
+    /// The window doesn't take ownership, keep the icon alive while it is shown.
+    pub fn set_icon(&self, size: IconSize, icon: &OwnedIcon) -> HICON {
+        unsafe { set_icon(self.hwnd, size, icon.handle()) }
+    }
+
+    /// The DPI of the monitor the window is on, see [`GetDpiForWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getdpiforwindow)
+    pub fn dpi(&self) -> u32 {
+        unsafe { GetDpiForWindow(self.hwnd) }
+    }
+
+    /// The monitor showing most of the window.
+    pub fn monitor(&self) -> Result<Monitor, Win32Error> {
+        unsafe { Monitor::from_window(self.hwnd) }
+    }
+
+    /// Switches between a borderless window covering its monitor and the window as it was
+    /// before, see [`Fullscreen`].
+    pub fn set_fullscreen(&self, fullscreen: bool) -> Result<(), Win32Error> {
+        let state = self.state_ptr()?;
+        // Switching sends `WM_SIZE` and friends, whose handler may destroy the window. Counting
+        // as a call like the trampoline does keeps the state alive until it is put back.
+        unsafe {
+            (*state).depth.set((*state).depth.get() + 1);
+            let mut current = (*state).fullscreen.take();
+            let result = current.set(self.hwnd, fullscreen);
+            (*state).fullscreen.set(current);
+            leave(state);
+            resume_panic();
+            result
+        }
+    }
+
+    /// Whether the window is fullscreen. While switching this is still the old state.
+    pub fn is_fullscreen(&self) -> bool {
+        self.state().is_ok_and(|state| {
+            let current = state.fullscreen.take();
+            let active = current.is_active();
+            state.fullscreen.set(current);
+            active
+        })
+    }
+
+    /// Every message the window gets from now on, before its handler sees it.
+    pub fn events(&self) -> Result<Events<WindowEvent>, Win32Error> {
+        Ok(self.state()?.events.clone())
+    }
+
+    /// The next `msg` the window gets, e.g. `window.next_message(WM_LBUTTONUP)?.await`.
+    pub fn next_message(&self, msg: UINT) -> Result<NextEvent<WindowEvent>, Win32Error> {
+        Ok(self.events()?.wait_for(move |event| event.msg == msg))
+    }
+
+    /// The state the trampoline keeps, until the window is destroyed.
+    fn state(&self) -> Result<&WindowState, Win32Error> {
+        // Only used within a method call that sends no messages, so `WM_NCDESTROY` can't free it.
+        self.state_ptr().map(|state| unsafe { &*state })
+    }
+
+    fn state_ptr(&self) -> Result<*mut WindowState, Win32Error> {
+        let state = unsafe { GetWindowLongPtrW(self.hwnd, GWLP_USERDATA) } as *mut WindowState;
+        if state.is_null() {
+            Err(Win32Error(ERROR_INVALID_WINDOW_HANDLE))
+        } else {
+            Ok(state)
+        }
+    }
+
+    /// See [`DestroyWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroywindow)
+    pub fn destroy(&self) -> Result<(), Win32Error> {
+        let destroyed = unsafe { DestroyWindow(self.hwnd) };
+        resume_panic();
+        if destroyed != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+}
+
+/// Which of a window's two icons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSize {
+    /// The caption icon, 16 pixels at 96 DPI.
+    Small,
+    /// The Alt+Tab and taskbar icon, 32 pixels at 96 DPI.
+    Big,
+}
+
+impl IconSize {
+    /// The size at 96 DPI, see [`icon_size`](crate::icon::icon_size) for other DPIs.
+    pub fn base_size(self) -> u32 {
+        match self {
+            IconSize::Small => 16,
+            IconSize::Big => 32,
+        }
+    }
+}
+
+/// Sends `WM_SETICON`, returning the previous icon.
+///
+/// # Safety
+/// `hwnd` must be a window handle, [`Window::set_icon`] is the safe way.
+pub unsafe fn set_icon(hwnd: HWND, size: IconSize, icon: HICON) -> HICON {
+    let which = match size {
+        IconSize::Small => ICON_SMALL,
+        IconSize::Big => ICON_BIG,
+    };
+    SendMessageW(hwnd, WM_SETICON, which, icon as LPARAM) as HICON
+}
+
+/// Invalidates `rect` of `hwnd`, or all of it, without erasing the background.
+///
+/// # Safety
+/// `hwnd` must be a window handle, [`Window::invalidate`] is the safe way.
+pub unsafe fn invalidate(hwnd: HWND, rect: Option<Rect>) -> Result<(), Win32Error> {
+    let rect: Option<RECT> = rect.map(Into::into);
+    let rect_ptr = rect.as_ref().map_or(null(), |rect| rect as *const RECT);
+    if InvalidateRect(hwnd, rect_ptr, 0) != 0 {
+        Ok(())
+    } else {
+        Err(get_last_error())
+    }
+}
+
+unsafe fn translate_and_dispatch(msg: &MSG) {
+    TranslateMessage(msg);
+    DispatchMessageW(msg);
+    resume_panic();
+}
+
+/// Waits for and dispatches messages until `WM_QUIT`, returning its exit code.
+pub fn run_message_loop() -> Result<CInt, Win32Error> {
+    let mut msg = MSG::default();
+    loop {
+        match unsafe { GetMessageW(&mut msg, null_mut(), 0, 0) } {
+            0 => return Ok(msg.w_param as CInt),
+            -1 => return Err(get_last_error()),
+            _ => unsafe { translate_and_dispatch(&msg) },
+        }
+    }
+}
+
+/// Like [`run_message_loop`], but the shortcuts of `accelerators` become `WM_COMMAND` messages to
+/// `window` instead of key presses.
+///
+/// The table is checked before `TranslateMessage`, so a shortcut never also produces a `WM_CHAR`.
+pub fn run_message_loop_with_accelerators(window: &Window, accelerators: &AcceleratorTable) -> Result<CInt, Win32Error> {
+    let mut msg = MSG::default();
+    loop {
+        match unsafe { GetMessageW(&mut msg, null_mut(), 0, 0) } {
+            0 => return Ok(msg.w_param as CInt),
+            -1 => return Err(get_last_error()),
+            // The `WM_COMMAND` was sent, so the handler already ran.
+            _ if unsafe { accelerators.translate(window.hwnd, &msg) } => resume_panic(),
+            _ => unsafe { translate_and_dispatch(&msg) },
+        }
+    }
+}
+
+/// Like [`run_message_loop`], but runs the tasks of `executor` in between messages.
+///
+/// The wait is alertable, so APCs (like completed overlapped I/O) run as well.
+pub fn run_message_loop_with_executor(executor: &Executor) -> Result<CInt, Win32Error> {
+    loop {
+        if let Some(code) = pump_messages() {
+            return Ok(code);
+        }
+        executor.run_ready();
+        let timeout = executor.wait_millis().unwrap_or(INFINITE);
+        let flags = MWMO_ALERTABLE | MWMO_INPUTAVAILABLE;
+        if unsafe { MsgWaitForMultipleObjectsEx(0, null(), timeout, QS_ALLINPUT, flags) } == WAIT_FAILED {
+            return Err(get_last_error());
+        }
+    }
+}
+
+/// Dispatches every message already queued without waiting for more.
+///
+/// Returns the exit code once `WM_QUIT` is seen, for loops that render continuously.
+pub fn pump_messages() -> Option<CInt> {
+    let mut msg = MSG::default();
+    while unsafe { PeekMessageW(&mut msg, null_mut(), 0, 0, PM_REMOVE) } != 0 {
+        if msg.message == WM_QUIT {
+            return Some(msg.w_param as CInt);
+        }
+        unsafe { translate_and_dispatch(&msg) };
+    }
+    None
+}
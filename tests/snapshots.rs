@@ -0,0 +1,24 @@
+//! Golden image tests for the library's painting code, see `triangle_from_scratch::snapshot`.
+//!
+//! Run with `SNAPSHOT_BLESS=1 cargo test --test snapshots` after an intended change in output.
+
+use triangle_from_scratch::demo::{paint_triangle, render_cube};
+use triangle_from_scratch::geometry::{Rect, Size};
+use triangle_from_scratch::snapshot::Snapshots;
+
+fn snapshots() -> Snapshots {
+    Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"))
+}
+
+#[test]
+fn triangle() {
+    let size = Size::new(320, 160);
+    snapshots().assert_paint("triangle", size, |canvas| {
+        paint_triangle(canvas, Rect::from_origin_size(Default::default(), size))
+    });
+}
+
+#[test]
+fn cube() {
+    snapshots().assert_matches("cube", &render_cube(240, 180, 0.6));
+}